@@ -0,0 +1,647 @@
+// Headless CLI
+// `dark-gpt <command>` runs the same code as the Tauri commands without a
+// webview, for servers and SSH sessions. With no subcommand the desktop app
+// starts as usual.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use tracing_subscriber::EnvFilter;
+
+use crate::commands::docker::{self, DockerStatus};
+use crate::commands::health::{HealthReport, HealthStatus, ServiceHealth};
+use crate::commands::ollama::{self, OllamaStatus};
+use crate::commands::{backup, health, installer, ports, setup};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::audit::{self, Verification};
+use crate::services::container::{self, RuntimeKind};
+use crate::services::host::Host;
+use crate::services::metrics::Metrics;
+use crate::services::settings::{AppSettings, SettingsStore};
+use crate::utils::paths::AppPaths;
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0      success
+  1      internal error
+  2      invalid input or usage
+  10-13  container runtime: not installed, not running, compose failed, port in use
+  20-21  Ollama: unreachable, model not found
+  30-31  network: request failed, integrity check failed
+  40-44  system: timeout, I/O, permission denied, busy, cancelled";
+
+#[derive(Debug, Parser)]
+#[command(name = "dark-gpt", version, about, after_help = EXIT_CODES)]
+struct Cli {
+    /// Print JSON on stdout instead of text
+    #[arg(long, global = true)]
+    json: bool,
+    /// Log debug output to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Container runtime, Ollama and service health
+    Status,
+    /// Start the compose stack
+    Start,
+    /// Stop the compose stack
+    Stop,
+    /// Show service logs
+    Logs {
+        /// Compose service (all services when omitted)
+        service: Option<String>,
+        /// Lines of history to show
+        #[arg(short = 'n', long, default_value_t = 100)]
+        lines: u32,
+        /// Keep streaming new lines until interrupted (raw output, even with --json)
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Manage Ollama models
+    #[command(subcommand)]
+    Models(ModelsCommand),
+    /// Check the setup and suggest fixes
+    Doctor,
+    /// Back up settings, app data and the stack's volumes
+    Backup {
+        /// Directory to create the backup folder in
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+    /// Check or export the audit log of administrative actions
+    #[command(subcommand)]
+    Audit(AuditCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum ModelsCommand {
+    /// List installed models
+    List,
+    /// Download a model
+    Pull { name: String },
+    /// Delete a model
+    Rm { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum AuditCommand {
+    /// Check the hash chain for edits and truncation
+    Verify,
+    /// Copy the log (and its head, as `<output>.head`)
+    Export {
+        #[arg(short, long, default_value = "dark-gpt-audit.jsonl")]
+        output: PathBuf,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+    container_runtime: RuntimeKind,
+    runtime: DockerStatus,
+    ollama: OllamaStatus,
+    health: HealthReport,
+    profile: String,
+    default_model: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    name: &'static str,
+    ok: bool,
+    detail: String,
+    error: Option<AppError>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok: true,
+            detail: detail.into(),
+            error: None,
+        }
+    }
+
+    fn failed(name: &'static str, error: AppError) -> Self {
+        Self {
+            name,
+            ok: false,
+            detail: error.message.clone(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Run the subcommand in the process arguments and return its exit status,
+/// or `None` when there is none and the desktop app should start
+pub fn run() -> Option<i32> {
+    // No arguments, or a deep link (`dark-gpt://...`) for the desktop app
+    match std::env::args().nth(1) {
+        None => return None,
+        Some(arg) if arg.contains("://") => return None,
+        Some(_) => {}
+    }
+    attach_console();
+    // Exits by itself for --help, --version and usage errors
+    let cli = Cli::parse();
+    let command = cli.command?;
+    init_logging(cli.verbose);
+
+    let paths = match AppPaths::headless(&crate::context()) {
+        Ok(paths) => paths,
+        Err(e) => return Some(fail(cli.json, &e.into())),
+    };
+    let settings = SettingsStore::load(&paths.config_dir).get();
+    audit::init(&paths.data_dir, &paths.config_dir, audit::Source::Cli);
+
+    let host = Host::system();
+    host.set_runtime_preference(settings.container_runtime);
+    host.activate(&settings.profile());
+    host.set_certs_dir(paths.certs_dir());
+    let result =
+        tauri::async_runtime::block_on(execute(command, &host, &paths, &settings, cli.json));
+    Some(match result {
+        Ok(code) => code,
+        Err(e) => fail(cli.json, &e),
+    })
+}
+
+async fn execute(
+    command: Command,
+    host: &Host,
+    paths: &AppPaths,
+    settings: &AppSettings,
+    json: bool,
+) -> AppResult<i32> {
+    let compose_file = paths.compose_file();
+    match command {
+        Command::Status => {
+            let status = status(host, settings).await?;
+            print(json, &status, human_status);
+        }
+        Command::Start => {
+            let result = docker::start(host, &compose_file).await;
+            let services = audit::recorded("services.start", serde_json::json!({}), result)?;
+            print(json, &services, |services| {
+                let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+                format!("Started {}", names.join(", "))
+            });
+        }
+        Command::Stop => {
+            let result = docker::stop(host, &compose_file).await;
+            audit::recorded("services.stop", serde_json::json!({}), result)?;
+            print(json, &serde_json::json!({ "stopped": true }), |_| {
+                "Services stopped".to_string()
+            });
+        }
+        Command::Logs {
+            service,
+            lines,
+            follow,
+        } => {
+            if follow {
+                docker::follow_service_logs(host, &compose_file, service.as_deref(), lines).await?;
+            } else {
+                let logs =
+                    docker::service_logs(host, &compose_file, service.as_deref(), lines).await?;
+                let value = serde_json::json!({ "service": service, "logs": logs });
+                print(json, &value, |_| logs.trim_end().to_string());
+            }
+        }
+        Command::Models(command) => return models(host, command, json).await,
+        Command::Audit(AuditCommand::Verify) => {
+            let verification = audit::with_log(|log| log.verify())?;
+            print(json, &verification, human_verification);
+            return Ok(integrity_exit_code(&verification));
+        }
+        Command::Audit(AuditCommand::Export { output }) => {
+            let result = audit::with_log(|log| log.export(&output)).map_err(Into::into);
+            let details = serde_json::json!({ "path": output });
+            let verification = audit::recorded("audit.export", details, result)?;
+            print(json, &verification, |verification| {
+                format!(
+                    "Exported to {}\n{}",
+                    output.display(),
+                    human_verification(verification)
+                )
+            });
+            return Ok(integrity_exit_code(&verification));
+        }
+        Command::Doctor => {
+            let checks = doctor(host, &compose_file, settings).await?;
+            print(json, &checks, |checks| human_checks(checks));
+            return Ok(checks
+                .iter()
+                .find_map(|c| c.error.as_ref())
+                .map_or(0, |e| e.code.exit_code()));
+        }
+        Command::Backup { output } => {
+            let result = backup::backup(host, paths, &output).await;
+            let details = serde_json::json!({ "destination": output });
+            let summary = audit::recorded("backup.create", details, result)?;
+            print(json, &summary, |summary| {
+                let mut text = format!(
+                    "Backup written to {} ({} files, {} volumes, {})",
+                    summary.path,
+                    summary.files.len(),
+                    summary.volumes.len(),
+                    installer::format_bytes(summary.total_bytes)
+                );
+                for warning in &summary.warnings {
+                    text.push_str(&format!("\nwarning: {}", warning));
+                }
+                text
+            });
+        }
+    }
+    Ok(0)
+}
+
+async fn models(host: &Host, command: ModelsCommand, json: bool) -> AppResult<i32> {
+    match command {
+        ModelsCommand::List => {
+            let models = ollama::models(host).await?;
+            print(json, &models, |models| {
+                if models.is_empty() {
+                    return "No models installed".to_string();
+                }
+                let width = models.iter().map(|m| m.name.len()).max().unwrap_or(0);
+                models
+                    .iter()
+                    .map(|m| {
+                        format!(
+                            "{:width$}  {:>9}  {}",
+                            m.name,
+                            installer::format_bytes(m.size),
+                            m.modified_at,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        ModelsCommand::Pull { name } => {
+            let metrics = Metrics::default();
+            let result = ollama::pull(host, &metrics, &name, |progress| {
+                if !json {
+                    let mut stderr = std::io::stderr();
+                    let _ = write!(
+                        stderr,
+                        "\r\x1b[2K{} {:.0}%",
+                        progress.status, progress.percent
+                    );
+                    let _ = stderr.flush();
+                }
+            })
+            .await
+            .inspect_err(|_| clear_progress(json));
+            audit::recorded("model.pull", serde_json::json!({ "model": name }), result)?;
+            clear_progress(json);
+            print(json, &serde_json::json!({ "pulled": name }), |_| {
+                format!("Pulled {}", name)
+            });
+        }
+        ModelsCommand::Rm { name } => {
+            let result = ollama::delete_model(host, &name).await;
+            audit::recorded("model.delete", serde_json::json!({ "model": name }), result)?;
+            print(json, &serde_json::json!({ "deleted": name }), |_| {
+                format!("Deleted {}", name)
+            });
+        }
+    }
+    Ok(0)
+}
+
+async fn status(host: &Host, settings: &AppSettings) -> AppResult<Status> {
+    let runtime = container::current(host).await;
+    let (docker, ollama, health) = tokio::join!(
+        docker::status(host),
+        ollama::status(host),
+        health::collect_health(host),
+    );
+    Ok(Status {
+        container_runtime: runtime.kind(),
+        runtime: docker?,
+        ollama: ollama?,
+        health: health?.0,
+        profile: settings.active_profile.clone(),
+        default_model: settings.profile().default_model,
+    })
+}
+
+/// Everything the setup wizard would catch, as independent checks
+async fn doctor(host: &Host, compose_file: &Path, settings: &AppSettings) -> AppResult<Vec<Check>> {
+    let prereqs = setup::prerequisites(host).await?;
+    let runtime = container::current(host).await;
+    let name = runtime.display_name();
+    let mut checks = Vec::new();
+
+    let status = prereqs.runtime();
+    let install_hint = |url: &Option<String>| match url {
+        Some(url) => format!("Install it from {}, or run the desktop setup wizard.", url),
+        None => "Install it, or run the desktop setup wizard.".to_string(),
+    };
+    checks.push(if !status.installed {
+        Check::failed(
+            "Container runtime",
+            AppError::new(
+                ErrorCode::DockerNotInstalled,
+                format!("{} is not installed", name),
+            )
+            .remediation(install_hint(&status.download_url)),
+        )
+    } else if !status.running {
+        Check::failed(
+            "Container runtime",
+            AppError::new(ErrorCode::DaemonDown, format!("{} is not running", name))
+                .remediation(format!("Start {}.", name)),
+        )
+    } else {
+        Check::ok(
+            "Container runtime",
+            format!("{} {}", name, status.version.as_deref().unwrap_or("")),
+        )
+    });
+
+    checks.push(if compose_file.exists() {
+        Check::ok("Compose file", compose_file.to_string_lossy())
+    } else {
+        Check::failed(
+            "Compose file",
+            AppError::new(
+                ErrorCode::Io,
+                format!("docker-compose.yml not found at {:?}", compose_file),
+            )
+            .remediation("Reinstall Dark-GPT; the bundled compose file is missing."),
+        )
+    });
+
+    if status.running && compose_file.exists() {
+        let conflicts = ports::find_port_conflicts(runtime.as_ref(), compose_file).await;
+        checks.push(if conflicts.is_empty() {
+            Check::ok("Ports", "All required ports are free")
+        } else {
+            Check::failed("Ports", docker::port_conflict(conflicts))
+        });
+    }
+
+    let ollama = &prereqs.ollama;
+    checks.push(if !ollama.installed {
+        Check::failed(
+            "Ollama",
+            AppError::new(ErrorCode::OllamaUnreachable, "Ollama is not installed")
+                .remediation(install_hint(&ollama.download_url)),
+        )
+    } else if !ollama.running {
+        Check::failed("Ollama", AppError::ollama_unreachable("not running"))
+    } else {
+        Check::ok(
+            "Ollama",
+            format!("Ollama {}", ollama.version.as_deref().unwrap_or("")),
+        )
+    });
+
+    if ollama.running {
+        let model = &settings.profile().default_model;
+        let base = |name: &str| name.strip_suffix(":latest").unwrap_or(name).to_string();
+        let installed = prereqs
+            .installed_models
+            .iter()
+            .any(|m| base(m) == base(model));
+        checks.push(if installed {
+            Check::ok("Default model", model.as_str())
+        } else {
+            Check::failed(
+                "Default model",
+                AppError::new(
+                    ErrorCode::ModelNotFound,
+                    format!("{} is not installed", model),
+                )
+                .remediation(format!("Run `dark-gpt models pull {}`.", model)),
+            )
+        });
+    }
+
+    checks.push(if prereqs.https_configured {
+        Check::ok(
+            "HTTPS",
+            format!("{} is in the hosts file", setup::https_domain(host)),
+        )
+    } else {
+        Check::failed(
+            "HTTPS",
+            AppError::new(
+                ErrorCode::InvalidInput,
+                format!(
+                    "{} is missing from the hosts file",
+                    setup::https_domain(host)
+                ),
+            )
+            .remediation(format!(
+                "Add \"127.0.0.1 {}\" to your hosts file (needs administrator rights).",
+                setup::https_domain(host)
+            )),
+        )
+    });
+
+    Ok(checks)
+}
+
+fn print<T: Serialize>(json: bool, value: &T, human: impl FnOnce(&T) -> String) {
+    if json {
+        match serde_json::to_string_pretty(value) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("error: failed to serialize output: {}", e),
+        }
+    } else {
+        println!("{}", human(value));
+    }
+}
+
+/// Report an error and return the exit status for its category
+fn fail(json: bool, error: &AppError) -> i32 {
+    if json {
+        print(true, &serde_json::json!({ "error": error }), |_| {
+            String::new()
+        });
+    } else {
+        eprintln!("error: {}", error.describe());
+    }
+    error.code.exit_code()
+}
+
+fn clear_progress(json: bool) {
+    if !json {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+fn human_status(status: &Status) -> String {
+    let version = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+    let state = |installed: bool, running: bool| match (installed, running) {
+        (_, true) => "running",
+        (true, false) => "stopped",
+        (false, false) => "not installed",
+    };
+    let runtime = match status.container_runtime {
+        RuntimeKind::Docker => "Docker",
+        RuntimeKind::Podman => "Podman",
+    };
+
+    let mut lines = vec![
+        format!(
+            "{:<18}{} {} ({})",
+            "Container runtime",
+            runtime,
+            version(&status.runtime.version),
+            state(status.runtime.installed, status.runtime.running)
+        ),
+        format!(
+            "{:<18}{} ({})",
+            "Ollama",
+            version(&status.ollama.version),
+            state(status.ollama.installed, status.ollama.running)
+        ),
+        format!("{:<18}{}", "Profile", status.profile),
+        format!("{:<18}{}", "Default model", status.default_model),
+        "Services".to_string(),
+    ];
+    let health = &status.health;
+    for service in [&health.docker, &health.ollama, &health.webui, &health.caddy] {
+        lines.push(human_health(service));
+    }
+    lines.join("\n")
+}
+
+fn human_health(service: &ServiceHealth) -> String {
+    let mark = match service.status {
+        HealthStatus::Healthy => "ok",
+        HealthStatus::Unhealthy => "!!",
+        HealthStatus::Unknown => "??",
+    };
+    format!(
+        "  [{}] {:<12}{}",
+        mark,
+        service.name,
+        service.message.as_deref().unwrap_or("")
+    )
+}
+
+fn human_verification(verification: &Verification) -> String {
+    match &verification.problem {
+        None => format!("Audit log intact ({} entries)", verification.entries),
+        Some(problem) => {
+            let location = problem
+                .line
+                .map(|line| format!(" at line {}", line))
+                .unwrap_or_default();
+            format!(
+                "Audit log tampered with{}: {} ({} entries verified before it)",
+                location, problem.message, verification.entries
+            )
+        }
+    }
+}
+
+fn integrity_exit_code(verification: &Verification) -> i32 {
+    match verification.valid {
+        true => 0,
+        false => ErrorCode::IntegrityCheckFailed.exit_code(),
+    }
+}
+
+fn human_checks(checks: &[Check]) -> String {
+    let mut lines = Vec::new();
+    for check in checks {
+        let mark = if check.ok { "ok" } else { "!!" };
+        lines.push(format!(
+            "[{}] {:<18}{}",
+            mark,
+            check.name,
+            check.detail.trim()
+        ));
+        if let Some(remediation) = check.error.as_ref().and_then(|e| e.remediation.as_ref()) {
+            for line in remediation.lines() {
+                lines.push(format!("     {}", line));
+            }
+        }
+    }
+    let failed = checks.iter().filter(|c| !c.ok).count();
+    lines.push(String::new());
+    lines.push(match failed {
+        0 => "Everything looks good.".to_string(),
+        n => format!("{} problem(s) found.", n),
+    });
+    lines.join("\n")
+}
+
+fn init_logging(verbose: bool) {
+    let default = if verbose {
+        "dark_gpt=debug"
+    } else {
+        "dark_gpt=warn"
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(filter)
+        .init();
+}
+
+/// Release builds use the Windows GUI subsystem and start without a
+/// console; write to the terminal we were started from instead
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn arguments_are_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_subcommands_and_global_flags() {
+        let cli = Cli::parse_from(["dark-gpt", "logs", "webui", "-f", "-n", "20", "--json"]);
+        assert!(cli.json);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Logs { service: Some(ref s), lines: 20, follow: true }) if s == "webui"
+        ));
+
+        let cli = Cli::parse_from(["dark-gpt", "models", "pull", "llama3"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Models(ModelsCommand::Pull { ref name })) if name == "llama3"
+        ));
+
+        let cli = Cli::parse_from(["dark-gpt", "audit", "export", "-o", "audit.jsonl"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Audit(AuditCommand::Export { ref output })) if output.ends_with("audit.jsonl")
+        ));
+
+        assert!(Cli::parse_from(["dark-gpt"]).command.is_none());
+    }
+}
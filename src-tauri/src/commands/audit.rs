@@ -0,0 +1,43 @@
+// Audit log commands
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppResult;
+use crate::services::audit::{self, Verification};
+
+#[derive(Debug, Serialize)]
+pub struct AuditExport {
+    pub path: String,
+    pub verification: Verification,
+}
+
+/// Check the audit log's hash chain against its head
+#[tauri::command]
+pub fn verify_audit_log() -> AppResult<Verification> {
+    Ok(audit::with_log(|log| log.verify())?)
+}
+
+/// Copy the audit log (and its head, as `<path>.head`) out of the app.
+/// `destination` defaults to the user's download directory.
+#[tauri::command]
+pub fn export_audit_log(app: AppHandle, destination: Option<String>) -> AppResult<AuditExport> {
+    let dest = match destination {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .path()
+            .download_dir()
+            .or_else(|_| app.path().app_data_dir())
+            .map_err(|e| format!("No destination directory: {}", e))?
+            .join("dark-gpt-audit.jsonl"),
+    };
+    let path = dest.display().to_string();
+    audit::recorded(
+        "audit.export",
+        serde_json::json!({ "path": path }),
+        audit::with_log(|log| log.export(&dest)).map_err(Into::into),
+    )
+    .map(|verification| AuditExport { path, verification })
+}
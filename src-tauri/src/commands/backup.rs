@@ -0,0 +1,250 @@
+// Backup commands
+// Copies settings, app data and the stack's named volumes (chat history,
+// Caddy data) into a timestamped folder
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::time::{timeout, Duration};
+
+use super::docker;
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::audit;
+use crate::services::container::{self, ContainerRuntime};
+use crate::services::host::Host;
+use crate::services::settings;
+use crate::utils::paths::AppPaths;
+use crate::utils::time::unix_now;
+
+/// Small image with `tar`, used to read volumes without touching the stack
+const BACKUP_IMAGE: &str = "docker.io/library/alpine:3";
+const VOLUME_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Serialize)]
+pub struct BackupSummary {
+    /// Folder the backup was written to
+    pub path: String,
+    /// Copied files, relative to `path`
+    pub files: Vec<String>,
+    /// Volumes exported as `volumes/<name>.tar`
+    pub volumes: Vec<String>,
+    pub total_bytes: u64,
+    /// Parts that were skipped, with the reason
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest<'a> {
+    app_version: &'a str,
+    created_unix: u64,
+    files: &'a [String],
+    volumes: &'a [String],
+}
+
+/// Write a backup folder and describe what went into it.
+/// `destination` defaults to the user's download directory.
+#[tauri::command]
+pub async fn create_backup(
+    app: AppHandle,
+    destination: Option<String>,
+) -> AppResult<BackupSummary> {
+    let paths = AppPaths::from_app(&app)?;
+    let destination = match destination {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .path()
+            .download_dir()
+            .or_else(|_| app.path().home_dir())
+            .map_err(|e| format!("No destination directory: {}", e))?,
+    };
+    let result = backup(&app.state::<Host>(), &paths, &destination).await;
+    audit::recorded(
+        "backup.create",
+        serde_json::json!({ "destination": destination }),
+        result,
+    )
+}
+
+/// Back up into a new `dark-gpt-backup-<unix time>` folder under
+/// `destination`; shared with the CLI
+pub(crate) async fn backup(
+    host: &Host,
+    paths: &AppPaths,
+    destination: &Path,
+) -> AppResult<BackupSummary> {
+    let created_unix = unix_now();
+    let dir = destination.join(format!("dark-gpt-backup-{}", created_unix));
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| AppError::new(ErrorCode::Io, format!("Cannot create {:?}", dir)).cause(e))?;
+    tracing::info!("Writing backup to {:?}", dir);
+
+    let mut files = Vec::new();
+    let mut warnings = Vec::new();
+    for (name, source) in [("config", &paths.config_dir), ("data", &paths.data_dir)] {
+        if source.exists() {
+            copy_dir(source, &dir.join(name), Path::new(name), &dir, &mut files)?;
+        }
+    }
+    // Keys stay on this machine; they are entered again after a restore
+    settings::strip_secrets(&dir.join("config"))?;
+
+    let runtime = container::current(host).await;
+    let compose_file = paths.compose_file();
+    let volumes = match project_volumes(runtime.as_ref(), &compose_file).await {
+        Ok(volumes) => volumes,
+        Err(e) => {
+            warnings.push(format!(
+                "{} not available, volumes were not backed up: {}",
+                runtime.display_name(),
+                e
+            ));
+            vec![]
+        }
+    };
+    if !volumes.is_empty() && services_running(runtime.as_ref(), &compose_file).await {
+        warnings.push(
+            "Services were running, so chat history may have been mid-write. \
+             Stop them first for a consistent copy."
+                .to_string(),
+        );
+    }
+
+    let mut exported = Vec::new();
+    if !volumes.is_empty() {
+        std::fs::create_dir_all(dir.join("volumes"))?;
+    }
+    for volume in volumes {
+        let dest = dir.join("volumes").join(format!("{}.tar", volume));
+        match export_volume(runtime.as_ref(), &volume, &dest).await {
+            Ok(()) => exported.push(volume),
+            Err(e) => {
+                let _ = std::fs::remove_file(&dest);
+                warnings.push(format!("Volume {}: {}", volume, e));
+            }
+        }
+    }
+
+    let manifest = Manifest {
+        app_version: env!("CARGO_PKG_VERSION"),
+        created_unix,
+        files: &files,
+        volumes: &exported,
+    };
+    let manifest = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    std::fs::write(dir.join("manifest.json"), manifest)?;
+
+    tracing::info!(
+        "Backup written: {} files, {} volumes",
+        files.len(),
+        exported.len()
+    );
+    Ok(BackupSummary {
+        path: dir.to_string_lossy().to_string(),
+        total_bytes: dir_size(&dir),
+        files,
+        volumes: exported,
+        warnings,
+    })
+}
+
+/// Named volumes of the compose project that exist
+async fn project_volumes(
+    runtime: &dyn ContainerRuntime,
+    compose_file: &Path,
+) -> Result<Vec<String>, String> {
+    let config = docker::rendered_compose_config(runtime, compose_file).await?;
+    let mut volumes = Vec::new();
+    for name in docker::compose_names(&config, "volumes") {
+        let exists = runtime
+            .command()
+            .args(["volume", "inspect", &name])
+            .output()
+            .await
+            .is_ok_and(|o| o.status.success());
+        if exists {
+            volumes.push(name);
+        }
+    }
+    Ok(volumes)
+}
+
+async fn services_running(runtime: &dyn ContainerRuntime, compose_file: &Path) -> bool {
+    runtime
+        .compose(compose_file)
+        .args(["ps", "-q", "--status", "running"])
+        .output()
+        .await
+        .is_ok_and(|o| o.status.success() && !o.stdout.trim_ascii().is_empty())
+}
+
+/// Stream a volume's contents as a tar file through a throwaway container
+async fn export_volume(
+    runtime: &dyn ContainerRuntime,
+    volume: &str,
+    dest: &Path,
+) -> Result<(), String> {
+    let file = std::fs::File::create(dest).map_err(|e| format!("Cannot create file: {}", e))?;
+    let mount = format!("{}:/data:ro", volume);
+    let output = timeout(
+        VOLUME_TIMEOUT,
+        runtime
+            .command()
+            .args(["run", "--rm", "-v", &mount, BACKUP_IMAGE])
+            .args(["tar", "-cf", "-", "-C", "/data", "."])
+            .to_tokio()
+            .stdout(Stdio::from(file))
+            .stderr(Stdio::piped())
+            .output(),
+    )
+    .await
+    .map_err(|_| "Timed out".to_string())?
+    .map_err(|e| format!("Failed to run {}: {}", runtime.display_name(), e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+    Ok(())
+}
+
+/// Copy a directory tree, recording each file as `prefix/...`. `exclude`
+/// is the backup folder itself, in case it was put inside the tree.
+fn copy_dir(
+    from: &Path,
+    to: &Path,
+    prefix: &Path,
+    exclude: &Path,
+    files: &mut Vec<String>,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if entry.path() == exclude {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            let prefix = prefix.join(&name);
+            copy_dir(&entry.path(), &to.join(&name), &prefix, exclude, files)?;
+        } else {
+            std::fs::copy(entry.path(), to.join(&name))?;
+            files.push(prefix.join(&name).to_string_lossy().to_string());
+        }
+    }
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> u64 {
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
@@ -0,0 +1,396 @@
+// Model throughput benchmark
+// Runs fixed benign prompts through /api/generate and keeps a local history
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Window};
+use tokio::io::AsyncWriteExt;
+use tokio::time::{Duration, Instant};
+
+use super::gpu::{self, GpuDevice};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::host::Host;
+use crate::utils::time::unix_now;
+
+const HISTORY_FILE: &str = "benchmarks.jsonl";
+const DEFAULT_REPETITIONS: u32 = 3;
+const MAX_REPETITIONS: u32 = 20;
+
+/// Fixed prompts and sampling options so runs are comparable over time
+const PROMPTS: [&str; 3] = [
+    "Explain in two sentences how a rainbow forms.",
+    "Write a short haiku about autumn leaves.",
+    "List three practical tips for keeping houseplants healthy.",
+];
+const SEED: u64 = 42;
+const NUM_PREDICT: u32 = 128;
+const NUM_CTX: u32 = 2048;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkSample {
+    pub prompt_index: usize,
+    pub repetition: u32,
+    pub load_ms: f64,
+    pub ttft_ms: f64,
+    pub prompt_eval_tps: f64,
+    pub eval_tps: f64,
+    pub eval_count: u64,
+    pub total_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkHardware {
+    pub os: String,
+    pub arch: String,
+    pub gpus: Vec<GpuDevice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub model: String,
+    pub quantization: Option<String>,
+    pub parameter_size: Option<String>,
+    pub started_at: u64,
+    pub repetitions: u32,
+    pub hardware: BenchmarkHardware,
+    /// Cold loads only: the first prompt of each repetition
+    pub load_ms: Percentiles,
+    pub ttft_ms: Percentiles,
+    pub prompt_eval_tps: Percentiles,
+    pub eval_tps: Percentiles,
+    pub samples: Vec<BenchmarkSample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkProgress {
+    pub model: String,
+    pub completed: u32,
+    pub total: u32,
+}
+
+/// Benchmark a model and append the result to the local history
+#[tauri::command]
+pub async fn benchmark_model(
+    window: Window,
+    model_name: String,
+    repetitions: Option<u32>,
+) -> AppResult<BenchmarkResult> {
+    let repetitions = repetitions
+        .unwrap_or(DEFAULT_REPETITIONS)
+        .clamp(1, MAX_REPETITIONS);
+    tracing::info!("Benchmarking {} ({} repetitions)", model_name, repetitions);
+    let host = window.state::<Host>().inner().clone();
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(600))
+        .build()
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+
+    let (details, gpu) = tokio::join!(
+        model_details(&host, &client, &model_name),
+        gpu::detect(&host)
+    );
+    let (parameter_size, quantization) = details?;
+
+    let started_at = unix_now();
+
+    let total = repetitions * PROMPTS.len() as u32;
+    let mut samples = Vec::with_capacity(total as usize);
+    for repetition in 0..repetitions {
+        // Every repetition starts cold, so each one measures a load
+        unload_model(&host, &client, &model_name).await;
+        for (prompt_index, prompt) in PROMPTS.iter().enumerate() {
+            let sample = run_prompt(
+                &host,
+                &client,
+                &model_name,
+                prompt,
+                prompt_index,
+                repetition,
+            )
+            .await?;
+            samples.push(sample);
+            let _ = window.emit(
+                "benchmark-progress",
+                BenchmarkProgress {
+                    model: model_name.clone(),
+                    completed: samples.len() as u32,
+                    total,
+                },
+            );
+        }
+    }
+
+    let stat = |f: fn(&BenchmarkSample) -> f64| percentiles(samples.iter().map(f).collect());
+    // Later prompts find the model already loaded
+    let cold_loads = samples
+        .iter()
+        .filter(|s| s.prompt_index == 0)
+        .map(|s| s.load_ms)
+        .collect();
+    let result = BenchmarkResult {
+        model: model_name,
+        quantization,
+        parameter_size,
+        started_at,
+        repetitions,
+        hardware: BenchmarkHardware {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            gpus: gpu.devices,
+        },
+        load_ms: percentiles(cold_loads),
+        ttft_ms: stat(|s| s.ttft_ms),
+        prompt_eval_tps: stat(|s| s.prompt_eval_tps),
+        eval_tps: stat(|s| s.eval_tps),
+        samples,
+    };
+
+    append_history(window.app_handle(), &result).await?;
+    tracing::info!(
+        "Benchmark {} done: {:.1} tok/s (p50)",
+        result.model,
+        result.eval_tps.p50
+    );
+    Ok(result)
+}
+
+/// Past benchmark results, oldest first, optionally for one model
+#[tauri::command]
+pub async fn get_benchmark_history(
+    app: AppHandle,
+    model_name: Option<String>,
+) -> AppResult<Vec<BenchmarkResult>> {
+    let path = history_path(&app)?;
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => {
+            return Err(AppError::new(ErrorCode::Io, "Failed to read benchmark history").cause(e))
+        }
+    };
+
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<BenchmarkResult>(line).ok())
+        .filter(|r| model_name.as_ref().is_none_or(|m| &r.model == m))
+        .collect())
+}
+
+async fn run_prompt(
+    host: &Host,
+    client: &reqwest::Client,
+    model: &str,
+    prompt: &str,
+    prompt_index: usize,
+    repetition: u32,
+) -> AppResult<BenchmarkSample> {
+    let started = Instant::now();
+    let mut response = client
+        .post(host.ollama("/api/generate"))
+        .json(&serde_json::json!({
+            "model": model,
+            "prompt": prompt,
+            "stream": true,
+            "options": {
+                "seed": SEED,
+                "temperature": 0,
+                "num_predict": NUM_PREDICT,
+                "num_ctx": NUM_CTX,
+            },
+        }))
+        .send()
+        .await
+        .map_err(AppError::ollama_unreachable)?;
+
+    if !response.status().is_success() {
+        return Err(format!("Ollama returned {} for {}", response.status(), model).into());
+    }
+
+    let mut ttft: Option<Duration> = None;
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut last: Option<serde_json::Value> = None;
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Stream error: {}", e))?
+    {
+        buffer.extend_from_slice(&chunk);
+        while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            let Ok(event) = serde_json::from_slice::<serde_json::Value>(&line) else {
+                continue;
+            };
+            if let Some(error) = event["error"].as_str() {
+                return Err(format!("Ollama error: {}", error).into());
+            }
+            if ttft.is_none() && event["response"].as_str().is_some_and(|r| !r.is_empty()) {
+                ttft = Some(started.elapsed());
+            }
+            if event["done"].as_bool() == Some(true) {
+                last = Some(event);
+            }
+        }
+    }
+
+    let done = last.ok_or_else(|| "Generation ended without final statistics".to_string())?;
+    let ns = |key: &str| done[key].as_u64().unwrap_or(0) as f64;
+    let rate = |count: f64, duration_ns: f64| {
+        if duration_ns > 0.0 {
+            count / (duration_ns / 1e9)
+        } else {
+            0.0
+        }
+    };
+
+    Ok(BenchmarkSample {
+        prompt_index,
+        repetition,
+        load_ms: ns("load_duration") / 1e6,
+        ttft_ms: ttft.unwrap_or_else(|| started.elapsed()).as_secs_f64() * 1000.0,
+        prompt_eval_tps: rate(ns("prompt_eval_count"), ns("prompt_eval_duration")),
+        eval_tps: rate(ns("eval_count"), ns("eval_duration")),
+        eval_count: done["eval_count"].as_u64().unwrap_or(0),
+        total_ms: ns("total_duration") / 1e6,
+    })
+}
+
+/// (parameter_size, quantization) from /api/show
+async fn model_details(
+    host: &Host,
+    client: &reqwest::Client,
+    model: &str,
+) -> AppResult<(Option<String>, Option<String>)> {
+    let response = client
+        .post(host.ollama("/api/show"))
+        .json(&serde_json::json!({ "name": model }))
+        .send()
+        .await
+        .map_err(AppError::ollama_unreachable)?;
+
+    if !response.status().is_success() {
+        return Err(AppError::new(
+            ErrorCode::ModelNotFound,
+            format!("Model {} not found", model),
+        ));
+    }
+
+    let info: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse model info: {}", e))?;
+    let detail = |key: &str| info["details"][key].as_str().map(str::to_string);
+    Ok((detail("parameter_size"), detail("quantization_level")))
+}
+
+/// Drop the model from memory so the next request loads it again
+async fn unload_model(host: &Host, client: &reqwest::Client, model: &str) {
+    let _ = client
+        .post(host.ollama("/api/generate"))
+        .json(&serde_json::json!({ "model": model, "keep_alive": 0 }))
+        .send()
+        .await;
+}
+
+/// Nearest-rank percentiles
+fn percentiles(mut values: Vec<f64>) -> Percentiles {
+    if values.is_empty() {
+        return Percentiles {
+            min: 0.0,
+            mean: 0.0,
+            p50: 0.0,
+            p90: 0.0,
+            p99: 0.0,
+            max: 0.0,
+        };
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let rank = |p: f64| {
+        let index = ((p / 100.0) * values.len() as f64).ceil() as usize;
+        values[index.clamp(1, values.len()) - 1]
+    };
+
+    Percentiles {
+        min: values[0],
+        mean: values.iter().sum::<f64>() / values.len() as f64,
+        p50: rank(50.0),
+        p90: rank(90.0),
+        p99: rank(99.0),
+        max: values[values.len() - 1],
+    }
+}
+
+fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Data dir error: {}", e))?
+        .join(HISTORY_FILE))
+}
+
+async fn append_history(app: &AppHandle, result: &BenchmarkResult) -> Result<(), String> {
+    let path = history_path(app)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create data dir: {}", e))?;
+    }
+
+    let mut line = serde_json::to_string(result)
+        .map_err(|e| format!("Failed to serialize benchmark: {}", e))?;
+    line.push('\n');
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .map_err(|e| format!("Failed to open benchmark history: {}", e))?;
+    file.write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write benchmark history: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let values: Vec<f64> = (1..=10).rev().map(f64::from).collect();
+        let p = percentiles(values);
+        assert_eq!((p.min, p.max), (1.0, 10.0));
+        assert_eq!(p.mean, 5.5);
+        assert_eq!((p.p50, p.p90, p.p99), (5.0, 9.0, 10.0));
+
+        let p = percentiles((1..=200).map(f64::from).collect());
+        assert_eq!((p.p50, p.p90, p.p99), (100.0, 180.0, 198.0));
+    }
+
+    #[test]
+    fn percentiles_of_one_or_no_value() {
+        let p = percentiles(vec![42.5]);
+        assert_eq!(
+            (p.min, p.mean, p.p50, p.p99, p.max),
+            (42.5, 42.5, 42.5, 42.5, 42.5)
+        );
+
+        let p = percentiles(vec![]);
+        assert_eq!(
+            (p.min, p.mean, p.p50, p.p99, p.max),
+            (0.0, 0.0, 0.0, 0.0, 0.0)
+        );
+    }
+}
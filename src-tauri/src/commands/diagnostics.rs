@@ -0,0 +1,251 @@
+// Diagnostic bundle export
+// Collects everything needed to debug a broken setup into a single zip
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::time::{timeout, Duration};
+
+use super::{docker, health, setup};
+use crate::error::AppResult;
+use crate::services::container::{self, RuntimeKind};
+use crate::services::host::Host;
+use crate::utils::log_buffer::RecentLogs;
+use crate::utils::redact;
+use crate::utils::time::unix_now;
+
+const CMD_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_LOG_LINES: u32 = 200;
+
+#[derive(Debug, Serialize)]
+struct Versions {
+    app: String,
+    os: String,
+    arch: String,
+    container_runtime: RuntimeKind,
+    /// Version of the active runtime's engine
+    engine: Option<String>,
+    compose: Option<String>,
+    ollama: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    app_version: String,
+    created_unix: u64,
+    log_lines: u32,
+    files: Vec<String>,
+    /// Sections that could not be collected, with the reason
+    errors: Vec<String>,
+}
+
+/// Write a diagnostic zip and return its path.
+/// `destination` defaults to the user's download directory.
+#[tauri::command]
+pub async fn export_diagnostics(
+    app: AppHandle,
+    destination: Option<String>,
+    log_lines: Option<u32>,
+) -> AppResult<String> {
+    tracing::info!("Exporting diagnostic bundle");
+
+    let host = app.state::<Host>().inner().clone();
+    let log_lines = log_lines.unwrap_or(DEFAULT_LOG_LINES);
+    let compose_file = docker::compose_file_path(&app)?;
+
+    let mut files: Vec<(String, String)> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    let (prereqs, health, versions) = tokio::join!(
+        setup::prerequisites(&host),
+        health::collect_health(&host),
+        collect_versions(&host),
+    );
+    match prereqs.map_err(|e| e.describe()).and_then(|p| to_json(&p)) {
+        Ok(json) => files.push(("prerequisites.json".into(), json)),
+        Err(e) => errors.push(format!("prerequisites: {}", e)),
+    }
+    match health.and_then(|(h, _)| to_json(&h)) {
+        Ok(json) => files.push(("health.json".into(), json)),
+        Err(e) => errors.push(format!("health: {}", e)),
+    }
+    files.push(("versions.json".into(), to_json(&versions)?));
+
+    match compose_output(&host, &compose_file, &["config"]).await {
+        Ok(rendered) => files.push(("compose/docker-compose.rendered.yml".into(), rendered)),
+        Err(e) => errors.push(format!("rendered compose file: {}", e)),
+    }
+
+    match compose_output(&host, &compose_file, &["config", "--services"]).await {
+        Ok(services) => {
+            let tail = log_lines.to_string();
+            for service in services.lines().map(str::trim).filter(|s| !s.is_empty()) {
+                let args = [
+                    "logs",
+                    "--no-color",
+                    "--timestamps",
+                    "--tail",
+                    &tail,
+                    service,
+                ];
+                match compose_output(&host, &compose_file, &args).await {
+                    Ok(logs) => files.push((format!("logs/{}.log", service), logs)),
+                    Err(e) => errors.push(format!("{} logs: {}", service, e)),
+                }
+            }
+        }
+        Err(e) => errors.push(format!("compose services: {}", e)),
+    }
+
+    if let Some(recent) = app.try_state::<RecentLogs>() {
+        files.push(("app/recent.log".into(), recent.snapshot().join("\n")));
+    }
+
+    if let Some(crash_log) = crash_log_path().filter(|p| p.exists()) {
+        match tokio::fs::read_to_string(&crash_log).await {
+            Ok(content) => files.push(("app/crash.log".into(), content)),
+            Err(e) => errors.push(format!("crash log: {}", e)),
+        }
+    }
+
+    // Redact everything collected so far, then add the unredacted metadata
+    let mut files: Vec<(String, String)> = files
+        .into_iter()
+        .map(|(name, content)| (name, redact::redact(&content)))
+        .collect();
+    files.push(("REDACTION.txt".into(), redact::describe_rules()));
+
+    let manifest = Manifest {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_unix: unix_now(),
+        log_lines,
+        files: files.iter().map(|(name, _)| name.clone()).collect(),
+        errors,
+    };
+    files.push(("manifest.json".into(), to_json(&manifest)?));
+
+    let dest = match destination {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .path()
+            .download_dir()
+            .or_else(|_| app.path().app_data_dir())
+            .map_err(|e| format!("No destination directory: {}", e))?
+            .join(format!(
+                "dark-gpt-diagnostics-{}.zip",
+                manifest.created_unix
+            )),
+    };
+
+    let zip_path = dest.clone();
+    tokio::task::spawn_blocking(move || write_zip(&zip_path, &files))
+        .await
+        .map_err(|e| format!("Bundle task failed: {}", e))??;
+
+    tracing::info!("Diagnostic bundle written to {:?}", dest);
+    Ok(dest.to_string_lossy().to_string())
+}
+
+async fn collect_versions(host: &Host) -> Versions {
+    let runtime = container::current(host).await;
+    let (engine, compose, ollama) = tokio::join!(
+        runtime.version(),
+        runtime.compose_version(),
+        ollama_version(host),
+    );
+
+    Versions {
+        app: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        container_runtime: runtime.kind(),
+        engine,
+        compose,
+        ollama,
+    }
+}
+
+/// Version from the running API, or from the binary when the server is down
+async fn ollama_version(host: &Host) -> Option<String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .ok()?;
+
+    if let Ok(response) = client.get(host.ollama("/api/version")).send().await {
+        if let Ok(version) = response.json::<serde_json::Value>().await {
+            if let Some(v) = version["version"].as_str() {
+                return Some(v.to_string());
+            }
+        }
+    }
+    command_output(host, "ollama", &["--version"]).await.ok()
+}
+
+async fn compose_output(host: &Host, compose_file: &Path, args: &[&str]) -> Result<String, String> {
+    let output = timeout(
+        CMD_TIMEOUT,
+        container::current(host)
+            .await
+            .compose(compose_file)
+            .args(args)
+            .output(),
+    )
+    .await
+    .map_err(|_| "timed out".to_string())?
+    .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+async fn command_output(host: &Host, program: &str, args: &[&str]) -> Result<String, String> {
+    let output = timeout(CMD_TIMEOUT, host.command(program).args(args).output())
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Where the panic hook in main.rs writes its crash log
+fn crash_log_path() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name("dark-gpt-crash.log"))
+}
+
+fn write_zip(path: &Path, files: &[(String, String)]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create destination dir: {}", e))?;
+    }
+
+    let file =
+        std::fs::File::create(path).map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, content) in files {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        zip.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize bundle: {}", e))?;
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize: {}", e))
+}
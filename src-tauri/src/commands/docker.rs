@@ -1,10 +1,20 @@
 // Docker management commands
 
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
-use tokio::process::Command as TokioCommand;
+use tauri::{AppHandle, Manager, State};
 use tokio::time::{timeout, Duration};
 
+use super::ports::{self, PortConflict};
+use crate::error::{AppError, AppResult, ErrorCode, ErrorDetails};
+use crate::services::audit;
+use crate::services::container::{self, ContainerRuntime};
+use crate::services::host::Host;
+use crate::services::lifecycle;
+use crate::utils::paths::AppPaths;
+
 const CMD_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,93 +31,194 @@ pub struct ServiceStatus {
     pub health: Option<String>,
 }
 
-/// Check if Docker is installed and running
-#[tauri::command]
-pub async fn check_docker() -> Result<DockerStatus, String> {
-    tracing::debug!("Checking Docker status");
+/// Resolve the bundled docker-compose.yml
+pub(crate) fn compose_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(AppPaths::from_app(app)?.compose_file())
+}
+
+/// Recreate only the given services with the active profile's env, leaving
+/// the rest of the stack alone. Services that aren't running are skipped.
+pub(crate) async fn recreate_services(app: &AppHandle, services: &[&str]) -> Result<(), String> {
+    let compose_file = compose_file_path(app)?;
+    let runtime = container::current(&app.state::<Host>()).await;
+
+    let mut running = Vec::new();
+    for service in services {
+        let ps = timeout(
+            CMD_TIMEOUT,
+            runtime
+                .compose(&compose_file)
+                .args(["ps", "-q", service])
+                .output(),
+        )
+        .await;
+        if let Ok(Ok(o)) = ps {
+            if o.status.success() && !o.stdout.trim_ascii().is_empty() {
+                running.push(*service);
+            }
+        }
+    }
+    if running.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!("Recreating {:?} to apply settings", running);
+    let output = timeout(
+        Duration::from_secs(60),
+        runtime
+            .compose(&compose_file)
+            .args(["up", "-d", "--no-deps"])
+            .args(&running)
+            .output(),
+    )
+    .await
+    .map_err(|_| "Timed out recreating services (60s)".to_string())?
+    .map_err(|e| format!("Failed to recreate services: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "{} compose failed: {}",
+            runtime.display_name(),
+            stderr
+        ));
+    }
+    Ok(())
+}
 
-    let version_output = timeout(
+/// Render the compose file (env interpolation, defaults) as JSON via
+/// `compose config`.
+pub(crate) async fn rendered_compose_config(
+    runtime: &dyn ContainerRuntime,
+    compose_file: &Path,
+) -> Result<serde_json::Value, String> {
+    let output = timeout(
         CMD_TIMEOUT,
-        TokioCommand::new("docker")
-            .args(["version", "--format", "{{.Server.Version}}"])
+        runtime
+            .compose(compose_file)
+            .args(["config", "--format", "json"])
             .output(),
     )
-    .await;
+    .await
+    .map_err(|_| "Timed out rendering compose file".to_string())?
+    .map_err(|e| format!("Failed to render compose file: {}", e))?;
 
-    match version_output {
-        Ok(Ok(output)) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            tracing::info!("Docker version: {}", version);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("compose config failed: {}", stderr.trim()));
+    }
 
-            let running = timeout(
-                CMD_TIMEOUT,
-                TokioCommand::new("docker").args(["info"]).output(),
-            )
-            .await
-            .map(|r| r.map(|o| o.status.success()).unwrap_or(false))
-            .unwrap_or(false);
-
-            Ok(DockerStatus {
-                installed: true,
-                running,
-                version: Some(version),
-            })
-        }
-        Ok(Ok(_)) => Ok(DockerStatus {
-            installed: true,
-            running: false,
-            version: None,
-        }),
-        _ => Ok(DockerStatus {
-            installed: false,
-            running: false,
-            version: None,
-        }),
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse rendered compose file: {}", e))
+}
+
+/// Actual (project-prefixed) names of the top-level networks or volumes
+pub(crate) fn compose_names(config: &serde_json::Value, section: &str) -> Vec<String> {
+    config[section]
+        .as_object()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|(_, v)| !v["external"].as_bool().unwrap_or(false))
+                .filter_map(|(_, v)| v["name"].as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check if the container runtime (Docker or Podman, per settings) is
+/// installed and running
+#[tauri::command]
+pub async fn check_docker(host: State<'_, Host>) -> AppResult<DockerStatus> {
+    status(&host).await
+}
+
+/// Installed/running/version of the active runtime; shared with the CLI
+pub(crate) async fn status(host: &Host) -> AppResult<DockerStatus> {
+    let runtime = container::current(host).await;
+    tracing::debug!("Checking {} status", runtime.display_name());
+
+    let (version, running) = tokio::join!(runtime.version(), runtime.ping());
+    if let Some(version) = &version {
+        tracing::info!("{} version: {}", runtime.display_name(), version);
     }
+
+    Ok(DockerStatus {
+        installed: runtime.installed() || version.is_some(),
+        running,
+        version,
+    })
 }
 
 /// Start Docker Compose services
 #[tauri::command]
-pub async fn start_services(app: AppHandle) -> Result<Vec<ServiceStatus>, String> {
-    tracing::info!("Starting Docker services");
-
-    let docker_dir = app
-        .path()
-        .resource_dir()
-        .map_err(|e| format!("Failed to get resource dir: {}", e))?
-        .join("docker");
+pub async fn start_services(app: AppHandle) -> AppResult<Vec<ServiceStatus>> {
+    let compose_file = compose_file_path(&app)?;
+    let result = start(&app.state::<Host>(), &compose_file).await;
+    audit::recorded("services.start", serde_json::json!({}), result)
+}
 
-    let compose_file = docker_dir.join("docker-compose.yml");
+/// `compose up` with the preflight port check; shared with the CLI
+pub(crate) async fn start(host: &Host, compose_file: &Path) -> AppResult<Vec<ServiceStatus>> {
+    let runtime = container::current(host).await;
+    tracing::info!("Starting services with {}", runtime.display_name());
+    let context = "Starting services";
 
     if !compose_file.exists() {
-        return Err(format!(
-            "docker-compose.yml not found at {:?}",
-            compose_file
-        ));
+        return Err(AppError::new(
+            ErrorCode::Io,
+            format!("docker-compose.yml not found at {:?}", compose_file),
+        )
+        .context(context)
+        .remediation("Reinstall Dark-GPT; the bundled compose file is missing."));
+    }
+
+    // Preflight: refuse to start if another program holds a port we publish
+    let conflicts = ports::find_port_conflicts(runtime.as_ref(), compose_file).await;
+    if !conflicts.is_empty() {
+        return Err(port_conflict(conflicts).context(context));
     }
 
     let output = timeout(
         Duration::from_secs(60),
-        TokioCommand::new("docker")
-            .args([
-                "compose",
-                "-f",
-                compose_file.to_str().unwrap(),
-                "up",
-                "-d",
-            ])
-            .output(),
+        runtime.compose(compose_file).args(["up", "-d"]).output(),
     )
     .await
-    .map_err(|_| "Timed out starting services (60s)".to_string())?
-    .map_err(|e| format!("Failed to start services: {}", e))?;
+    .map_err(|_| AppError::timeout("starting services", 60))?
+    .map_err(|e| AppError::runtime_spawn(runtime.display_name(), e).context(context))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Docker compose failed: {}", stderr));
+
+        // Something grabbed a port between the preflight and `up`
+        if ports::is_port_error(&stderr) {
+            let conflicts = ports::find_port_conflicts(runtime.as_ref(), compose_file).await;
+            if !conflicts.is_empty() {
+                return Err(port_conflict(conflicts).context(context));
+            }
+        }
+
+        // Rootless Podman can't publish ports below 1024 by default
+        if stderr.contains("privileged port") {
+            return Err(AppError::new(
+                ErrorCode::PortInUse,
+                format!(
+                    "Rootless {} cannot publish ports 80/443",
+                    runtime.display_name()
+                ),
+            )
+            .context(context)
+            .remediation(
+                "Allow them with `sudo sysctl net.ipv4.ip_unprivileged_port_start=80`, then retry.",
+            )
+            .cause(stderr));
+        }
+
+        return Err(AppError::compose_failed(runtime.display_name(), &stderr).context(context));
     }
 
-    tracing::info!("Docker services started");
+    tracing::info!("Services started");
+    lifecycle::record_stack_started();
 
     Ok(vec![
         ServiceStatus {
@@ -123,40 +234,53 @@ pub async fn start_services(app: AppHandle) -> Result<Vec<ServiceStatus>, String
     ])
 }
 
+pub(crate) fn port_conflict(conflicts: Vec<PortConflict>) -> AppError {
+    let ports = conflicts
+        .iter()
+        .map(|c| c.port.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let remediation = conflicts
+        .iter()
+        .map(|c| c.suggestion.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    AppError::new(
+        ErrorCode::PortInUse,
+        format!("Required port(s) already in use: {}", ports),
+    )
+    .remediation(remediation)
+    .details(ErrorDetails::PortConflicts { conflicts })
+}
+
 /// Stop Docker Compose services
 #[tauri::command]
-pub async fn stop_services(app: AppHandle) -> Result<(), String> {
-    tracing::info!("Stopping Docker services");
-
-    let docker_dir = app
-        .path()
-        .resource_dir()
-        .map_err(|e| format!("Failed to get resource dir: {}", e))?
-        .join("docker");
+pub async fn stop_services(app: AppHandle) -> AppResult<()> {
+    let result = stop(&app.state::<Host>(), &compose_file_path(&app)?).await;
+    audit::recorded("services.stop", serde_json::json!({}), result)
+}
 
-    let compose_file = docker_dir.join("docker-compose.yml");
+/// `compose down`; shared with the CLI
+pub(crate) async fn stop(host: &Host, compose_file: &Path) -> AppResult<()> {
+    let runtime = container::current(host).await;
+    tracing::info!("Stopping services with {}", runtime.display_name());
+    let context = "Stopping services";
 
     let output = timeout(
         Duration::from_secs(30),
-        TokioCommand::new("docker")
-            .args([
-                "compose",
-                "-f",
-                compose_file.to_str().unwrap(),
-                "down",
-            ])
-            .output(),
+        runtime.compose(compose_file).arg("down").output(),
     )
     .await
-    .map_err(|_| "Timed out stopping services (30s)".to_string())?
-    .map_err(|e| format!("Failed to stop services: {}", e))?;
+    .map_err(|_| AppError::timeout("stopping services", 30))?
+    .map_err(|e| AppError::runtime_spawn(runtime.display_name(), e).context(context))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Docker compose down failed: {}", stderr));
+        return Err(AppError::compose_failed(runtime.display_name(), &stderr).context(context));
     }
 
-    tracing::info!("Docker services stopped");
+    tracing::info!("Services stopped");
+    lifecycle::record_stack_stopped();
     Ok(())
 }
 
@@ -166,33 +290,214 @@ pub async fn get_service_logs(
     app: AppHandle,
     service: String,
     lines: Option<u32>,
-) -> Result<String, String> {
-    let docker_dir = app
-        .path()
-        .resource_dir()
-        .map_err(|e| format!("Failed to get resource dir: {}", e))?
-        .join("docker");
+) -> AppResult<String> {
+    let compose_file = compose_file_path(&app)?;
+    let host = app.state::<Host>();
+    service_logs(&host, &compose_file, Some(&service), lines.unwrap_or(100)).await
+}
 
-    let compose_file = docker_dir.join("docker-compose.yml");
-    let lines_str = lines.unwrap_or(100).to_string();
+/// Last `lines` log lines of one service, or of all of them
+pub(crate) async fn service_logs(
+    host: &Host,
+    compose_file: &Path,
+    service: Option<&str>,
+    lines: u32,
+) -> AppResult<String> {
+    let runtime = container::current(host).await;
 
     let output = timeout(
         Duration::from_secs(10),
-        TokioCommand::new("docker")
-            .args([
-                "compose",
-                "-f",
-                compose_file.to_str().unwrap(),
-                "logs",
-                "--tail",
-                &lines_str,
-                &service,
-            ])
+        runtime
+            .compose(compose_file)
+            .args(["logs", "--tail", &lines.to_string()])
+            .args(service)
             .output(),
     )
     .await
-    .map_err(|_| "Timed out fetching logs".to_string())?
-    .map_err(|e| format!("Failed to get logs: {}", e))?;
+    .map_err(|_| AppError::timeout("fetching logs", 10))?
+    .map_err(|e| {
+        AppError::runtime_spawn(runtime.display_name(), e)
+            .context(format!("Fetching {} logs", service.unwrap_or("service")))
+    })?;
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
+
+/// Stream logs to our stdout/stderr until the services stop or the process
+/// is interrupted (CLI `logs -f`)
+pub(crate) async fn follow_service_logs(
+    host: &Host,
+    compose_file: &Path,
+    service: Option<&str>,
+    lines: u32,
+) -> AppResult<()> {
+    let runtime = container::current(host).await;
+    let context = format!("Following {} logs", service.unwrap_or("service"));
+
+    // kill_on_drop: shutdown cancels the stream by dropping the wait
+    let mut child = runtime
+        .compose(compose_file)
+        .args(["logs", "--follow", "--tail", &lines.to_string()])
+        .args(service)
+        .to_tokio()
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::runtime_spawn(runtime.display_name(), e).context(&context))?;
+    let status = lifecycle::cancellable(&context, async {
+        child.wait().await.map_err(AppError::from)
+    })
+    .await?;
+
+    if !status.success() {
+        return Err(AppError::new(
+            ErrorCode::ComposeFailed,
+            format!("{} compose logs exited with {}", runtime.display_name(), status),
+        )
+        .context(context));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::container::RuntimePreference;
+    use crate::services::profiles::Profile;
+    use crate::utils::testing::FakeRunner;
+
+    const CONFIG: &str = r#"{
+        "name": "dark-gpt",
+        "services": {
+            "caddy": { "ports": [{ "published": "8443", "target": 443 }] }
+        }
+    }"#;
+
+    /// A Docker host with the stack's compose file in a temp dir
+    fn docker() -> (std::sync::Arc<FakeRunner>, tempfile::TempDir, PathBuf) {
+        let fake = FakeRunner::new();
+        fake.installed("docker");
+        let dir = tempfile::tempdir().unwrap();
+        let compose_file = dir.path().join("docker-compose.yml");
+        std::fs::write(&compose_file, "services: {}\n").unwrap();
+        (fake, dir, compose_file)
+    }
+
+    fn compose(compose_file: &Path, args: &str) -> String {
+        format!(
+            "docker compose -p dark-gpt -f {} {}",
+            compose_file.display(),
+            args
+        )
+    }
+
+    #[tokio::test]
+    async fn reports_runtime_status() {
+        let fake = FakeRunner::new();
+        fake.installed("docker")
+            .on("docker version", "27.3.1\n")
+            .fail("docker info", "Cannot connect to the Docker daemon");
+        let status = status(&fake.host("http://127.0.0.1:9")).await.unwrap();
+        assert!(status.installed);
+        assert!(!status.running);
+        assert_eq!(status.version.as_deref(), Some("27.3.1"));
+
+        let missing = FakeRunner::new().host("http://127.0.0.1:9");
+        let status = super::status(&missing).await.unwrap();
+        assert!(!status.installed && !status.running);
+    }
+
+    #[tokio::test]
+    async fn starts_the_active_profiles_project() {
+        let (fake, _dir, compose_file) = docker();
+        fake.on(&compose(&compose_file, "config"), CONFIG)
+            .on("docker ps", "dark-gpt-caddy\t0.0.0.0:8443->443/tcp\n")
+            .on(&compose(&compose_file, "up -d"), "");
+
+        let services = start(&fake.host("http://127.0.0.1:9"), &compose_file)
+            .await
+            .unwrap();
+        assert_eq!(services.len(), 2);
+        let up = fake.calls_to(&compose(&compose_file, "up -d"));
+        assert_eq!(up.len(), 1);
+        let env = |key: &str, value: &str| (key.to_string(), value.to_string());
+        assert!(up[0]
+            .envs
+            .contains(&env("COMPOSE_PROJECT_NAME", "dark-gpt")));
+        assert!(up[0]
+            .envs
+            .contains(&env("DEFAULT_MODELS", "dolphin-llama3:8b")));
+    }
+
+    #[tokio::test]
+    async fn each_host_follows_its_own_runtime_and_profile() {
+        let fake = FakeRunner::new();
+        fake.installed("docker")
+            .installed("podman")
+            .on("podman compose version", "4.9.3\n");
+        let lab = fake.host("http://127.0.0.1:9");
+        lab.set_runtime_preference(RuntimePreference::Podman);
+        lab.activate(&Profile::default().clone_as("lab"));
+        lab.set_certs_dir(PathBuf::from("/srv/certs"));
+        let other = fake.host("http://127.0.0.1:9");
+
+        let compose_file = Path::new("/srv/docker-compose.yml");
+        let cmd = container::current(&lab).await.compose(compose_file);
+        assert_eq!(
+            cmd.line(),
+            "podman compose -p dark-gpt-lab -f /srv/docker-compose.yml"
+        );
+        let certs = ("DARK_GPT_CERTS_DIR".to_string(), "/srv/certs".to_string());
+        assert!(cmd.get_envs().contains(&certs));
+
+        let cmd = container::current(&other).await.compose(compose_file);
+        assert_eq!(
+            cmd.line(),
+            "docker compose -p dark-gpt -f /srv/docker-compose.yml"
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_to_start_when_a_port_is_taken() {
+        let (fake, _dir, compose_file) = docker();
+        fake.on(&compose(&compose_file, "config"), CONFIG)
+            .on("docker ps", "other-nginx\t0.0.0.0:8443->443/tcp\n");
+
+        let host = fake.host("http://127.0.0.1:9");
+        let err = start(&host, &compose_file).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::PortInUse);
+        assert!(fake.calls_to(&compose(&compose_file, "up")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn maps_compose_failures() {
+        let (fake, _dir, compose_file) = docker();
+        fake.on(&compose(&compose_file, "config"), CONFIG)
+            .on("docker ps", "")
+            .fail(
+                &compose(&compose_file, "up -d"),
+                "Error: rootlessport cannot expose privileged port 443",
+            )
+            .fail(
+                &compose(&compose_file, "down"),
+                "Cannot connect to the Docker daemon. Is the docker daemon running?",
+            );
+        let host = fake.host("http://127.0.0.1:9");
+
+        let err = start(&host, &compose_file).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::PortInUse);
+        assert!(err.message.contains("80/443"), "{}", err.message);
+
+        let err = stop(&host, &compose_file).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::DaemonDown);
+    }
+
+    #[tokio::test]
+    async fn missing_runtime_is_not_installed() {
+        let (_, _dir, compose_file) = docker();
+        let host = FakeRunner::new().host("http://127.0.0.1:9");
+        let err = stop(&host, &compose_file).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::DockerNotInstalled);
+    }
+}
@@ -0,0 +1,156 @@
+// API gateway commands
+// Keys are kept in settings as hashes; the key itself is only returned by
+// `create_api_key`.
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use super::settings;
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::audit;
+use crate::services::gateway::{ApiKey, GatewayServer, GatewaySettings, GatewayStatus};
+use crate::services::instance::random_token;
+use crate::services::settings::{AppSettings, SettingsStore};
+
+#[derive(Debug, Serialize)]
+pub struct CreatedKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    /// Shown once; only its hash is stored
+    pub secret: String,
+}
+
+/// Start the gateway and route Open-WebUI through it, across restarts too
+#[tauri::command]
+pub async fn start_gateway(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    server: State<'_, GatewayServer>,
+    port: Option<u16>,
+) -> AppResult<GatewayStatus> {
+    let gateway = store.get().gateway;
+    let port = port.unwrap_or(gateway.port);
+    let result = save(
+        &app,
+        &store,
+        GatewaySettings {
+            enabled: true,
+            port,
+            webui_api_key: gateway
+                .webui_api_key
+                .clone()
+                .or_else(|| Some(random_token())),
+            ..gateway
+        },
+    )
+    .await;
+    audit::recorded("gateway.start", serde_json::json!({ "port": port }), result)?;
+    Ok(server.status())
+}
+
+/// Stop the gateway; Open-WebUI goes back to Ollama directly
+#[tauri::command]
+pub async fn stop_gateway(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    server: State<'_, GatewayServer>,
+) -> AppResult<GatewayStatus> {
+    let gateway = store.get().gateway;
+    let result = save(
+        &app,
+        &store,
+        GatewaySettings {
+            enabled: false,
+            ..gateway
+        },
+    )
+    .await;
+    audit::recorded("gateway.stop", serde_json::json!({}), result)?;
+    Ok(server.status())
+}
+
+/// Whether the gateway is running, and per-key usage since the app started
+#[tauri::command]
+pub fn get_gateway_status(server: State<'_, GatewayServer>) -> GatewayStatus {
+    server.status()
+}
+
+/// The gateway's client keys, without secrets
+#[tauri::command]
+pub fn list_api_keys(store: State<'_, SettingsStore>) -> Vec<ApiKey> {
+    store.get().gateway.keys
+}
+
+/// Create a client key. `rate_limit` is per minute (0 or none: unlimited);
+/// an empty `allowed_models` allows every model.
+#[tauri::command]
+pub async fn create_api_key(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    name: String,
+    rate_limit: Option<u32>,
+    allowed_models: Option<Vec<String>>,
+) -> AppResult<CreatedKey> {
+    let (key, secret) = ApiKey::generate(
+        &name,
+        rate_limit.unwrap_or(0),
+        allowed_models.unwrap_or_default(),
+    );
+    let mut gateway = store.get().gateway;
+    gateway.keys.push(key.clone());
+    let result = save(&app, &store, gateway).await;
+    audit::recorded(
+        "gateway.key.create",
+        serde_json::json!({
+            "id": key.id,
+            "name": key.name,
+            "prefix": key.prefix,
+            "rate_limit": key.rate_limit,
+            "allowed_models": key.allowed_models,
+        }),
+        result,
+    )?;
+    tracing::info!("Created API key {} ({})", key.name, key.prefix);
+    Ok(CreatedKey { key, secret })
+}
+
+/// Revoke a client key; requests using it are refused from now on
+#[tauri::command]
+pub async fn revoke_api_key(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    id: String,
+) -> AppResult<()> {
+    let mut gateway = store.get().gateway;
+    let Some(index) = gateway.keys.iter().position(|k| k.id == id) else {
+        let unknown = AppError::new(
+            ErrorCode::InvalidInput,
+            format!("No API key with id {}", id),
+        );
+        return audit::recorded(
+            "gateway.key.revoke",
+            serde_json::json!({ "id": id }),
+            Err(unknown),
+        );
+    };
+    let key = gateway.keys.remove(index);
+    let result = save(&app, &store, gateway).await;
+    audit::recorded(
+        "gateway.key.revoke",
+        serde_json::json!({ "id": key.id, "name": key.name, "prefix": key.prefix }),
+        result,
+    )?;
+    tracing::info!("Revoked API key {} ({})", key.name, key.prefix);
+    Ok(())
+}
+
+/// Validate and persist, then apply (gateway, webui route)
+async fn save(app: &AppHandle, store: &SettingsStore, gateway: GatewaySettings) -> AppResult<()> {
+    let previous = store.get();
+    let updated = store.save(AppSettings {
+        gateway,
+        ..previous.clone()
+    })?;
+    settings::apply_changes(app, &previous, &updated).await?;
+    Ok(())
+}
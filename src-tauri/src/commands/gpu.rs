@@ -0,0 +1,241 @@
+// GPU / accelerator detection
+// Parses nvidia-smi and rocm-smi output, and checks Docker for GPU runtimes
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tokio::time::{timeout, Duration};
+
+use crate::error::AppResult;
+use crate::services::host::Host;
+
+const CMD_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuDevice {
+    pub vendor: String,
+    pub name: String,
+    /// 0 when the driver doesn't report it
+    pub vram_mb: u64,
+    pub driver_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub devices: Vec<GpuDevice>,
+    /// Containers can use the GPU (nvidia runtime, or /dev/kfd for ROCm)
+    pub container_gpu_support: bool,
+    pub container_runtimes: Vec<String>,
+}
+
+impl GpuInfo {
+    /// VRAM of the largest GPU, if any was detected with a known size
+    pub fn max_vram_mb(&self) -> Option<u64> {
+        self.devices
+            .iter()
+            .map(|d| d.vram_mb)
+            .filter(|v| *v > 0)
+            .max()
+    }
+}
+
+/// Detect GPUs and container GPU support
+#[tauri::command]
+pub async fn detect_gpu(host: State<'_, Host>) -> AppResult<GpuInfo> {
+    Ok(detect(&host).await)
+}
+
+pub(crate) async fn detect(host: &Host) -> GpuInfo {
+    let (nvidia, rocm, runtimes) = tokio::join!(
+        command_stdout(
+            host,
+            "nvidia-smi",
+            &[
+                "--query-gpu=name,memory.total,driver_version",
+                "--format=csv,noheader,nounits",
+            ],
+        ),
+        command_stdout(
+            host,
+            "rocm-smi",
+            &[
+                "--showproductname",
+                "--showmeminfo",
+                "vram",
+                "--showdriverversion",
+                "--json",
+            ],
+        ),
+        command_stdout(host, "docker", &["info", "--format", "{{json .Runtimes}}"]),
+    );
+
+    let nvidia = nvidia.map(|out| parse_nvidia_smi(&out)).unwrap_or_default();
+    let amd = rocm.map(|out| parse_rocm_smi(&out)).unwrap_or_default();
+    let container_runtimes = runtimes
+        .map(|out| parse_docker_runtimes(&out))
+        .unwrap_or_default();
+
+    let nvidia_in_containers =
+        !nvidia.is_empty() && container_runtimes.iter().any(|r| r == "nvidia");
+    let amd_in_containers = !amd.is_empty() && std::path::Path::new("/dev/kfd").exists();
+
+    let info = GpuInfo {
+        devices: nvidia.into_iter().chain(amd).collect(),
+        container_gpu_support: nvidia_in_containers || amd_in_containers,
+        container_runtimes,
+    };
+    tracing::info!("GPU detection: {:?}", info);
+    info
+}
+
+/// Parse `nvidia-smi --query-gpu=name,memory.total,driver_version --format=csv,noheader,nounits`
+pub(crate) fn parse_nvidia_smi(output: &str) -> Vec<GpuDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [name, memory, driver] = fields.as_slice() else {
+                return None;
+            };
+            if name.is_empty() {
+                return None;
+            }
+            Some(GpuDevice {
+                vendor: "nvidia".to_string(),
+                name: name.to_string(),
+                vram_mb: memory.parse().unwrap_or(0),
+                driver_version: reported(driver),
+            })
+        })
+        .collect()
+}
+
+/// Parse `rocm-smi --showproductname --showmeminfo vram --showdriverversion --json`
+pub(crate) fn parse_rocm_smi(output: &str) -> Vec<GpuDevice> {
+    let Ok(serde_json::Value::Object(root)) = serde_json::from_str(output) else {
+        return vec![];
+    };
+
+    // Key casing differs between rocm-smi releases
+    let field = |card: &serde_json::Value, key: &str| -> Option<String> {
+        card.as_object()?
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .and_then(|(_, v)| v.as_str())
+            .map(str::to_string)
+    };
+    let driver_version = root
+        .get("system")
+        .and_then(|system| field(system, "Driver version"));
+
+    root.iter()
+        .filter(|(key, _)| key.starts_with("card"))
+        .map(|(key, card)| GpuDevice {
+            vendor: "amd".to_string(),
+            name: field(card, "Card Series")
+                .or_else(|| field(card, "Card Model"))
+                .unwrap_or_else(|| key.clone()),
+            vram_mb: field(card, "VRAM Total Memory (B)")
+                .and_then(|b| b.parse::<u64>().ok())
+                .map(|bytes| bytes / (1024 * 1024))
+                .unwrap_or(0),
+            driver_version: driver_version.clone(),
+        })
+        .collect()
+}
+
+/// Parse `docker info --format '{{json .Runtimes}}'` into runtime names
+pub(crate) fn parse_docker_runtimes(output: &str) -> Vec<String> {
+    match serde_json::from_str(output) {
+        Ok(serde_json::Value::Object(runtimes)) => runtimes.keys().cloned().collect(),
+        _ => vec![],
+    }
+}
+
+fn reported(value: &str) -> Option<String> {
+    match value {
+        "" | "[N/A]" | "N/A" => None,
+        v => Some(v.to_string()),
+    }
+}
+
+async fn command_stdout(host: &Host, program: &str, args: &[&str]) -> Option<String> {
+    match timeout(CMD_TIMEOUT, host.command(program).args(args).output()).await {
+        Ok(Ok(o)) if o.status.success() => Some(String::from_utf8_lossy(&o.stdout).to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NVIDIA_SINGLE: &str = include_str!("../../tests/fixtures/gpu/nvidia-smi-single.csv");
+    const NVIDIA_MULTI: &str = include_str!("../../tests/fixtures/gpu/nvidia-smi-multi.csv");
+    const NVIDIA_NA: &str = include_str!("../../tests/fixtures/gpu/nvidia-smi-na.csv");
+    const ROCM: &str = include_str!("../../tests/fixtures/gpu/rocm-smi.json");
+    const RUNTIMES_NVIDIA: &str =
+        include_str!("../../tests/fixtures/gpu/docker-runtimes-nvidia.json");
+    const RUNTIMES_DEFAULT: &str =
+        include_str!("../../tests/fixtures/gpu/docker-runtimes-default.json");
+
+    #[test]
+    fn parses_single_nvidia_gpu() {
+        let devices = parse_nvidia_smi(NVIDIA_SINGLE);
+        assert_eq!(
+            devices,
+            vec![GpuDevice {
+                vendor: "nvidia".into(),
+                name: "NVIDIA GeForce RTX 3080".into(),
+                vram_mb: 10240,
+                driver_version: Some("535.129.03".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_multiple_nvidia_gpus() {
+        let devices = parse_nvidia_smi(NVIDIA_MULTI);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "NVIDIA RTX A6000");
+        assert_eq!(devices[1].vram_mb, 4096);
+
+        let info = GpuInfo {
+            devices,
+            ..Default::default()
+        };
+        assert_eq!(info.max_vram_mb(), Some(49140));
+    }
+
+    #[test]
+    fn unreported_nvidia_memory_is_zero() {
+        let devices = parse_nvidia_smi(NVIDIA_NA);
+        assert_eq!(devices[0].vram_mb, 0);
+        assert_eq!(devices[0].driver_version.as_deref(), Some("560.35.03"));
+    }
+
+    #[test]
+    fn parses_rocm_smi_json() {
+        let devices = parse_rocm_smi(ROCM);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].vendor, "amd");
+        assert_eq!(
+            devices[0].name,
+            "Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]"
+        );
+        assert_eq!(devices[0].vram_mb, 16368);
+        assert_eq!(devices[0].driver_version.as_deref(), Some("6.3.6"));
+    }
+
+    #[test]
+    fn garbage_output_yields_no_devices() {
+        assert!(parse_nvidia_smi("").is_empty());
+        assert!(parse_nvidia_smi("command not found").is_empty());
+        assert!(parse_rocm_smi("ERROR: No AMD GPUs").is_empty());
+    }
+
+    #[test]
+    fn detects_nvidia_container_runtime() {
+        assert!(parse_docker_runtimes(RUNTIMES_NVIDIA).contains(&"nvidia".to_string()));
+        assert!(!parse_docker_runtimes(RUNTIMES_DEFAULT).contains(&"nvidia".to_string()));
+    }
+}
@@ -1,8 +1,18 @@
 // Health check commands
 
+use std::future::Future;
+
 use serde::{Deserialize, Serialize};
-use tokio::process::Command as TokioCommand;
-use tokio::time::{timeout, Duration};
+use tauri::{AppHandle, State};
+use tokio::time::{timeout, Duration, Instant};
+
+use super::ollama;
+use crate::error::{AppError, AppResult, ErrorCode, ErrorDetails};
+use crate::services::audit;
+use crate::services::auto_heal::{AutoHealPolicy, AutoHealer};
+use crate::services::container;
+use crate::services::host::Host;
+use crate::services::settings::{AppSettings, SettingsStore};
 
 const CMD_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -28,9 +38,23 @@ pub enum HealthStatus {
     Unknown,
 }
 
+/// How long each check in a `HealthReport` took
+#[derive(Debug, Clone, Copy)]
+pub struct HealthTimings {
+    pub docker: Duration,
+    pub ollama: Duration,
+    pub webui: Duration,
+    pub caddy: Duration,
+}
+
 /// Check health of all services
 #[tauri::command]
-pub async fn check_all_services() -> Result<HealthReport, String> {
+pub async fn check_all_services(host: State<'_, Host>) -> AppResult<HealthReport> {
+    Ok(collect_health(&host).await.map(|(report, _)| report)?)
+}
+
+/// Run all health checks concurrently, timing each one
+pub(crate) async fn collect_health(host: &Host) -> Result<(HealthReport, HealthTimings), String> {
     tracing::debug!("Checking all services health");
 
     let client = reqwest::Client::builder()
@@ -39,80 +63,99 @@ pub async fn check_all_services() -> Result<HealthReport, String> {
         .map_err(|e| format!("Failed to create client: {}", e))?;
 
     // Run all health checks concurrently
-    let (docker_health, ollama_health, webui_health, caddy_health) = tokio::join!(
-        check_docker_health(),
-        check_ollama_health(&client),
-        check_webui_health(&client),
-        check_caddy_health(&client),
+    let (
+        (docker_health, docker_time),
+        (ollama_health, ollama_time),
+        (webui_health, webui_time),
+        (caddy_health, caddy_time),
+    ) = tokio::join!(
+        timed(check_docker_health(host)),
+        timed(check_ollama_health(host, &client)),
+        timed(check_webui_health(host, &client)),
+        timed(check_caddy_health(host, &client)),
     );
 
-    Ok(HealthReport {
-        docker: docker_health,
-        ollama: ollama_health,
-        webui: webui_health,
-        caddy: caddy_health,
-    })
+    Ok((
+        HealthReport {
+            docker: docker_health,
+            ollama: ollama_health,
+            webui: webui_health,
+            caddy: caddy_health,
+        },
+        HealthTimings {
+            docker: docker_time,
+            ollama: ollama_time,
+            webui: webui_time,
+            caddy: caddy_time,
+        },
+    ))
 }
 
-async fn check_docker_health() -> ServiceHealth {
-    let output = timeout(
-        CMD_TIMEOUT,
-        TokioCommand::new("docker").args(["info"]).output(),
-    )
-    .await;
+async fn timed<F: Future<Output = ServiceHealth>>(check: F) -> (ServiceHealth, Duration) {
+    let start = Instant::now();
+    let health = check.await;
+    (health, start.elapsed())
+}
 
-    match output {
-        Ok(Ok(o)) if o.status.success() => ServiceHealth {
-            name: "Docker".to_string(),
-            status: HealthStatus::Healthy,
-            message: Some("Docker daemon running".to_string()),
-        },
-        Ok(Ok(_)) => ServiceHealth {
-            name: "Docker".to_string(),
-            status: HealthStatus::Unhealthy,
-            message: Some("Docker daemon not responding".to_string()),
-        },
-        Ok(Err(_)) => ServiceHealth {
-            name: "Docker".to_string(),
-            status: HealthStatus::Unknown,
-            message: Some("Docker not installed".to_string()),
-        },
-        Err(_) => ServiceHealth {
-            name: "Docker".to_string(),
-            status: HealthStatus::Unhealthy,
-            message: Some("Docker check timed out (5s)".to_string()),
-        },
+/// Health of the active container runtime (Docker or Podman)
+async fn check_docker_health(host: &Host) -> ServiceHealth {
+    let runtime = container::current(host).await;
+    let name = runtime.display_name();
+    let health = |status, message: String| ServiceHealth {
+        name: name.to_string(),
+        status,
+        message: Some(message),
+    };
+
+    if !runtime.installed() {
+        return health(HealthStatus::Unknown, format!("{} not installed", name));
+    }
+    match timeout(CMD_TIMEOUT, runtime.ping()).await {
+        Ok(true) => health(HealthStatus::Healthy, format!("{} engine running", name)),
+        Ok(false) => health(
+            HealthStatus::Unhealthy,
+            format!("{} engine not responding", name),
+        ),
+        Err(_) => health(
+            HealthStatus::Unhealthy,
+            format!("{} check timed out (5s)", name),
+        ),
     }
 }
 
-async fn check_ollama_health(client: &reqwest::Client) -> ServiceHealth {
-    match client.get("http://localhost:11434/api/tags").send().await {
-        Ok(response) if response.status().is_success() => ServiceHealth {
-            name: "Ollama".to_string(),
-            status: HealthStatus::Healthy,
-            message: Some("Ollama running".to_string()),
-        },
-        Ok(_) => ServiceHealth {
-            name: "Ollama".to_string(),
-            status: HealthStatus::Unhealthy,
-            message: Some("Ollama not responding correctly".to_string()),
-        },
-        Err(_) => ServiceHealth {
-            name: "Ollama".to_string(),
-            status: HealthStatus::Unhealthy,
-            message: Some("Cannot connect to Ollama".to_string()),
-        },
+/// Health of the active profile's model server (Ollama or OpenAI-compatible)
+async fn check_ollama_health(host: &Host, client: &reqwest::Client) -> ServiceHealth {
+    let backend = ollama::backend::current(host);
+    let name = backend.display_name();
+    let health = |status, message: String| ServiceHealth {
+        name: name.to_string(),
+        status,
+        message: Some(message),
+    };
+
+    match backend.probe(client).send().await {
+        Ok(response) if response.status().is_success() => {
+            health(HealthStatus::Healthy, format!("{} running", name))
+        }
+        Ok(_) => health(
+            HealthStatus::Unhealthy,
+            format!("{} not responding correctly", name),
+        ),
+        Err(_) => health(
+            HealthStatus::Unhealthy,
+            format!("Cannot connect to {}", name),
+        ),
     }
 }
 
-async fn check_webui_health(client: &reqwest::Client) -> ServiceHealth {
+async fn check_webui_health(host: &Host, client: &reqwest::Client) -> ServiceHealth {
     let urls = [
-        "https://dark-gpt.local/health",
-        "http://localhost:3002/health",
+        format!("{}/health", host.profile().web_url()),
+        "http://localhost:3002/health".to_string(),
     ];
 
     for url in urls {
-        if let Ok(response) = client.get(url).send().await {
+        if let Ok(response) = client.get(&url).send().await {
             if response.status().is_success() {
                 return ServiceHealth {
                     name: "Open-WebUI".to_string(),
@@ -130,8 +173,9 @@ async fn check_webui_health(client: &reqwest::Client) -> ServiceHealth {
     }
 }
 
-async fn check_caddy_health(client: &reqwest::Client) -> ServiceHealth {
-    match client.get("https://dark-gpt.local/health").send().await {
+async fn check_caddy_health(host: &Host, client: &reqwest::Client) -> ServiceHealth {
+    let url = format!("{}/health", host.profile().web_url());
+    match client.get(url).send().await {
         Ok(response) if response.status().is_success() => ServiceHealth {
             name: "Caddy".to_string(),
             status: HealthStatus::Healthy,
@@ -152,21 +196,111 @@ async fn check_caddy_health(client: &reqwest::Client) -> ServiceHealth {
 
 /// Get the WebUI URL
 #[tauri::command]
-pub async fn get_webui_url() -> Result<String, String> {
+pub async fn get_webui_url(host: State<'_, Host>) -> AppResult<String> {
+    webui_url(&host).await
+}
+
+/// The active profile's HTTPS URL if it answers, else the direct port
+pub(crate) async fn webui_url(host: &Host) -> AppResult<String> {
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(2))
         .danger_accept_invalid_certs(true)
         .build()
         .map_err(|e| format!("Client error: {}", e))?;
 
-    if client
-        .get("https://dark-gpt.local")
-        .send()
-        .await
-        .is_ok()
-    {
-        return Ok("https://dark-gpt.local".to_string());
+    let url = host.profile().web_url();
+    if client.get(&url).send().await.is_ok() {
+        return Ok(url);
     }
 
     Ok("http://localhost:3002".to_string())
 }
+
+/// Current auto-heal policy
+#[tauri::command]
+pub fn get_auto_heal_policy(healer: State<'_, AutoHealer>) -> AutoHealPolicy {
+    healer.policy()
+}
+
+/// Replace and save the auto-heal policy; enabling it starts the background
+/// monitor
+#[tauri::command]
+pub fn set_auto_heal_policy(
+    app: AppHandle,
+    healer: State<'_, AutoHealer>,
+    store: State<'_, SettingsStore>,
+    policy: AutoHealPolicy,
+) -> AppResult<AutoHealPolicy> {
+    let details = serde_json::to_value(&policy).unwrap_or_default();
+    let result = set_policy(&app, &healer, &store, policy);
+    audit::recorded("auto_heal.set_policy", details, result)
+}
+
+fn set_policy(
+    app: &AppHandle,
+    healer: &AutoHealer,
+    store: &SettingsStore,
+    policy: AutoHealPolicy,
+) -> AppResult<AutoHealPolicy> {
+    policy.validate().map_err(|(field, message)| {
+        AppError::new(ErrorCode::InvalidInput, format!("{} {}", field, message)).details(
+            ErrorDetails::Field {
+                field: format!("auto_heal.{}", field),
+            },
+        )
+    })?;
+    healer.apply(app, policy.clone())?;
+    store.save(AppSettings {
+        auto_heal: policy,
+        ..store.get()
+    })?;
+    Ok(healer.policy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    #[tokio::test]
+    async fn runtime_health_follows_the_engine() {
+        let fake = FakeRunner::new();
+        let health = check_docker_health(&fake.host("http://127.0.0.1:9")).await;
+        assert_eq!(health.status, HealthStatus::Unknown);
+
+        fake.installed("docker").on("docker info", "Server: ...");
+        let health = check_docker_health(&fake.host("http://127.0.0.1:9")).await;
+        assert_eq!(health.status, HealthStatus::Healthy);
+
+        let down = FakeRunner::new();
+        down.installed("docker")
+            .fail("docker info", "Cannot connect to the Docker daemon");
+        let health = check_docker_health(&down.host("http://127.0.0.1:9")).await;
+        assert_eq!(health.status, HealthStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn ollama_health_follows_the_api() {
+        let client = reqwest::Client::new();
+        let stub = StubOllama::start().await;
+        stub.route(Method::GET, "/api/tags", 200, r#"{"models":[]}"#);
+        let host = FakeRunner::new().host(&stub.url);
+        let health = check_ollama_health(&host, &client).await;
+        assert_eq!(health.status, HealthStatus::Healthy);
+
+        let broken = StubOllama::start().await;
+        broken.route(Method::GET, "/api/tags", 500, "");
+        let host = FakeRunner::new().host(&broken.url);
+        let health = check_ollama_health(&host, &client).await;
+        assert_eq!(health.status, HealthStatus::Unhealthy);
+        assert_eq!(
+            health.message.as_deref(),
+            Some("Ollama not responding correctly")
+        );
+
+        let host = FakeRunner::new().host("http://127.0.0.1:9");
+        let health = check_ollama_health(&host, &client).await;
+        assert_eq!(health.message.as_deref(), Some("Cannot connect to Ollama"));
+    }
+}
@@ -1,14 +1,23 @@
-// Auto-installer for dependencies (Docker Desktop, Ollama)
-// Windows-only silent install with streaming progress events
+// Auto-installer for dependencies (Docker, Ollama)
+// Silent installers on Windows, package manager / release tarball on Linux
+// (elevated through pkexec), with streaming progress events
 
+use reqwest::{header, StatusCode};
 use serde::Serialize;
-use std::path::PathBuf;
-use tauri::{Emitter, Window};
-use tokio::io::AsyncWriteExt;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{Emitter, Manager, Window};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::host::Host;
+use crate::services::{audit, lifecycle};
 
-const OLLAMA_INSTALLER_URL: &str = "https://ollama.com/download/OllamaSetup.exe";
 const DOCKER_INSTALLER_URL: &str =
     "https://desktop.docker.com/win/main/amd64/Docker%20Desktop%20Installer.exe";
+const OLLAMA_RELEASE_API: &str = "https://api.github.com/repos/ollama/ollama/releases/latest";
+const OLLAMA_DOWNLOAD_BASE: &str = "https://github.com/ollama/ollama/releases/download";
+const DOWNLOAD_ATTEMPTS: u32 = 5;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct InstallProgress {
@@ -48,7 +57,7 @@ fn emit_progress(
     );
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
     if bytes == 0 {
         return "0 B".to_string();
     }
@@ -59,94 +68,396 @@ fn format_bytes(bytes: u64) -> String {
     format!("{:.1} {}", bytes as f64 / k.powi(i as i32), units[i])
 }
 
-/// Download a file with chunked streaming + progress events.
+/// What a download must match before anything runs it
+#[derive(Debug, Default)]
+struct Expected {
+    /// Hex SHA-256 from a checksum manifest
+    sha256: Option<String>,
+    /// Required Authenticode signer (substring of the certificate subject),
+    /// checked on Windows only
+    signer: Option<&'static str>,
+}
+
+/// Create a private, uniquely named temp directory for an installer.
+/// It is deleted (with the download) when the returned guard is dropped.
+fn download_dir(dependency: &str) -> Result<tempfile::TempDir, String> {
+    let dir = tempfile::Builder::new()
+        .prefix(&format!("dark-gpt-{}-", dependency))
+        .tempdir()
+        .map_err(|e| format!("Cannot create temp directory: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Cannot restrict temp directory: {}", e))?;
+    }
+    Ok(dir)
+}
+
+/// Download a file with chunked streaming + progress events, resuming with
+/// HTTP Range requests after interruptions, then verify it against
+/// `expected`. On any failure the file is deleted.
 async fn download_file(
     window: &Window,
     dependency: &str,
     url: &str,
-    dest: &PathBuf,
-) -> Result<(), String> {
+    dest: &Path,
+    expected: &Expected,
+) -> AppResult<()> {
+    let host = window.state::<Host>().inner().clone();
+    let progress = |downloaded: u64, total: u64, message: &str| {
+        emit_progress(
+            window,
+            dependency,
+            "downloading",
+            downloaded,
+            total,
+            message,
+        )
+    };
+    download(&host, dependency, url, dest, expected, &progress).await
+}
+
+/// `download_file` without the window: progress goes to `on_progress`
+/// as (downloaded, total, message)
+async fn download(
+    host: &Host,
+    dependency: &str,
+    url: &str,
+    dest: &Path,
+    expected: &Expected,
+    on_progress: &Progress<'_>,
+) -> AppResult<()> {
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(600))
+        .connect_timeout(std::time::Duration::from_secs(30))
+        .read_timeout(std::time::Duration::from_secs(60))
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
 
-    let mut response = client
-        .get(url)
-        .send()
+    let mut file = tokio::fs::File::create(dest)
         .await
-        .map_err(|e| format!("Download request failed: {}", e))?;
+        .map_err(|e| format!("Cannot create temp file: {}", e))?;
+    let mut progress = (0u64, 0u64); // (downloaded, total)
+
+    let mut attempt = 1;
+    let result = loop {
+        match fetch(&client, url, &mut file, &mut progress, on_progress).await {
+            Ok(()) => break Ok(()),
+            Err(Fetch::Retry(e)) if attempt < DOWNLOAD_ATTEMPTS => {
+                tracing::warn!("Download of {} interrupted ({}), resuming", url, e);
+                on_progress(
+                    progress.0,
+                    progress.1,
+                    &format!(
+                        "Connection lost, resuming ({}/{})...",
+                        attempt, DOWNLOAD_ATTEMPTS
+                    ),
+                );
+                tokio::time::sleep(std::time::Duration::from_secs(2u64.pow(attempt))).await;
+                attempt += 1;
+            }
+            Err(Fetch::Retry(e)) | Err(Fetch::Fatal(e)) => {
+                break Err(AppError::new(ErrorCode::Network, "Download failed")
+                    .context(format!("Downloading {}", dependency))
+                    .cause(e)
+                    .remediation("Check your internet connection and retry."))
+            }
+        }
+    };
+    let result = match result {
+        Ok(()) => file.flush().await.map_err(AppError::from),
+        Err(e) => Err(e),
+    };
+    drop(file);
 
-    if !response.status().is_success() {
-        return Err(format!("HTTP {} — download failed", response.status()));
+    let result = match result {
+        Ok(()) => verify_download(host, dest, expected).await,
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(dest).await;
     }
+    result
+}
 
-    let total = response.content_length().unwrap_or(0);
-    let mut downloaded: u64 = 0;
+/// Download progress sink: (downloaded, total, message)
+type Progress<'a> = dyn Fn(u64, u64, &str) + Sync + 'a;
 
-    let mut file = tokio::fs::File::create(dest)
+enum Fetch {
+    /// Network hiccup: try again from where we stopped
+    Retry(String),
+    Fatal(String),
+}
+
+/// One request of a (possibly resumed) download, appending to `file`
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    file: &mut tokio::fs::File,
+    (downloaded, total): &mut (u64, u64),
+    on_progress: &Progress<'_>,
+) -> Result<(), Fetch> {
+    let mut request = client.get(url);
+    if *downloaded > 0 {
+        request = request.header(header::RANGE, format!("bytes={}-", downloaded));
+    }
+    let mut response = request
+        .send()
         .await
-        .map_err(|e| format!("Cannot create temp file: {}", e))?;
+        .map_err(|e| Fetch::Retry(format!("Download request failed: {}", e)))?;
+
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => {
+            // Content-Range: bytes <start>-<end>/<total>
+            let range = response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            let start = range
+                .strip_prefix("bytes ")
+                .and_then(|r| r.split('-').next())
+                .and_then(|s| s.parse::<u64>().ok());
+            if start != Some(*downloaded) {
+                return Err(Fetch::Fatal(format!(
+                    "Server resumed at the wrong offset ({})",
+                    range
+                )));
+            }
+            if let Some(size) = range.rsplit('/').next().and_then(|s| s.parse().ok()) {
+                *total = size;
+            }
+        }
+        status if status.is_success() => {
+            if *downloaded > 0 {
+                // Range not supported: start over
+                tracing::info!("Server ignored Range request, restarting download");
+                file.set_len(0)
+                    .await
+                    .and(file.seek(std::io::SeekFrom::Start(0)).await.map(|_| ()))
+                    .map_err(|e| Fetch::Fatal(format!("Write error: {}", e)))?;
+                *downloaded = 0;
+            }
+            *total = response.content_length().unwrap_or(0);
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE if *total > 0 && *downloaded == *total => {
+            return Ok(());
+        }
+        status if status.is_server_error() => {
+            return Err(Fetch::Retry(format!("HTTP {} — download failed", status)));
+        }
+        status => return Err(Fetch::Fatal(format!("HTTP {} — download failed", status))),
+    }
 
     while let Some(chunk) = response
         .chunk()
         .await
-        .map_err(|e| format!("Download stream error: {}", e))?
+        .map_err(|e| Fetch::Retry(format!("Download stream error: {}", e)))?
     {
         file.write_all(&chunk)
             .await
-            .map_err(|e| format!("Write error: {}", e))?;
-        downloaded += chunk.len() as u64;
-        emit_progress(
-            window,
-            dependency,
-            "downloading",
-            downloaded,
-            total,
+            .map_err(|e| Fetch::Fatal(format!("Write error: {}", e)))?;
+        *downloaded += chunk.len() as u64;
+        on_progress(
+            *downloaded,
+            *total,
             &format!(
                 "Downloading... {} / {}",
-                format_bytes(downloaded),
-                format_bytes(total)
+                format_bytes(*downloaded),
+                format_bytes(*total)
             ),
         );
     }
 
-    file.flush()
-        .await
-        .map_err(|e| format!("Flush error: {}", e))?;
+    if *total > 0 && *downloaded < *total {
+        return Err(Fetch::Retry("Connection closed early".to_string()));
+    }
+    Ok(())
+}
+
+/// Check size, checksum and signature of a finished download
+async fn verify_download(host: &Host, path: &Path, expected: &Expected) -> AppResult<()> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
 
     // Sanity check: file shouldn't be empty
-    let meta = tokio::fs::metadata(dest)
+    let meta = tokio::fs::metadata(path)
         .await
         .map_err(|e| format!("Cannot stat downloaded file: {}", e))?;
     if meta.len() < 1024 {
-        let _ = tokio::fs::remove_file(dest).await;
-        return Err("Downloaded file is suspiciously small — aborting".to_string());
+        return Err(AppError::new(
+            ErrorCode::IntegrityCheckFailed,
+            "Downloaded file is suspiciously small — aborting",
+        ));
+    }
+
+    if let Some(expected_sha) = &expected.sha256 {
+        let owned = path.to_path_buf();
+        let actual = tokio::task::spawn_blocking(move || sha256_file(&owned))
+            .await
+            .map_err(|e| format!("Checksum task failed: {}", e))?
+            .map_err(|e| format!("Cannot read downloaded file: {}", e))?;
+        if !actual.eq_ignore_ascii_case(expected_sha) {
+            tracing::error!(
+                "SHA-256 mismatch for {}: expected {}, got {}",
+                name,
+                expected_sha,
+                actual
+            );
+            return Err(AppError::new(
+                ErrorCode::IntegrityCheckFailed,
+                format!(
+                    "Integrity check failed: {} has SHA-256 {} but the release lists {}. \
+                     The download was deleted and nothing was run.",
+                    name, actual, expected_sha
+                ),
+            )
+            .remediation("Retry the install; if it keeps failing, download it manually."));
+        }
+        tracing::info!("SHA-256 verified for {}", name);
+    }
+
+    if let Some(signer) = expected.signer {
+        if cfg!(target_os = "windows") {
+            verify_authenticode(host, path, signer).await?;
+            tracing::info!("Authenticode signature verified for {}", name);
+        }
     }
+    Ok(())
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Require a valid Authenticode signature whose subject contains `signer`
+async fn verify_authenticode(host: &Host, path: &Path, signer: &str) -> AppResult<()> {
+    // Path goes through the environment, not the script text
+    let output = host
+        .command("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "$s = Get-AuthenticodeSignature -LiteralPath $env:DARKGPT_VERIFY_PATH; \
+             \"$($s.Status)|$($s.SignerCertificate.Subject)\"",
+        ])
+        .envs([("DARKGPT_VERIFY_PATH", path.to_string_lossy())])
+        .output()
+        .await
+        .map_err(|e| format!("Cannot check installer signature: {}", e))?;
 
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (status, subject) = stdout.trim().split_once('|').unwrap_or(("Unknown", ""));
+    if status != "Valid" || !subject.contains(signer) {
+        return Err(AppError::new(
+            ErrorCode::IntegrityCheckFailed,
+            format!(
+                "Integrity check failed: installer signature is '{}' (signer: '{}'), expected \
+                 a valid signature from {}. The download was deleted and nothing was run.",
+                status, subject, signer
+            ),
+        ));
+    }
     Ok(())
 }
 
+/// Latest Ollama release tag and its `sha256sum.txt` (file name → hex digest)
+async fn ollama_release() -> Result<(String, HashMap<String, String>), String> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("dark-gpt/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let release: serde_json::Value = client
+        .get(OLLAMA_RELEASE_API)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Cannot look up the latest Ollama release: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid release info: {}", e))?;
+    let tag = release["tag_name"]
+        .as_str()
+        .ok_or("Release info has no tag")?
+        .to_string();
+
+    let manifest = client
+        .get(format!("{}/{}/sha256sum.txt", OLLAMA_DOWNLOAD_BASE, tag))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Cannot fetch Ollama checksums: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Cannot read Ollama checksums: {}", e))?;
+
+    Ok((tag, parse_checksums(&manifest)))
+}
+
+/// Parse `sha256sum` output: `<hex>  [./|*]<file>` per line
+fn parse_checksums(manifest: &str) -> HashMap<String, String> {
+    manifest
+        .lines()
+        .filter_map(|line| {
+            let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+            let file = file.trim().trim_start_matches('*').trim_start_matches("./");
+            (hash.len() == 64).then(|| (file.to_string(), hash.to_lowercase()))
+        })
+        .collect()
+}
+
 // -- Ollama -------------------------------------------------------------------
 
-/// Download and install Ollama silently (Windows only).
+/// Download and install Ollama silently (Windows and Linux).
 #[tauri::command]
-pub async fn install_ollama(window: Window) -> Result<(), String> {
+pub async fn install_ollama(window: Window) -> AppResult<()> {
+    let result = install_ollama_here(window).await;
+    audit::recorded("ollama.install", serde_json::json!({}), result)
+}
+
+async fn install_ollama_here(window: Window) -> AppResult<()> {
+    let host = window.state::<Host>().inner().clone();
+    if cfg!(target_os = "linux") {
+        return install_ollama_linux(&window, &host).await;
+    }
     if cfg!(not(target_os = "windows")) {
         return Err(
-            "Auto-install is only available on Windows. Please install Ollama manually."
-                .to_string(),
+            "Auto-install is only available on Windows and Linux. Please install Ollama manually."
+                .to_string()
+                .into(),
         );
     }
 
     tracing::info!("Starting Ollama auto-install");
 
-    let temp_dir = std::env::temp_dir();
-    let installer_path = temp_dir.join("OllamaSetup.exe");
+    let temp_dir = download_dir("ollama")?;
+    let installer_path = temp_dir.path().join("OllamaSetup.exe");
 
-    // Phase 1: Download
+    // Phase 1: Download + verify against the release checksums
     emit_progress(&window, "ollama", "downloading", 0, 0, "Starting download...");
-    download_file(&window, "ollama", OLLAMA_INSTALLER_URL, &installer_path).await?;
+    let (tag, checksums) = ollama_release().await?;
+    let expected = Expected {
+        sha256: Some(
+            checksums
+                .get("OllamaSetup.exe")
+                .cloned()
+                .ok_or_else(|| format!("No checksum published for OllamaSetup.exe in {}", tag))?,
+        ),
+        signer: Some("Ollama"),
+    };
+    let url = format!("{}/{}/OllamaSetup.exe", OLLAMA_DOWNLOAD_BASE, tag);
+    download_file(&window, "ollama", &url, &installer_path, &expected).await?;
 
     // Phase 2: Silent install (Inno Setup flags)
     emit_progress(
@@ -158,7 +469,8 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
         "Installing Ollama (this may take a moment)...",
     );
 
-    let output = tokio::process::Command::new(&installer_path)
+    let output = host
+        .command(&installer_path.to_string_lossy())
         .args(["/VERYSILENT", "/NORESTART", "/SP-"])
         .output()
         .await
@@ -166,8 +478,7 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        let _ = tokio::fs::remove_file(&installer_path).await;
-        return Err(format!("Ollama installer failed: {}", stderr));
+        return Err(AppError::new(ErrorCode::Internal, "Ollama installer failed").cause(stderr));
     }
 
     // Phase 3: Verify — poll the API
@@ -194,11 +505,7 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
         );
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
 
-        if let Ok(resp) = client
-            .get("http://localhost:11434/api/version")
-            .send()
-            .await
-        {
+        if let Ok(resp) = client.get(host.ollama("/api/version")).send().await {
             if resp.status().is_success() {
                 verified = true;
                 break;
@@ -216,9 +523,9 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
             0,
             "Starting Ollama service manually...",
         );
-        let _ = tokio::process::Command::new("ollama")
-            .arg("serve")
-            .spawn();
+        if let Ok(child) = host.command("ollama").arg("serve").to_tokio().spawn() {
+            lifecycle::record_ollama_spawned(child);
+        }
 
         for attempt in 1..=15 {
             emit_progress(
@@ -231,11 +538,7 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
             );
             tokio::time::sleep(std::time::Duration::from_secs(1)).await;
 
-            if let Ok(resp) = client
-                .get("http://localhost:11434/api/version")
-                .send()
-                .await
-            {
+            if let Ok(resp) = client.get(host.ollama("/api/version")).send().await {
                 if resp.status().is_success() {
                     verified = true;
                     break;
@@ -244,9 +547,6 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
         }
     }
 
-    // Cleanup
-    let _ = tokio::fs::remove_file(&installer_path).await;
-
     if verified {
         emit_progress(
             &window,
@@ -267,10 +567,11 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
             0,
             "Ollama installed but service not responding",
         );
-        Err(
-            "Ollama installed but could not verify the service is running. Try restarting."
-                .to_string(),
+        Err(AppError::new(
+            ErrorCode::OllamaUnreachable,
+            "Ollama installed but could not verify the service is running",
         )
+        .remediation("Try restarting your computer."))
     }
 }
 
@@ -278,19 +579,30 @@ pub async fn install_ollama(window: Window) -> Result<(), String> {
 
 /// Download and install Docker Desktop silently (Windows only).
 /// The UAC prompt will still appear — that's expected.
+/// On Linux, installs Docker Engine from the distro packages instead.
 #[tauri::command]
-pub async fn install_docker(window: Window) -> Result<(), String> {
+pub async fn install_docker(window: Window) -> AppResult<()> {
+    let result = install_docker_here(window).await;
+    audit::recorded("docker.install", serde_json::json!({}), result)
+}
+
+async fn install_docker_here(window: Window) -> AppResult<()> {
+    let host = window.state::<Host>().inner().clone();
+    if cfg!(target_os = "linux") {
+        return install_docker_linux(&window, &host).await;
+    }
     if cfg!(not(target_os = "windows")) {
         return Err(
-            "Auto-install is only available on Windows. Please install Docker manually."
-                .to_string(),
+            "Auto-install is only available on Windows and Linux. Please install Docker manually."
+                .to_string()
+                .into(),
         );
     }
 
     tracing::info!("Starting Docker Desktop auto-install");
 
-    let temp_dir = std::env::temp_dir();
-    let installer_path = temp_dir.join("DockerDesktopInstaller.exe");
+    let temp_dir = download_dir("docker")?;
+    let installer_path = temp_dir.path().join("DockerDesktopInstaller.exe");
 
     // Phase 1: Download
     emit_progress(
@@ -301,7 +613,19 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
         0,
         "Starting download...",
     );
-    download_file(&window, "docker", DOCKER_INSTALLER_URL, &installer_path).await?;
+    // Docker publishes no stable checksum URL; rely on its code signature
+    let expected = Expected {
+        sha256: None,
+        signer: Some("Docker Inc"),
+    };
+    download_file(
+        &window,
+        "docker",
+        DOCKER_INSTALLER_URL,
+        &installer_path,
+        &expected,
+    )
+    .await?;
 
     // Phase 2: Silent install (will show UAC prompt)
     emit_progress(
@@ -313,7 +637,8 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
         "Installing Docker Desktop (accept the UAC prompt)...",
     );
 
-    let output = tokio::process::Command::new(&installer_path)
+    let output = host
+        .command(&installer_path.to_string_lossy())
         .args(["install", "--quiet", "--accept-license"])
         .output()
         .await
@@ -323,8 +648,6 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
     let stderr_lower = stderr.to_lowercase();
 
     if !output.status.success() {
-        let _ = tokio::fs::remove_file(&installer_path).await;
-
         // Docker often requires a restart after first install
         if stderr_lower.contains("restart") || stderr_lower.contains("reboot") {
             emit_progress(
@@ -337,11 +660,12 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
             );
             return Err(
                 "Docker Desktop installed but a system restart is required to complete setup."
-                    .to_string(),
+                    .to_string()
+                    .into(),
             );
         }
 
-        return Err(format!("Docker installer failed: {}", stderr));
+        return Err(AppError::new(ErrorCode::Internal, "Docker installer failed").cause(stderr));
     }
 
     // Phase 3: Start Docker Desktop
@@ -354,7 +678,7 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
         "Starting Docker Desktop...",
     );
     let docker_exe = r"C:\Program Files\Docker\Docker\Docker Desktop.exe";
-    let _ = tokio::process::Command::new(docker_exe).spawn();
+    let _ = host.command(docker_exe).to_tokio().spawn();
 
     // Phase 4: Wait for daemon — docker info
     let mut verified = false;
@@ -369,10 +693,7 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
         );
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
 
-        let check = tokio::process::Command::new("docker")
-            .arg("info")
-            .output()
-            .await;
+        let check = host.command("docker").arg("info").output().await;
 
         if let Ok(out) = check {
             if out.status.success() {
@@ -382,9 +703,6 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
         }
     }
 
-    // Cleanup
-    let _ = tokio::fs::remove_file(&installer_path).await;
-
     // Restart hint from stderr
     if stderr_lower.contains("restart") || stderr_lower.contains("reboot") {
         emit_progress(
@@ -423,3 +741,512 @@ pub async fn install_docker(window: Window) -> Result<(), String> {
 
     Ok(())
 }
+
+// -- Linux --------------------------------------------------------------------
+
+/// Package manager used to install Docker Engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PackageManager {
+    Apt,
+    /// Fedora, which packages Docker as moby-engine
+    Dnf,
+    /// RHEL and its rebuilds ship no Docker; docker-ce comes from Docker's
+    /// own repository (`rhel` for RHEL itself, `centos` for the rest)
+    DnfDockerCe {
+        rhel: bool,
+    },
+    Pacman,
+}
+
+impl PackageManager {
+    /// Root shell script installing Docker Engine + the compose plugin
+    fn docker_install_script(self) -> &'static str {
+        match self {
+            PackageManager::Apt => {
+                "apt-get update && \
+                 DEBIAN_FRONTEND=noninteractive apt-get install -y docker.io && \
+                 (DEBIAN_FRONTEND=noninteractive apt-get install -y docker-compose-v2 || \
+                  DEBIAN_FRONTEND=noninteractive apt-get install -y docker-compose-plugin)"
+            }
+            PackageManager::Dnf => "dnf install -y moby-engine docker-compose",
+            PackageManager::DnfDockerCe { rhel } => {
+                if rhel {
+                    "dnf install -y dnf-plugins-core && \
+                     dnf config-manager --add-repo https://download.docker.com/linux/rhel/docker-ce.repo && \
+                     dnf install -y docker-ce docker-ce-cli containerd.io docker-compose-plugin"
+                } else {
+                    "dnf install -y dnf-plugins-core && \
+                     dnf config-manager --add-repo https://download.docker.com/linux/centos/docker-ce.repo && \
+                     dnf install -y docker-ce docker-ce-cli containerd.io docker-compose-plugin"
+                }
+            }
+            PackageManager::Pacman => "pacman -Sy --noconfirm --needed docker docker-compose",
+        }
+    }
+}
+
+/// `ID` and `ID_LIKE` from /etc/os-release
+#[derive(Debug, Default)]
+struct LinuxDistro {
+    id: String,
+    id_like: Vec<String>,
+    pretty_name: String,
+}
+
+impl LinuxDistro {
+    fn detect() -> Self {
+        std::fs::read_to_string("/etc/os-release")
+            .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut distro = LinuxDistro::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "ID" => distro.id = value.to_lowercase(),
+                "ID_LIKE" => {
+                    distro.id_like = value.split_whitespace().map(str::to_lowercase).collect()
+                }
+                "PRETTY_NAME" => distro.pretty_name = value,
+                _ => {}
+            }
+        }
+        distro
+    }
+
+    fn package_manager(&self) -> Option<PackageManager> {
+        let is = |names: &[&str]| {
+            std::iter::once(&self.id)
+                .chain(&self.id_like)
+                .any(|id| names.contains(&id.as_str()))
+        };
+        // Before Fedora: the RHEL family lists it in ID_LIKE
+        if is(&["debian", "ubuntu"]) {
+            Some(PackageManager::Apt)
+        } else if is(&["rhel", "centos", "rocky", "almalinux", "ol"]) {
+            Some(PackageManager::DnfDockerCe {
+                rhel: self.id == "rhel",
+            })
+        } else if is(&["fedora"]) {
+            Some(PackageManager::Dnf)
+        } else if is(&["arch"]) {
+            Some(PackageManager::Pacman)
+        } else {
+            None
+        }
+    }
+}
+
+/// systemd unit matching the one from Ollama's install.sh
+const OLLAMA_SYSTEMD_UNIT: &str = "[Unit]
+Description=Ollama Service
+After=network-online.target
+
+[Service]
+ExecStart=/usr/local/bin/ollama serve
+User=ollama
+Group=ollama
+Restart=always
+RestartSec=3
+Environment=\"PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin\"
+
+[Install]
+WantedBy=default.target
+";
+
+/// Run a shell script as root through pkexec (one polkit prompt).
+/// `args` are passed as positional parameters ($1, $2, ...), never
+/// interpolated into the script.
+pub(crate) async fn run_elevated(host: &Host, script: &str, args: &[&str]) -> AppResult<()> {
+    let output = host
+        .command("pkexec")
+        .args(["sh", "-c", script, "sh"])
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                "pkexec not found. Install polkit, or run the command manually with sudo."
+                    .to_string()
+            } else {
+                format!("Failed to run pkexec: {}", e)
+            }
+        })?;
+
+    match output.status.code() {
+        Some(0) => Ok(()),
+        // pkexec: 126 = authorization dismissed, 127 = not authorized
+        Some(126) | Some(127) => Err(AppError::new(
+            ErrorCode::PermissionDenied,
+            "Administrator authorization was cancelled",
+        )),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+            Err(AppError::new(ErrorCode::Internal, "Elevated command failed")
+                .cause(tail.into_iter().rev().collect::<Vec<_>>().join("\n")))
+        }
+    }
+}
+
+fn current_user() -> Result<String, String> {
+    std::env::var("USER")
+        .ok()
+        .filter(|u| !u.is_empty() && u != "root")
+        .ok_or_else(|| "Cannot determine the current user".to_string())
+}
+
+/// Whether this session already has the `docker` group (a new group only
+/// applies after logging in again)
+async fn session_in_docker_group(host: &Host) -> bool {
+    host.command("id")
+        .arg("-nG")
+        .output()
+        .await
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split_whitespace()
+                .any(|g| g == "docker")
+        })
+        .unwrap_or(false)
+}
+
+async fn install_docker_linux(window: &Window, host: &Host) -> AppResult<()> {
+    let distro = LinuxDistro::detect();
+    let manager = distro.package_manager().ok_or_else(|| {
+        format!(
+            "Unsupported distribution '{}'. Install Docker Engine with your package \
+             manager (see https://docs.docker.com/engine/install/), then retry.",
+            distro.pretty_name
+        )
+    })?;
+    let user = current_user()?;
+    tracing::info!(
+        "Installing Docker Engine on {} via {:?}",
+        distro.pretty_name,
+        manager
+    );
+
+    emit_progress(
+        window,
+        "docker",
+        "installing",
+        0,
+        0,
+        &format!(
+            "Installing Docker Engine for {} (enter your password)...",
+            distro.pretty_name
+        ),
+    );
+
+    let script = format!(
+        "set -e\n{}\nsystemctl enable --now docker\nusermod -aG docker \"$1\"",
+        manager.docker_install_script()
+    );
+    if let Err(e) = run_elevated(host, &script, &[&user]).await {
+        emit_progress(window, "docker", "error", 0, 0, &e.describe());
+        return Err(e);
+    }
+
+    emit_progress(
+        window,
+        "docker",
+        "verifying",
+        0,
+        0,
+        "Waiting for Docker daemon...",
+    );
+    let mut daemon_up = false;
+    for _ in 0..30 {
+        let status = host
+            .command("systemctl")
+            .args(["is-active", "--quiet", "docker"])
+            .output()
+            .await;
+        if status.is_ok_and(|o| o.status.success()) {
+            daemon_up = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    if !daemon_up {
+        tracing::warn!("Docker installed but the service is not active yet");
+    }
+
+    let message = if session_in_docker_group(host).await {
+        "Docker Engine installed and running!".to_string()
+    } else {
+        format!(
+            "Docker installed. {} was added to the 'docker' group: log out and back in \
+             (or reboot) before starting services.",
+            user
+        )
+    };
+    emit_progress(window, "docker", "done", 1, 1, &message);
+    tracing::info!("{}", message);
+    Ok(())
+}
+
+async fn install_ollama_linux(window: &Window, host: &Host) -> AppResult<()> {
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        other => return Err(format!("No Ollama build for {}", other).into()),
+    };
+    let user = current_user()?;
+    tracing::info!("Starting Ollama auto-install (linux-{})", arch);
+
+    emit_progress(window, "ollama", "downloading", 0, 0, "Starting download...");
+    let (tag, checksums) = ollama_release().await?;
+    // Newer releases ship .tar.zst instead of .tgz
+    let (asset, sha256) = [
+        format!("ollama-linux-{}.tgz", arch),
+        format!("ollama-linux-{}.tar.zst", arch),
+    ]
+    .into_iter()
+    .find_map(|name| checksums.get(&name).cloned().map(|sha| (name, sha)))
+    .ok_or_else(|| format!("No linux-{} build with a checksum in Ollama {}", arch, tag))?;
+
+    let temp_dir = download_dir("ollama")?;
+    let tarball = temp_dir.path().join(&asset);
+    let expected = Expected {
+        sha256: Some(sha256),
+        signer: None,
+    };
+    let url = format!("{}/{}/{}", OLLAMA_DOWNLOAD_BASE, tag, asset);
+    download_file(window, "ollama", &url, &tarball, &expected).await?;
+
+    emit_progress(
+        window,
+        "ollama",
+        "installing",
+        0,
+        0,
+        "Installing Ollama to /usr/local (enter your password)...",
+    );
+
+    // $1 = tarball, $2 = desktop user, $3 = unit file contents
+    let script = r#"set -e
+tar -C /usr/local -xf "$1"
+if ! id ollama >/dev/null 2>&1; then
+    useradd -r -s /bin/false -U -m -d /usr/share/ollama ollama
+fi
+getent group render >/dev/null 2>&1 && usermod -a -G render ollama
+getent group video >/dev/null 2>&1 && usermod -a -G video ollama
+usermod -a -G ollama "$2"
+printf '%s' "$3" > /etc/systemd/system/ollama.service
+systemctl daemon-reload
+systemctl enable --now ollama
+"#;
+    let tarball_arg = tarball.to_string_lossy().to_string();
+    if let Err(e) = run_elevated(host, script, &[&tarball_arg, &user, OLLAMA_SYSTEMD_UNIT]).await {
+        emit_progress(window, "ollama", "error", 0, 0, &e.describe());
+        return Err(e);
+    }
+
+    let client = reqwest::Client::new();
+    for attempt in 1..=30 {
+        emit_progress(
+            window,
+            "ollama",
+            "verifying",
+            attempt,
+            30,
+            &format!("Waiting for Ollama... ({}/30)", attempt),
+        );
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+        if let Ok(resp) = client.get(host.ollama("/api/version")).send().await {
+            if resp.status().is_success() {
+                emit_progress(
+                    window,
+                    "ollama",
+                    "done",
+                    1,
+                    1,
+                    "Ollama installed successfully!",
+                );
+                tracing::info!("Ollama installed and verified");
+                return Ok(());
+            }
+        }
+    }
+
+    emit_progress(
+        window,
+        "ollama",
+        "error",
+        0,
+        0,
+        "Ollama installed but service not responding",
+    );
+    Err(AppError::new(
+        ErrorCode::OllamaUnreachable,
+        "Ollama installed but the service did not start",
+    )
+    .remediation("Check `systemctl status ollama`."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    /// 2 KiB, over `verify_download`'s minimum size
+    fn payload() -> String {
+        (0..2048u32)
+            .map(|i| char::from(b'a' + (i % 26) as u8))
+            .collect()
+    }
+
+    /// `dest` holding `partial`, opened like `download` leaves it
+    async fn partial_file(dest: &Path, partial: &str) -> tokio::fs::File {
+        std::fs::write(dest, partial).unwrap();
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(dest)
+            .await
+            .unwrap();
+        file.seek(std::io::SeekFrom::End(0)).await.unwrap();
+        file
+    }
+
+    #[test]
+    fn picks_the_docker_source_for_each_distro() {
+        let manager = |os_release: &str| LinuxDistro::parse(os_release).package_manager();
+
+        assert_eq!(
+            manager("ID=ubuntu\nID_LIKE=debian"),
+            Some(PackageManager::Apt)
+        );
+        assert_eq!(manager("ID=fedora"), Some(PackageManager::Dnf));
+        assert_eq!(
+            manager("ID=\"rhel\"\nID_LIKE=\"fedora\""),
+            Some(PackageManager::DnfDockerCe { rhel: true })
+        );
+        assert_eq!(
+            manager("ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\""),
+            Some(PackageManager::DnfDockerCe { rhel: false })
+        );
+        assert_eq!(manager("ID=arch"), Some(PackageManager::Pacman));
+        assert_eq!(manager("ID=void"), None);
+    }
+
+    #[tokio::test]
+    async fn resumes_a_partial_download_with_content_range() {
+        let body = payload();
+        let stub = StubOllama::start().await;
+        stub.route_with_headers(
+            Method::GET,
+            "/OllamaSetup.exe",
+            206,
+            &[("content-range", "bytes 1000-2047/2048")],
+            &body[1000..],
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("OllamaSetup.exe");
+        let mut file = partial_file(&dest, &body[..1000]).await;
+        let mut progress = (1000, 2048);
+
+        let url = format!("{}/OllamaSetup.exe", stub.url);
+        let client = reqwest::Client::new();
+        let result = fetch(&client, &url, &mut file, &mut progress, &|_, _, _| {}).await;
+        assert!(result.is_ok());
+        file.flush().await.unwrap();
+
+        assert_eq!(stub.received()[0].headers["range"], "bytes=1000-");
+        assert_eq!(progress, (2048, 2048));
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn restarts_when_the_server_ignores_range() {
+        let body = payload();
+        let stub = StubOllama::start().await;
+        stub.route(Method::GET, "/OllamaSetup.exe", 200, &body);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("OllamaSetup.exe");
+        let mut file = partial_file(&dest, &"z".repeat(1000)).await;
+        let mut progress = (1000, 2048);
+
+        let url = format!("{}/OllamaSetup.exe", stub.url);
+        let client = reqwest::Client::new();
+        let result = fetch(&client, &url, &mut file, &mut progress, &|_, _, _| {}).await;
+        assert!(result.is_ok());
+        file.flush().await.unwrap();
+
+        assert_eq!(progress, (2048, 2048));
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn deletes_a_download_with_the_wrong_checksum() {
+        let body = payload();
+        let stub = StubOllama::start().await;
+        stub.route(Method::GET, "/OllamaSetup.exe", 200, &body);
+        let host = FakeRunner::new().host(&stub.url);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("OllamaSetup.exe");
+        let url = format!("{}/OllamaSetup.exe", stub.url);
+
+        let expected = Expected {
+            sha256: Some("0".repeat(64)),
+            signer: None,
+        };
+        let err = download(&host, "ollama", &url, &dest, &expected, &|_, _, _| {})
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::IntegrityCheckFailed);
+        assert!(!dest.exists());
+
+        let expected = Expected {
+            sha256: Some(sha256_of(&body)),
+            signer: None,
+        };
+        download(&host, "ollama", &url, &dest, &expected, &|_, _, _| {})
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), body);
+    }
+
+    fn sha256_of(content: &str) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(content.as_bytes()))
+    }
+
+    #[tokio::test]
+    async fn rejects_an_installer_from_another_signer() {
+        let fake = FakeRunner::new();
+        fake.on("powershell", "Valid|CN=Someone Else, O=Someone Else");
+        let host = fake.host("http://127.0.0.1:9");
+        let path = Path::new("/tmp/OllamaSetup.exe");
+
+        let err = verify_authenticode(&host, path, "Ollama")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::IntegrityCheckFailed);
+        let calls = fake.calls_to("powershell");
+        let env = (
+            "DARKGPT_VERIFY_PATH".to_string(),
+            path.display().to_string(),
+        );
+        assert!(calls[0].envs.contains(&env));
+
+        let fake = FakeRunner::new();
+        fake.on("powershell", "NotSigned|");
+        let host = fake.host("http://127.0.0.1:9");
+        assert!(verify_authenticode(&host, path, "Ollama").await.is_err());
+
+        let fake = FakeRunner::new();
+        fake.on("powershell", "Valid|CN=Ollama Inc, O=Ollama Inc");
+        let host = fake.host("http://127.0.0.1:9");
+        verify_authenticode(&host, path, "Ollama").await.unwrap();
+    }
+}
@@ -0,0 +1,33 @@
+// Log commands
+// Backend for the in-app log viewer: read the log files back, filtered,
+// and change the log level without restarting
+
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::services::audit;
+use crate::services::logging::{self, LogEntry, LogLevel, LogQuery};
+use crate::utils::paths::AppPaths;
+
+/// Recent entries from the log files, oldest first
+#[tauri::command]
+pub fn read_logs(app: AppHandle, query: Option<LogQuery>) -> AppResult<Vec<LogEntry>> {
+    let paths = AppPaths::from_app(&app)?;
+    Ok(logging::read(&paths.log_dir, &query.unwrap_or_default())?)
+}
+
+#[tauri::command]
+pub fn get_log_level() -> LogLevel {
+    logging::level()
+}
+
+/// Applies until the app restarts
+#[tauri::command]
+pub fn set_log_level(level: LogLevel) -> AppResult<()> {
+    let result = logging::set_level(level).map_err(Into::into);
+    audit::recorded(
+        "logs.set_level",
+        serde_json::json!({ "level": level }),
+        result,
+    )
+}
@@ -0,0 +1,80 @@
+// Metrics endpoint commands
+
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::audit;
+use crate::services::host::Host;
+use crate::services::metrics::{Metrics, MetricsServer, MetricsStatus, DEFAULT_METRICS_PORT};
+use crate::services::settings::{AppSettings, MetricsSettings, SettingsStore};
+
+/// Start serving Prometheus metrics on the configured address, and keep it on
+/// across restarts
+#[tauri::command]
+pub async fn start_metrics_server(
+    metrics: State<'_, Metrics>,
+    host: State<'_, Host>,
+    server: State<'_, MetricsServer>,
+    store: State<'_, SettingsStore>,
+    port: Option<u16>,
+) -> AppResult<MetricsStatus> {
+    let port = port.unwrap_or(DEFAULT_METRICS_PORT);
+    let result = start(&metrics, &host, &server, &store, port).await;
+    audit::recorded("metrics.start", serde_json::json!({ "port": port }), result)
+}
+
+async fn start(
+    metrics: &Metrics,
+    host: &Host,
+    server: &MetricsServer,
+    store: &SettingsStore,
+    port: u16,
+) -> AppResult<MetricsStatus> {
+    let settings = store.get().metrics;
+    let status = server
+        .start(metrics.clone(), host.clone(), &settings.bind_address, port)
+        .await?;
+    save(
+        store,
+        MetricsSettings {
+            enabled: true,
+            port,
+            ..settings
+        },
+    )?;
+    Ok(status)
+}
+
+/// Stop the metrics server
+#[tauri::command]
+pub fn stop_metrics_server(
+    server: State<'_, MetricsServer>,
+    store: State<'_, SettingsStore>,
+) -> AppResult<MetricsStatus> {
+    server.stop();
+    let result = save(
+        &store,
+        MetricsSettings {
+            enabled: false,
+            ..store.get().metrics
+        },
+    );
+    audit::recorded("metrics.stop", serde_json::json!({}), result)?;
+    Ok(server.status())
+}
+
+/// Whether the metrics server is running, and where
+#[tauri::command]
+pub fn get_metrics_status(server: State<'_, MetricsServer>) -> MetricsStatus {
+    server.status()
+}
+
+fn save(store: &SettingsStore, metrics: MetricsSettings) -> AppResult<()> {
+    store
+        .save(AppSettings {
+            metrics,
+            ..store.get()
+        })
+        .map(|_| ())
+        .map_err(Into::into)
+}
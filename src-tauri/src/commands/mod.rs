@@ -1,8 +1,21 @@
 // Commands module
 // Exposes Tauri commands to the frontend
 
+pub mod audit;
+pub mod backup;
+pub mod benchmark;
+pub mod diagnostics;
 pub mod docker;
+pub mod gateway;
+pub mod gpu;
 pub mod health;
 pub mod installer;
+pub mod logs;
+pub mod metrics;
 pub mod ollama;
+pub mod ports;
+pub mod profiles;
+pub mod settings;
 pub mod setup;
+pub mod uninstall;
+pub mod updates;
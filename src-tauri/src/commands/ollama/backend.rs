@@ -0,0 +1,307 @@
+// Model server backends
+// Ollama's native API, or an OpenAI-compatible server such as llama.cpp's
+// `llama-server` or vLLM. The backend is set per profile endpoint;
+// `current` follows the active profile like `container::current` follows
+// the runtime setting.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::openai::OpenAiCompatible;
+use super::{Model, OllamaStatus};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::host::Host;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    #[default]
+    Ollama,
+    /// `/v1/models` and `/v1/chat/completions`
+    OpenAi,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// system | user | assistant
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+}
+
+#[async_trait]
+pub trait Backend: Send + Sync {
+    fn kind(&self) -> BackendKind;
+    fn display_name(&self) -> &'static str;
+    /// A cheap request that only succeeds when the API is up
+    fn probe(&self, client: &reqwest::Client) -> reqwest::RequestBuilder;
+    async fn status(&self) -> AppResult<OllamaStatus>;
+    async fn models(&self) -> AppResult<Vec<Model>>;
+    /// Stream a reply, handing each piece of it to `on_token`; returns the
+    /// whole reply
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> AppResult<String>;
+}
+
+/// The backend of the active profile's endpoint
+pub fn current(host: &Host) -> Box<dyn Backend> {
+    let profile = host.profile();
+    match profile.backend {
+        BackendKind::Ollama => Box::new(Ollama { host: host.clone() }),
+        BackendKind::OpenAi => Box::new(OpenAiCompatible::new(host.clone(), profile.api_key)),
+    }
+}
+
+/// Pulling, inspecting and deleting models only exist in Ollama's API
+pub(crate) fn ollama_only(host: &Host, what: &str) -> AppResult<()> {
+    match host.profile().backend {
+        BackendKind::Ollama => Ok(()),
+        BackendKind::OpenAi => Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("{} needs the Ollama backend", what),
+        )
+        .remediation(
+            "Manage models on the OpenAI-compatible server itself, or switch the profile's \
+             backend to Ollama.",
+        )),
+    }
+}
+
+/// Feed each complete line of a streamed body to `on_line`, which returns
+/// false to stop reading. Lines can span chunks.
+pub(super) async fn read_lines(
+    mut response: reqwest::Response,
+    mut on_line: impl FnMut(&str) -> AppResult<bool>,
+) -> AppResult<()> {
+    let interrupted = |e: reqwest::Error| {
+        AppError::new(ErrorCode::Network, "Response stream interrupted").cause(e)
+    };
+    let mut pending = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(interrupted)? {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            if !on_line(String::from_utf8_lossy(&line).trim())? {
+                return Ok(());
+            }
+        }
+    }
+    if !pending.is_empty() {
+        on_line(String::from_utf8_lossy(&pending).trim())?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaVersion {
+    version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaModels {
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaModel {
+    name: String,
+    size: u64,
+    modified_at: String,
+}
+
+/// Ollama's own API
+pub struct Ollama {
+    host: Host,
+}
+
+#[async_trait]
+impl Backend for Ollama {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Ollama
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Ollama"
+    }
+
+    fn probe(&self, client: &reqwest::Client) -> reqwest::RequestBuilder {
+        client.get(self.host.ollama("/api/tags"))
+    }
+
+    async fn status(&self) -> AppResult<OllamaStatus> {
+        let client = reqwest::Client::new();
+        let url = self.host.ollama("/api/version");
+
+        match client.get(&url).send().await {
+            Ok(response) if response.status().is_success() => {
+                let version: OllamaVersion = response
+                    .json()
+                    .await
+                    .map_err(|e| format!("Failed to parse version: {}", e))?;
+
+                tracing::info!("Ollama version: {}", version.version);
+
+                Ok(OllamaStatus {
+                    installed: true,
+                    running: true,
+                    version: Some(version.version),
+                })
+            }
+            Ok(_) => Ok(OllamaStatus {
+                installed: true,
+                running: false,
+                version: None,
+            }),
+            Err(_) => Ok(OllamaStatus {
+                installed: self.host.on_path("ollama"),
+                running: false,
+                version: None,
+            }),
+        }
+    }
+
+    async fn models(&self) -> AppResult<Vec<Model>> {
+        let client = reqwest::Client::new();
+        let url = self.host.ollama("/api/tags");
+
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(AppError::ollama_unreachable)?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ollama_unreachable(format!(
+                "HTTP {}",
+                response.status()
+            )));
+        }
+
+        let models: OllamaModels = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse models: {}", e))?;
+
+        Ok(models
+            .models
+            .into_iter()
+            .map(|m| Model {
+                name: m.name,
+                size: m.size,
+                modified_at: m.modified_at,
+            })
+            .collect())
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> AppResult<String> {
+        let response = reqwest::Client::new()
+            .post(self.host.ollama("/api/chat"))
+            .json(&serde_json::json!({
+                "model": request.model,
+                "messages": request.messages,
+                "stream": true,
+            }))
+            .send()
+            .await
+            .map_err(AppError::ollama_unreachable)?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::new(
+                ErrorCode::ModelNotFound,
+                format!("Model {} is not installed", request.model),
+            )
+            .remediation(format!("Run `dark-gpt models pull {}`.", request.model)));
+        }
+        if !response.status().is_success() {
+            return Err(format!("Ollama returned {} for chat", response.status()).into());
+        }
+
+        // One JSON object per line, the last with `"done": true`
+        let mut reply = String::new();
+        read_lines(response, |line| {
+            if line.is_empty() {
+                return Ok(true);
+            }
+            let event: serde_json::Value = serde_json::from_str(line)
+                .map_err(|e| format!("Failed to parse chat stream: {}", e))?;
+            if let Some(error) = event["error"].as_str() {
+                return Err(AppError::new(ErrorCode::Network, "Chat failed").cause(error));
+            }
+            if let Some(token) = event["message"]["content"]
+                .as_str()
+                .filter(|t| !t.is_empty())
+            {
+                on_token(token);
+                reply.push_str(token);
+            }
+            Ok(!event["done"].as_bool().unwrap_or(false))
+        })
+        .await?;
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    #[tokio::test]
+    async fn ollama_chat_streams_tokens() {
+        let stub = StubOllama::start().await;
+        stub.route(
+            Method::POST,
+            "/api/chat",
+            200,
+            concat!(
+                r#"{"message":{"role":"assistant","content":"Hel"},"done":false}"#,
+                "\n",
+                r#"{"message":{"role":"assistant","content":"lo"},"done":false}"#,
+                "\n",
+                r#"{"message":{"role":"assistant","content":""},"done":true}"#,
+                "\n"
+            ),
+        );
+        let backend = Ollama {
+            host: FakeRunner::new().host(&stub.url),
+        };
+        let request = ChatRequest {
+            model: "dolphin-phi".into(),
+            messages: vec![ChatMessage {
+                role: "user".into(),
+                content: "hi".into(),
+            }],
+        };
+
+        let mut tokens = Vec::new();
+        let reply = backend
+            .chat(&request, &mut |token| tokens.push(token.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello");
+        assert_eq!(tokens, ["Hel", "lo"]);
+        let body = &stub.received()[0].body;
+        assert!(body.contains(r#""stream":true"#), "{}", body);
+
+        let missing = StubOllama::start().await;
+        missing.route(Method::POST, "/api/chat", 404, "");
+        let backend = Ollama {
+            host: FakeRunner::new().host(&missing.url),
+        };
+        let err = backend.chat(&request, &mut |_| {}).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::ModelNotFound);
+    }
+}
@@ -0,0 +1,452 @@
+// Ollama API commands
+// Status, model listing and chat go through the active profile's backend
+// (Ollama or OpenAI-compatible); pulling and deleting models are Ollama's.
+
+pub mod backend;
+mod openai;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State, Window};
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::audit;
+use crate::services::host::Host;
+use crate::services::lifecycle;
+use crate::services::metrics::Metrics;
+use backend::{BackendKind, ChatRequest};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OllamaStatus {
+    pub installed: bool,
+    pub running: bool,
+    pub version: Option<String>,
+}
+
+/// `size` and `modified_at` are 0 and empty on OpenAI-compatible backends
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Model {
+    pub name: String,
+    pub size: u64,
+    pub modified_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub name: String,
+    pub size: u64,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub model: String,
+    pub status: String,
+    pub completed: u64,
+    pub total: u64,
+    pub percent: f32,
+}
+
+/// Check if Ollama is running
+#[tauri::command]
+pub async fn check_ollama(host: State<'_, Host>) -> AppResult<OllamaStatus> {
+    status(&host).await
+}
+
+/// Whether the API answers, else whether the binary is installed; shared
+/// with the CLI
+pub(crate) async fn status(host: &Host) -> AppResult<OllamaStatus> {
+    let backend = backend::current(host);
+    tracing::debug!("Checking {} status", backend.display_name());
+    backend.status().await
+}
+
+/// List installed models
+#[tauri::command]
+pub async fn list_models(host: State<'_, Host>) -> AppResult<Vec<Model>> {
+    models(&host).await
+}
+
+/// Installed models; shared with the CLI
+pub(crate) async fn models(host: &Host) -> AppResult<Vec<Model>> {
+    let backend = backend::current(host);
+    tracing::debug!("Listing {} models", backend.display_name());
+    backend.models().await
+}
+
+/// Chat with a model, emitting `chat-token` events as the reply streams in;
+/// returns the whole reply
+#[tauri::command]
+pub async fn chat(window: Window, request: ChatRequest) -> AppResult<String> {
+    let host = window.state::<Host>().inner().clone();
+    let backend = backend::current(&host);
+    let mut emit = |token: &str| {
+        let _ = window.emit("chat-token", token);
+    };
+    let chat = backend.chat(&request, &mut emit);
+    lifecycle::cancellable(&format!("Chat with {}", request.model), chat).await
+}
+
+/// Pull a model (with progress events)
+#[tauri::command]
+pub async fn pull_model(window: Window, model_name: String) -> AppResult<()> {
+    let metrics = window.state::<Metrics>().inner().clone();
+    let host = window.state::<Host>().inner().clone();
+    let pull = pull(&host, &metrics, &model_name, |progress| {
+        let _ = window.emit("model-download-progress", progress);
+    });
+    let result = lifecycle::cancellable(&format!("Pulling {}", model_name), pull).await;
+    audit::recorded(
+        "model.pull",
+        serde_json::json!({ "model": model_name }),
+        result,
+    )
+}
+
+/// Pull a model, reporting each progress line; shared with the CLI
+pub(crate) async fn pull(
+    host: &Host,
+    metrics: &Metrics,
+    model_name: &str,
+    mut on_progress: impl FnMut(DownloadProgress),
+) -> AppResult<()> {
+    backend::ollama_only(host, "Pulling models")?;
+    tracing::info!("Pulling model: {}", model_name);
+
+    let result = stream_pull(host, metrics, model_name, &mut on_progress).await;
+    metrics.record_pull(
+        model_name,
+        if result.is_ok() { "success" } else { "error" },
+    );
+
+    if result.is_ok() {
+        tracing::info!("Model {} pulled successfully", model_name);
+    }
+    result.map_err(|e| e.context(format!("Pulling {}", model_name)))
+}
+
+async fn stream_pull(
+    host: &Host,
+    metrics: &Metrics,
+    model_name: &str,
+    on_progress: &mut impl FnMut(DownloadProgress),
+) -> AppResult<()> {
+    let client = reqwest::Client::new();
+    let url = host.ollama("/api/pull");
+
+    let mut response = client
+        .post(&url)
+        .json(&serde_json::json!({ "name": model_name, "stream": true }))
+        .send()
+        .await
+        .map_err(AppError::ollama_unreachable)?;
+
+    // Last `completed` value per layer, to count downloaded bytes
+    let mut layer_progress: HashMap<String, u64> = HashMap::new();
+
+    // Stream progress events
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| {
+            AppError::new(ErrorCode::Network, "Model download interrupted")
+                .cause(e)
+                .remediation("Retry; Ollama resumes partial downloads.")
+        })?
+    {
+        let text = String::from_utf8_lossy(&chunk);
+        for line in text.lines() {
+            if let Ok(progress) = serde_json::from_str::<serde_json::Value>(line) {
+                if let Some(error) = progress["error"].as_str() {
+                    return Err(pull_error(model_name, error));
+                }
+                let status = progress["status"].as_str().unwrap_or("").to_string();
+                let completed = progress["completed"].as_u64().unwrap_or(0);
+                let total = progress["total"].as_u64().unwrap_or(1);
+                let percent = if total > 0 {
+                    (completed as f32 / total as f32) * 100.0
+                } else {
+                    0.0
+                };
+
+                if let Some(digest) = progress["digest"].as_str() {
+                    let seen = layer_progress.entry(digest.to_string()).or_default();
+                    if completed > *seen {
+                        metrics.add_pull_bytes(model_name, completed - *seen);
+                        *seen = completed;
+                    }
+                }
+
+                on_progress(DownloadProgress {
+                    model: model_name.to_string(),
+                    status,
+                    completed,
+                    total,
+                    percent,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Error line from the pull stream, e.g. an unknown model name
+fn pull_error(model_name: &str, error: &str) -> AppError {
+    if error.contains("file does not exist") || error.contains("not found") {
+        AppError::new(
+            ErrorCode::ModelNotFound,
+            format!("Model {} does not exist in the Ollama library", model_name),
+        )
+        .cause(error)
+        .remediation("Check the model name at https://ollama.com/library.")
+    } else {
+        AppError::new(ErrorCode::Network, "Model download failed").cause(error)
+    }
+}
+
+/// Get detailed model info
+#[tauri::command]
+pub async fn get_model_info(host: State<'_, Host>, model_name: String) -> AppResult<ModelInfo> {
+    backend::ollama_only(&host, "Model details")?;
+    let client = reqwest::Client::new();
+    let url = host.ollama("/api/show");
+
+    let response = client
+        .post(&url)
+        .json(&serde_json::json!({ "name": model_name }))
+        .send()
+        .await
+        .map_err(AppError::ollama_unreachable)?;
+
+    if !response.status().is_success() {
+        return Err(AppError::new(
+            ErrorCode::ModelNotFound,
+            format!("Model {} not found", model_name),
+        ));
+    }
+
+    let info: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse info: {}", e))?;
+
+    Ok(ModelInfo {
+        name: model_name,
+        size: info["size"].as_u64().unwrap_or(0),
+        parameter_size: info["details"]["parameter_size"]
+            .as_str()
+            .map(|s| s.to_string()),
+        quantization: info["details"]["quantization_level"]
+            .as_str()
+            .map(|s| s.to_string()),
+    })
+}
+
+/// Start `ollama serve` if nothing answers on the API yet, and wait for it
+pub(crate) async fn ensure_running(host: &Host) -> AppResult<()> {
+    if status(host).await.is_ok_and(|s| s.running) {
+        return Ok(());
+    }
+    // Other model servers are the user's to run
+    if backend::current(host).kind() != BackendKind::Ollama {
+        return Err(openai::unreachable(host.ollama("/v1/models")));
+    }
+
+    tracing::info!("Ollama not running, launching `ollama serve`");
+    let child = host
+        .command("ollama")
+        .arg("serve")
+        .to_tokio()
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            AppError::new(ErrorCode::OllamaUnreachable, "Failed to launch `ollama serve`")
+                .cause(e)
+                .remediation("Install Ollama from the setup wizard.")
+        })?;
+    lifecycle::record_ollama_spawned(child);
+
+    for _ in 0..30 {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        if status(host).await.is_ok_and(|s| s.running) {
+            return Ok(());
+        }
+    }
+    Err(AppError::timeout("waiting for Ollama to start", 15))
+}
+
+/// Load a model into memory so the first chat doesn't pay the load time.
+/// Skipped when the model isn't installed (preloading must never download),
+/// and on OpenAI-compatible servers, which load their model at startup.
+pub(crate) async fn preload_model(host: &Host, model_name: &str) -> AppResult<()> {
+    if backend::current(host).kind() != BackendKind::Ollama {
+        return Ok(());
+    }
+    let installed = models(host).await?;
+    if !installed.iter().any(|m| m.name == model_name) {
+        return Err(AppError::new(
+            ErrorCode::ModelNotFound,
+            format!("{} is not installed, not preloading", model_name),
+        ));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .build()
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+    let response = client
+        .post(host.ollama("/api/generate"))
+        .json(&serde_json::json!({ "model": model_name, "keep_alive": "30m" }))
+        .send()
+        .await
+        .map_err(AppError::ollama_unreachable)?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Ollama returned {} preloading {}",
+            response.status(),
+            model_name
+        )
+        .into());
+    }
+    tracing::info!("Preloaded {}", model_name);
+    Ok(())
+}
+
+/// Remove an installed model and its blobs
+pub(crate) async fn delete_model(host: &Host, model_name: &str) -> AppResult<()> {
+    backend::ollama_only(host, "Deleting models")?;
+    let response = reqwest::Client::new()
+        .delete(host.ollama("/api/delete"))
+        .json(&serde_json::json!({ "model": model_name }))
+        .send()
+        .await
+        .map_err(AppError::ollama_unreachable)?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::new(
+            ErrorCode::ModelNotFound,
+            format!("Model {} not found", model_name),
+        ));
+    }
+    if !response.status().is_success() {
+        return Err(format!(
+            "Ollama returned {} deleting {}",
+            response.status(),
+            model_name
+        )
+        .into());
+    }
+    tracing::info!("Deleted model {}", model_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    #[tokio::test]
+    async fn status_reports_version_or_installed_binary() {
+        let stub = StubOllama::start().await;
+        stub.route(Method::GET, "/api/version", 200, r#"{"version":"0.5.7"}"#);
+        let status = status(&FakeRunner::new().host(&stub.url)).await.unwrap();
+        assert!(status.running);
+        assert_eq!(status.version.as_deref(), Some("0.5.7"));
+
+        let fake = FakeRunner::new();
+        fake.installed("ollama");
+        let status = super::status(&fake.host("http://127.0.0.1:9"))
+            .await
+            .unwrap();
+        assert!(status.installed && !status.running);
+    }
+
+    #[tokio::test]
+    async fn lists_and_deletes_models() {
+        let stub = StubOllama::start().await;
+        stub.route(
+            Method::GET,
+            "/api/tags",
+            200,
+            r#"{"models":[{"name":"dolphin-phi:latest","size":1600000000,
+                "modified_at":"2026-10-01T12:00:00Z"}]}"#,
+        )
+        .route(Method::DELETE, "/api/delete", 200, "");
+        let host = FakeRunner::new().host(&stub.url);
+
+        let listed = models(&host).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "dolphin-phi:latest");
+        assert_eq!(listed[0].size, 1_600_000_000);
+
+        delete_model(&host, "dolphin-phi").await.unwrap();
+        let request = stub.received().pop().unwrap();
+        assert_eq!(request.method, Method::DELETE);
+        assert_eq!(request.path, "/api/delete");
+        assert!(request.body.contains("dolphin-phi"), "{}", request.body);
+
+        let empty = StubOllama::start().await;
+        let host = FakeRunner::new().host(&empty.url);
+        let err = delete_model(&host, "nope").await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::ModelNotFound);
+    }
+
+    #[tokio::test]
+    async fn pull_streams_progress() {
+        let stub = StubOllama::start().await;
+        stub.route(
+            Method::POST,
+            "/api/pull",
+            200,
+            concat!(
+                r#"{"status":"pulling manifest"}"#,
+                "\n",
+                r#"{"status":"downloading","digest":"sha256:a","completed":50,"total":100}"#,
+                "\n",
+                r#"{"status":"downloading","digest":"sha256:a","completed":100,"total":100}"#,
+                "\n",
+                r#"{"status":"success"}"#,
+                "\n"
+            ),
+        );
+        let host = FakeRunner::new().host(&stub.url);
+        let metrics = Metrics::default();
+
+        let mut seen = Vec::new();
+        pull(&host, &metrics, "dolphin-phi", |p| {
+            seen.push((p.status, p.percent))
+        })
+        .await
+        .unwrap();
+        assert_eq!(seen.len(), 4);
+        assert_eq!(seen[2], ("downloading".to_string(), 100.0));
+        assert_eq!(seen[3].0, "success");
+    }
+
+    #[tokio::test]
+    async fn pull_errors_map_to_error_codes() {
+        let stub = StubOllama::start().await;
+        stub.route(
+            Method::POST,
+            "/api/pull",
+            200,
+            r#"{"error":"pull model manifest: file does not exist"}"#,
+        );
+        let host = FakeRunner::new().host(&stub.url);
+        let err = pull(&host, &Metrics::default(), "dolphin-nope", |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::ModelNotFound);
+
+        let host = FakeRunner::new().host("http://127.0.0.1:9");
+        let err = models(&host).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::OllamaUnreachable);
+    }
+}
@@ -0,0 +1,276 @@
+// OpenAI-compatible backend
+// For model servers that speak the OpenAI API instead of Ollama's, such as
+// llama.cpp's `llama-server` or vLLM. Only what the app needs: health,
+// model listing and streamed chat completions.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::backend::{read_lines, Backend, BackendKind, ChatRequest};
+use super::{Model, OllamaStatus};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::host::Host;
+
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+pub struct OpenAiCompatible {
+    host: Host,
+    /// Sent as a bearer token (`--api-key` on llama-server and vLLM)
+    api_key: Option<String>,
+}
+
+impl OpenAiCompatible {
+    pub fn new(host: Host, api_key: Option<String>) -> Self {
+        Self { host, api_key }
+    }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.api_key.as_deref().filter(|key| !key.is_empty()) {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+}
+
+/// Nothing answers on the profile's endpoint
+pub(super) fn unreachable(cause: impl ToString) -> AppError {
+    AppError::new(
+        ErrorCode::OllamaUnreachable,
+        "The OpenAI-compatible server is not reachable",
+    )
+    .cause(cause)
+    .remediation("Start the server (llama-server, vLLM) at the profile's endpoint and try again.")
+}
+
+/// Non-2xx responses carry `{"error": {"message": ...}}`
+async fn error_message(response: reqwest::Response) -> String {
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    body["error"]["message"]
+        .as_str()
+        .or(body["error"].as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("HTTP {}", status))
+}
+
+#[async_trait]
+impl Backend for OpenAiCompatible {
+    fn kind(&self) -> BackendKind {
+        BackendKind::OpenAi
+    }
+
+    fn display_name(&self) -> &'static str {
+        "OpenAI-compatible server"
+    }
+
+    fn probe(&self, client: &reqwest::Client) -> reqwest::RequestBuilder {
+        self.authorized(client.get(self.host.ollama("/v1/models")))
+    }
+
+    async fn status(&self) -> AppResult<OllamaStatus> {
+        // Nothing to install locally; the version isn't part of the API
+        let running = self
+            .probe(&reqwest::Client::new())
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success());
+        Ok(OllamaStatus {
+            installed: running,
+            running,
+            version: None,
+        })
+    }
+
+    async fn models(&self) -> AppResult<Vec<Model>> {
+        let response = self
+            .probe(&reqwest::Client::new())
+            .send()
+            .await
+            .map_err(unreachable)?;
+
+        if matches!(response.status().as_u16(), 401 | 403) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "The OpenAI-compatible server rejected the API key",
+            )
+            .remediation("Check the API key in the profile settings."));
+        }
+        if !response.status().is_success() {
+            return Err(unreachable(error_message(response).await));
+        }
+
+        let list: ModelList = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse models: {}", e))?;
+
+        // Size and modification time aren't part of the API
+        Ok(list
+            .data
+            .into_iter()
+            .map(|m| Model {
+                name: m.id,
+                size: 0,
+                modified_at: String::new(),
+            })
+            .collect())
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> AppResult<String> {
+        let response = self
+            .authorized(reqwest::Client::new().post(self.host.ollama("/v1/chat/completions")))
+            .json(&serde_json::json!({
+                "model": request.model,
+                "messages": request.messages,
+                "stream": true,
+            }))
+            .send()
+            .await
+            .map_err(unreachable)?;
+
+        match response.status().as_u16() {
+            200..=299 => {}
+            404 => {
+                return Err(AppError::new(
+                    ErrorCode::ModelNotFound,
+                    format!("The server doesn't serve {}", request.model),
+                )
+                .cause(error_message(response).await)
+                .remediation("Pick one of the models the server lists."));
+            }
+            401 | 403 => {
+                return Err(AppError::new(
+                    ErrorCode::InvalidInput,
+                    "The OpenAI-compatible server rejected the API key",
+                )
+                .remediation("Check the API key in the profile settings."));
+            }
+            _ => {
+                return Err(AppError::new(ErrorCode::Network, "Chat failed")
+                    .cause(error_message(response).await));
+            }
+        }
+
+        // Server-sent events: `data: {chunk}` lines, then `data: [DONE]`
+        let mut reply = String::new();
+        read_lines(response, |line| {
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                return Ok(true);
+            };
+            if data == "[DONE]" {
+                return Ok(false);
+            }
+            let chunk: serde_json::Value = serde_json::from_str(data)
+                .map_err(|e| format!("Failed to parse chat stream: {}", e))?;
+            if let Some(error) = chunk["error"]["message"].as_str() {
+                return Err(AppError::new(ErrorCode::Network, "Chat failed").cause(error));
+            }
+            if let Some(token) = chunk["choices"][0]["delta"]["content"]
+                .as_str()
+                .filter(|t| !t.is_empty())
+            {
+                on_token(token);
+                reply.push_str(token);
+            }
+            Ok(true)
+        })
+        .await?;
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ollama::backend::ChatMessage;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    fn backend(url: &str) -> OpenAiCompatible {
+        OpenAiCompatible::new(FakeRunner::new().host(url), Some("secret".into()))
+    }
+
+    #[tokio::test]
+    async fn lists_models_and_reports_health() {
+        let stub = StubOllama::start().await;
+        stub.route(
+            Method::GET,
+            "/v1/models",
+            200,
+            r#"{"object":"list","data":[{"id":"Qwen2.5-7B-Instruct","object":"model",
+                "created":1760000000,"owned_by":"vllm"}]}"#,
+        );
+        let server = backend(&stub.url);
+        assert!(server.status().await.unwrap().running);
+        let models = server.models().await.unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "Qwen2.5-7B-Instruct");
+
+        let down = backend("http://127.0.0.1:9");
+        assert!(!down.status().await.unwrap().running);
+        let err = down.models().await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::OllamaUnreachable);
+    }
+
+    #[tokio::test]
+    async fn chat_streams_server_sent_events() {
+        let stub = StubOllama::start().await;
+        stub.route(
+            Method::POST,
+            "/v1/chat/completions",
+            200,
+            concat!(
+                r#"data: {"choices":[{"index":0,"delta":{"role":"assistant"}}]}"#,
+                "\n\n",
+                r#"data: {"choices":[{"index":0,"delta":{"content":"Hel"}}]}"#,
+                "\n\n",
+                ": keep-alive\n\n",
+                r#"data: {"choices":[{"index":0,"delta":{"content":"lo"}}]}"#,
+                "\n\n",
+                "data: [DONE]\n\n"
+            ),
+        );
+        let request = ChatRequest {
+            model: "Qwen2.5-7B-Instruct".into(),
+            messages: vec![ChatMessage {
+                role: "user".into(),
+                content: "hi".into(),
+            }],
+        };
+
+        let mut tokens = Vec::new();
+        let reply = backend(&stub.url)
+            .chat(&request, &mut |token| tokens.push(token.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello");
+        assert_eq!(tokens, ["Hel", "lo"]);
+        let body = &stub.received()[0].body;
+        assert!(body.contains(r#""stream":true"#), "{}", body);
+
+        let missing = StubOllama::start().await;
+        missing.route(
+            Method::POST,
+            "/v1/chat/completions",
+            404,
+            r#"{"error":{"message":"The model `nope` does not exist."}}"#,
+        );
+        let err = backend(&missing.url)
+            .chat(&request, &mut |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::ModelNotFound);
+    }
+}
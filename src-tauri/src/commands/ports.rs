@@ -0,0 +1,531 @@
+// Port conflict diagnostics
+// Preflight check for every host port the stack needs before `compose up`
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+use tokio::time::{timeout, Duration};
+
+use super::docker;
+use crate::error::AppResult;
+use crate::services::container::{self, ContainerRuntime};
+use crate::services::host::Host;
+use crate::services::profiles::Profile;
+
+const CMD_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DEFAULT_OLLAMA_PORT: u16 = 11434;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequiredPort {
+    pub port: u16,
+    pub service: String,
+    /// Process name allowed to hold the port (Ollama runs on the host)
+    pub expected_owner: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PortHolder {
+    Process { pid: u32, name: String },
+    Container { name: String },
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PortConflict {
+    pub port: u16,
+    pub service: String,
+    pub holder: PortHolder,
+    pub suggestion: String,
+}
+
+/// Check every port the stack needs and report which ones are taken
+#[tauri::command]
+pub async fn check_port_conflicts(
+    app: AppHandle,
+    host: State<'_, Host>,
+) -> AppResult<Vec<PortConflict>> {
+    let compose_file = docker::compose_file_path(&app)?;
+    let runtime = container::current(&host).await;
+    Ok(find_port_conflicts(runtime.as_ref(), &compose_file).await)
+}
+
+/// Run the preflight against a compose file. An empty list means all clear.
+pub(crate) async fn find_port_conflicts(
+    runtime: &dyn ContainerRuntime,
+    compose_file: &Path,
+) -> Vec<PortConflict> {
+    let profile = runtime.host().profile();
+    let required = required_ports(runtime, compose_file, &profile).await;
+    let containers = published_container_ports(runtime).await;
+    let ports: Vec<u16> = required.iter().map(|r| r.port).collect();
+
+    let listeners = tokio::task::spawn_blocking(move || find_listeners(&ports))
+        .await
+        .unwrap_or_default();
+
+    let mut conflicts = Vec::new();
+    for req in required {
+        let holder = match containers.get(&req.port) {
+            Some(name) => PortHolder::Container { name: name.clone() },
+            None => match listeners.get(&req.port) {
+                Some(holder) => holder.clone(),
+                None => continue,
+            },
+        };
+
+        if is_expected_holder(&req, &holder, &profile) {
+            continue;
+        }
+
+        tracing::warn!(
+            "Port {} ({}) is held by {:?}",
+            req.port,
+            req.service,
+            holder
+        );
+        conflicts.push(PortConflict {
+            suggestion: suggest_fix(&req, &holder),
+            port: req.port,
+            service: req.service,
+            holder,
+        });
+    }
+    conflicts
+}
+
+/// Who is listening on `port`, if anyone
+pub(crate) async fn port_holder(host: &Host, port: u16) -> Option<PortHolder> {
+    let runtime = container::current(host).await;
+    if let Some(name) = published_container_ports(runtime.as_ref())
+        .await
+        .remove(&port)
+    {
+        return Some(PortHolder::Container { name });
+    }
+    tokio::task::spawn_blocking(move || find_listeners(&[port]))
+        .await
+        .ok()?
+        .remove(&port)
+}
+
+/// Whether a `compose up` stderr looks like a port binding failure
+pub(crate) fn is_port_error(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    stderr.contains("address already in use")
+        || stderr.contains("port is already allocated")
+        || stderr.contains("ports are not available")
+}
+
+/// Ports published by the rendered compose file, plus the host Ollama port
+/// that `OLLAMA_BASE_URL` points the webui at.
+async fn required_ports(
+    runtime: &dyn ContainerRuntime,
+    compose_file: &Path,
+    profile: &Profile,
+) -> Vec<RequiredPort> {
+    let mut required = Vec::new();
+    let mut ollama_port = DEFAULT_OLLAMA_PORT;
+
+    match docker::rendered_compose_config(runtime, compose_file).await {
+        Ok(config) => {
+            let services = config["services"].as_object().cloned().unwrap_or_default();
+            for (name, service) in &services {
+                for port in service["ports"].as_array().into_iter().flatten() {
+                    // `published` is a string in recent compose versions, a number in older ones
+                    let published = port["published"]
+                        .as_str()
+                        .and_then(|p| p.parse().ok())
+                        .or_else(|| port["published"].as_u64().map(|p| p as u16));
+                    if let Some(port) = published {
+                        required.push(RequiredPort {
+                            port,
+                            service: name.clone(),
+                            expected_owner: None,
+                        });
+                    }
+                }
+                if let Some(port) = service["environment"]["OLLAMA_BASE_URL"]
+                    .as_str()
+                    .and_then(host_port_from_url)
+                {
+                    ollama_port = port;
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Using default port list: {}", e);
+            // What the bundled compose file publishes
+            let caddy = [profile.https_port, profile.http_port];
+            required.extend(caddy.into_iter().map(|port| RequiredPort {
+                port,
+                service: "caddy".to_string(),
+                expected_owner: None,
+            }));
+        }
+    }
+
+    required.push(RequiredPort {
+        port: ollama_port,
+        service: "ollama".to_string(),
+        expected_owner: Some("ollama".to_string()),
+    });
+    let mut seen = std::collections::HashSet::new();
+    required.retain(|r| seen.insert(r.port));
+    required
+}
+
+/// Extract the port from a URL that targets the host (`host.docker.internal`,
+/// `localhost`, ...). Remote endpoints don't need a local port.
+fn host_port_from_url(url: &str) -> Option<u16> {
+    let authority = url.split("://").nth(1)?.split('/').next()?;
+    let (host, port) = authority.rsplit_once(':')?;
+    let local = ["host.docker.internal", "localhost", "127.0.0.1", "0.0.0.0"];
+    if !local.contains(&host) {
+        return None;
+    }
+    port.parse().ok()
+}
+
+fn is_expected_holder(req: &RequiredPort, holder: &PortHolder, profile: &Profile) -> bool {
+    match holder {
+        // `compose up -d` on this profile's running containers is a no-op,
+        // not a conflict; another profile's containers are
+        PortHolder::Container { name } => {
+            let own = format!("{}-{}", profile.project, req.service);
+            name == &own
+                || name.starts_with(&format!("{}-", own))
+                || req
+                    .expected_owner
+                    .as_deref()
+                    .is_some_and(|owner| name.contains(owner))
+        }
+        PortHolder::Process { name, .. } => req
+            .expected_owner
+            .as_deref()
+            .is_some_and(|owner| name.to_lowercase().contains(owner)),
+        // Another user's process: we can't read its name, so give Ollama the benefit of the doubt
+        PortHolder::Unknown => req.expected_owner.is_some(),
+    }
+}
+
+fn suggest_fix(req: &RequiredPort, holder: &PortHolder) -> String {
+    let reason = format!("Port {} (needed by {}) is taken", req.port, req.service);
+
+    match holder {
+        PortHolder::Container { name } => format!(
+            "{} by container '{}'. Stop it with `docker stop {}` and retry.",
+            reason, name, name
+        ),
+        PortHolder::Process { pid, name } => {
+            let stop_hint = if cfg!(target_os = "windows") {
+                format!("`taskkill /PID {}`", pid)
+            } else {
+                format!("`sudo systemctl stop {}` or `kill {}`", name, pid)
+            };
+            format!(
+                "{} by '{}' (pid {}). Stop it with {}, or change the port mapping in docker-compose.yml.",
+                reason, name, pid, stop_hint
+            )
+        }
+        PortHolder::Unknown => format!(
+            "{} by another program. Free the port or change the port mapping in docker-compose.yml.",
+            reason
+        ),
+    }
+}
+
+/// Host ports published by running containers, from `docker ps` / `podman ps`
+async fn published_container_ports(runtime: &dyn ContainerRuntime) -> BTreeMap<u16, String> {
+    let output = timeout(
+        CMD_TIMEOUT,
+        runtime
+            .command()
+            .args(["ps", "--format", "{{.Names}}\t{{.Ports}}"])
+            .output(),
+    )
+    .await;
+
+    let mut published = BTreeMap::new();
+    if let Ok(Ok(o)) = output {
+        if o.status.success() {
+            for line in String::from_utf8_lossy(&o.stdout).lines() {
+                let Some((name, ports)) = line.split_once('\t') else {
+                    continue;
+                };
+                // e.g. "127.0.0.1:443->443/tcp, [::]:80->80/tcp"
+                for mapping in ports.split(", ") {
+                    let Some((host, _)) = mapping.split_once("->") else {
+                        continue;
+                    };
+                    if let Some(port) = host.rsplit(':').next().and_then(|p| p.parse().ok()) {
+                        published.insert(port, name.to_string());
+                    }
+                }
+            }
+        }
+    }
+    published
+}
+
+/// Find which of `ports` have a listening socket, and who owns it
+#[cfg(target_os = "linux")]
+fn find_listeners(ports: &[u16]) -> BTreeMap<u16, PortHolder> {
+    use std::collections::HashMap;
+
+    // Listening socket inode per port, from the kernel tables
+    let mut inodes: HashMap<u64, u16> = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(content) = std::fs::read_to_string(table) {
+            inodes.extend(listening_sockets(&content, ports));
+        }
+    }
+
+    let mut listeners: BTreeMap<u16, PortHolder> = inodes
+        .values()
+        .map(|port| (*port, PortHolder::Unknown))
+        .collect();
+    if inodes.is_empty() {
+        return listeners;
+    }
+
+    // Map socket inodes back to processes. Other users' fds are unreadable,
+    // in which case the holder stays Unknown.
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return listeners;
+    };
+    for entry in procs.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            if let Some(port) = inodes.get(&inode) {
+                let name = std::fs::read_to_string(entry.path().join("comm"))
+                    .map(|n| n.trim().to_string())
+                    .unwrap_or_else(|_| "unknown".to_string());
+                listeners.insert(*port, PortHolder::Process { pid, name });
+            }
+        }
+    }
+    listeners
+}
+
+/// `(inode, port)` of the sockets listening on one of `ports` in a
+/// /proc/net/tcp or tcp6 table
+#[cfg(any(target_os = "linux", test))]
+fn listening_sockets(table: &str, ports: &[u16]) -> Vec<(u64, u16)> {
+    let mut sockets = Vec::new();
+    for line in table.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // sl local_address rem_address st ... uid timeout inode
+        if fields.len() < 10 || fields[3] != "0A" {
+            continue;
+        }
+        let Some(port) = fields[1]
+            .rsplit_once(':')
+            .and_then(|(_, p)| u16::from_str_radix(p, 16).ok())
+        else {
+            continue;
+        };
+        if !ports.contains(&port) {
+            continue;
+        }
+        if let Ok(inode) = fields[9].parse() {
+            sockets.push((inode, port));
+        }
+    }
+    sockets
+}
+
+/// Without /proc we can only tell whether the port is free by binding it
+#[cfg(not(target_os = "linux"))]
+fn find_listeners(ports: &[u16]) -> BTreeMap<u16, PortHolder> {
+    ports
+        .iter()
+        .filter(|port| {
+            matches!(
+                std::net::TcpListener::bind(("127.0.0.1", **port)),
+                Err(e) if e.kind() == std::io::ErrorKind::AddrInUse
+            )
+        })
+        .map(|port| (*port, PortHolder::Unknown))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::container::RuntimeKind;
+    use crate::utils::testing::FakeRunner;
+
+    const COMPOSE_CONFIG: &str = include_str!("../../tests/fixtures/ports/compose-config.json");
+    const COMPOSE_CONFIG_LEGACY: &str =
+        include_str!("../../tests/fixtures/ports/compose-config-legacy.json");
+    const PROC_NET_TCP: &str = include_str!("../../tests/fixtures/ports/proc-net-tcp");
+    const PROC_NET_TCP6: &str = include_str!("../../tests/fixtures/ports/proc-net-tcp6");
+
+    fn compose_file() -> &'static Path {
+        Path::new("/opt/dark-gpt/docker/docker-compose.yml")
+    }
+
+    /// Required ports when `compose config` prints `config` (None = fails)
+    async fn required(config: Option<&str>) -> Vec<(u16, String, Option<String>)> {
+        let fake = FakeRunner::new();
+        let prefix = format!(
+            "docker compose -p dark-gpt -f {} config",
+            compose_file().display()
+        );
+        match config {
+            Some(config) => fake.on(&prefix, config),
+            None => fake.fail(&prefix, "no such service"),
+        };
+        let host = fake.host("http://127.0.0.1:9");
+        let runtime = container::runtime(&host, RuntimeKind::Docker).await;
+        required_ports(runtime.as_ref(), compose_file(), &Profile::default())
+            .await
+            .into_iter()
+            .map(|r| (r.port, r.service, r.expected_owner))
+            .collect()
+    }
+
+    fn port(port: u16, service: &str, owner: Option<&str>) -> (u16, String, Option<String>) {
+        (port, service.to_string(), owner.map(str::to_string))
+    }
+
+    #[tokio::test]
+    async fn required_ports_come_from_the_rendered_compose_file() {
+        assert_eq!(
+            required(Some(COMPOSE_CONFIG)).await,
+            vec![
+                port(8443, "caddy", None),
+                port(8080, "caddy", None),
+                // OLLAMA_BASE_URL points the webui at the gateway
+                port(11435, "ollama", Some("ollama")),
+            ]
+        );
+        // Numeric `published` from older compose; a remote Ollama needs no
+        // local port, so the default is checked
+        assert_eq!(
+            required(Some(COMPOSE_CONFIG_LEGACY)).await,
+            vec![
+                port(443, "caddy", None),
+                port(80, "caddy", None),
+                port(11434, "ollama", Some("ollama")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn required_ports_fall_back_to_the_profile_ports() {
+        assert_eq!(
+            required(None).await,
+            vec![
+                port(443, "caddy", None),
+                port(80, "caddy", None),
+                port(11434, "ollama", Some("ollama")),
+            ]
+        );
+    }
+
+    #[test]
+    fn host_port_only_for_local_urls() {
+        assert_eq!(
+            host_port_from_url("http://host.docker.internal:11434"),
+            Some(11434)
+        );
+        assert_eq!(host_port_from_url("http://localhost:8000/v1"), Some(8000));
+        assert_eq!(host_port_from_url("http://127.0.0.1:11435/"), Some(11435));
+        assert_eq!(host_port_from_url("http://gpu-box.lan:11434"), None);
+        // No explicit port, or not a URL
+        assert_eq!(host_port_from_url("http://localhost"), None);
+        assert_eq!(host_port_from_url("localhost:11434"), None);
+    }
+
+    #[test]
+    fn expected_holders_are_not_conflicts() {
+        let profile = Profile::default();
+        let caddy = RequiredPort {
+            port: 443,
+            service: "caddy".to_string(),
+            expected_owner: None,
+        };
+        let ollama = RequiredPort {
+            port: 11434,
+            service: "ollama".to_string(),
+            expected_owner: Some("ollama".to_string()),
+        };
+        let container = |name: &str| PortHolder::Container {
+            name: name.to_string(),
+        };
+        let process = |name: &str| PortHolder::Process {
+            pid: 4242,
+            name: name.to_string(),
+        };
+
+        assert!(is_expected_holder(
+            &caddy,
+            &container("dark-gpt-caddy"),
+            &profile
+        ));
+        assert!(is_expected_holder(
+            &caddy,
+            &container("dark-gpt-caddy-1"),
+            &profile
+        ));
+        // Another profile's stack holds it
+        assert!(!is_expected_holder(
+            &caddy,
+            &container("dark-gpt-staging-caddy"),
+            &profile
+        ));
+        assert!(!is_expected_holder(&caddy, &process("nginx"), &profile));
+        assert!(!is_expected_holder(&caddy, &PortHolder::Unknown, &profile));
+
+        assert!(is_expected_holder(&ollama, &process("ollama"), &profile));
+        assert!(is_expected_holder(
+            &ollama,
+            &process("Ollama.exe"),
+            &profile
+        ));
+        assert!(is_expected_holder(&ollama, &container("ollama"), &profile));
+        assert!(is_expected_holder(&ollama, &PortHolder::Unknown, &profile));
+        assert!(!is_expected_holder(
+            &ollama,
+            &process("llama-server"),
+            &profile
+        ));
+    }
+
+    #[test]
+    fn parses_listening_sockets_from_proc() {
+        let ports = [443, 80, 11434];
+        // Established connections and unrequested ports are skipped
+        assert_eq!(
+            listening_sockets(PROC_NET_TCP, &ports),
+            vec![(31337, 443), (40112, 80)]
+        );
+        assert_eq!(
+            listening_sockets(PROC_NET_TCP6, &ports),
+            vec![(52904, 11434)]
+        );
+        assert!(listening_sockets(PROC_NET_TCP, &[8443]).is_empty());
+    }
+}
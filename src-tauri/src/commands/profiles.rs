@@ -0,0 +1,278 @@
+// Environment profile commands
+// Each profile is its own compose project, so switching leaves the other
+// profiles' containers and volumes alone. Stacks of two profiles can run
+// side by side as long as their ports differ.
+
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tokio::time::{timeout, Duration};
+
+use super::{docker, settings};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::audit;
+use crate::services::container::{self, ContainerRuntime};
+use crate::services::host::Host;
+use crate::services::profiles::Profile;
+use crate::services::settings::{AppSettings, SettingsStore};
+
+const CMD_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize)]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+/// All profiles and the active one's name
+#[tauri::command]
+pub fn list_profiles(store: State<'_, SettingsStore>) -> ProfileList {
+    let settings = store.get();
+    ProfileList {
+        active: settings.active_profile,
+        profiles: settings.profiles.iter().map(Profile::redacted).collect(),
+    }
+}
+
+/// Add a profile. It isn't activated; see `switch_profile`.
+#[tauri::command]
+pub async fn create_profile(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    profile: Profile,
+) -> AppResult<Profile> {
+    let details = serde_json::json!({ "name": profile.name, "project": profile.project });
+    let result = add(&app, &store, profile).await;
+    audit::recorded("profile.create", details, result).map(|p| p.redacted())
+}
+
+/// Copy `source` as `name`, in a project of its own. Ports are kept, so the
+/// copy can't run next to the original until one of them is changed.
+#[tauri::command]
+pub async fn clone_profile(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    source: String,
+    name: String,
+) -> AppResult<Profile> {
+    let result = match find(&store.get(), &source) {
+        Ok(profile) => add(&app, &store, profile.clone_as(&name)).await,
+        Err(e) => Err(e),
+    };
+    audit::recorded(
+        "profile.clone",
+        serde_json::json!({ "source": source, "name": name }),
+        result,
+    )
+    .map(|p| p.redacted())
+}
+
+/// Make `name` the active profile. A running stack on the same ports is
+/// stopped first; if the previous profile's stack was running, the new
+/// one is started in its place.
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    name: String,
+) -> AppResult<Profile> {
+    let result = switch(&app, &store, &name).await;
+    audit::recorded(
+        "profile.switch",
+        serde_json::json!({ "name": name }),
+        result,
+    )
+    .map(|p| p.redacted())
+}
+
+async fn switch(app: &AppHandle, store: &SettingsStore, name: &str) -> AppResult<Profile> {
+    let settings = store.get();
+    let target = find(&settings, name)?;
+    let current = settings.profile();
+    if current.name == target.name {
+        return Ok(target);
+    }
+
+    let host = app.state::<Host>();
+    let compose_file = docker::compose_file_path(app)?;
+    let runtime = container::current(&host).await;
+
+    for other in settings
+        .profiles
+        .iter()
+        .filter(|p| p.name != current.name && p.name != target.name && p.clashes_with(&target))
+    {
+        if is_running(runtime.as_ref(), &compose_file, other).await {
+            return Err(AppError::new(
+                ErrorCode::PortInUse,
+                format!(
+                    "Profile {} is running on the ports {} uses",
+                    other.name, target.name
+                ),
+            )
+            .remediation(format!(
+                "Switch to {} and stop its services, or give {} other ports.",
+                other.name, target.name
+            )));
+        }
+    }
+
+    let was_running = is_running(runtime.as_ref(), &compose_file, &current).await;
+    if was_running && current.clashes_with(&target) {
+        docker::stop(&host, &compose_file).await?;
+    }
+
+    tracing::info!("Switching profile {} -> {}", current.name, target.name);
+    save(
+        app,
+        store,
+        AppSettings {
+            active_profile: target.name.clone(),
+            ..settings
+        },
+    )
+    .await?;
+
+    if was_running {
+        docker::start(&host, &compose_file).await?;
+    }
+    Ok(target)
+}
+
+/// Remove a profile and bring its stack down. Its volumes (chat history,
+/// Caddy data) are only deleted with `remove_data`.
+#[tauri::command]
+pub async fn delete_profile(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    name: String,
+    remove_data: Option<bool>,
+) -> AppResult<()> {
+    let remove_data = remove_data.unwrap_or(false);
+    let result = delete(&app, &store, &name, remove_data).await;
+    audit::recorded(
+        "profile.delete",
+        serde_json::json!({ "name": name, "remove_data": remove_data }),
+        result,
+    )
+}
+
+async fn delete(
+    app: &AppHandle,
+    store: &SettingsStore,
+    name: &str,
+    remove_data: bool,
+) -> AppResult<()> {
+    let settings = store.get();
+    let profile = find(&settings, name)?;
+    if settings.active_profile == profile.name {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("{} is the active profile", profile.name),
+        )
+        .remediation("Switch to another profile before deleting this one."));
+    }
+
+    let host = app.state::<Host>();
+    let compose_file = docker::compose_file_path(app)?;
+    let runtime = container::current(&host).await;
+    let mut down = runtime.compose_profile(&compose_file, &profile);
+    down.arg("down");
+    if remove_data {
+        down.arg("--volumes");
+    }
+    let result = timeout(Duration::from_secs(60), down.output())
+        .await
+        .map_err(|_| AppError::timeout("removing the profile's stack", 60))
+        .and_then(|o| o.map_err(|e| AppError::runtime_spawn(runtime.display_name(), e)))
+        .and_then(|o| match o.status.success() {
+            true => Ok(()),
+            false => Err(AppError::compose_failed(
+                runtime.display_name(),
+                &String::from_utf8_lossy(&o.stderr),
+            )),
+        });
+    match result {
+        Ok(()) => {}
+        // Data left behind has to be reported; a stack that can't be
+        // reached is most likely not running
+        Err(e) if remove_data => return Err(e.context("Deleting profile data")),
+        Err(e) => tracing::warn!("Profile {}: {}", profile.name, e.describe()),
+    }
+
+    let profiles = settings
+        .profiles
+        .iter()
+        .filter(|p| p.name != profile.name)
+        .cloned()
+        .collect();
+    save(
+        app,
+        store,
+        AppSettings {
+            profiles,
+            ..settings
+        },
+    )
+    .await?;
+    tracing::info!("Deleted profile {}", profile.name);
+    Ok(())
+}
+
+fn find(settings: &AppSettings, name: &str) -> AppResult<Profile> {
+    settings
+        .profiles
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidInput,
+                format!("No profile named {}", name),
+            )
+        })
+}
+
+async fn add(app: &AppHandle, store: &SettingsStore, profile: Profile) -> AppResult<Profile> {
+    let mut settings = store.get();
+    if settings.profiles.iter().any(|p| p.name == profile.name) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("A profile named {} already exists", profile.name),
+        ));
+    }
+    settings.profiles.push(profile.clone());
+    save(app, store, settings).await?;
+    tracing::info!(
+        "Created profile {} (project {})",
+        profile.name,
+        profile.project
+    );
+    Ok(profile)
+}
+
+/// Validate and persist, then apply the change (activation, model preload)
+async fn save(app: &AppHandle, store: &SettingsStore, updated: AppSettings) -> AppResult<()> {
+    let previous = store.get();
+    let updated = store.save(updated)?;
+    settings::apply_changes(app, &previous, &updated).await?;
+    Ok(())
+}
+
+/// Whether any of the profile's containers are up
+async fn is_running(
+    runtime: &dyn ContainerRuntime,
+    compose_file: &Path,
+    profile: &Profile,
+) -> bool {
+    let ps = timeout(
+        CMD_TIMEOUT,
+        runtime
+            .compose_profile(compose_file, profile)
+            .args(["ps", "-q"])
+            .output(),
+    )
+    .await;
+    matches!(ps, Ok(Ok(o)) if o.status.success() && !o.stdout.trim_ascii().is_empty())
+}
@@ -0,0 +1,218 @@
+// Settings commands
+// Also applies settings at startup and after changes
+
+use tauri::{AppHandle, Manager, State};
+
+use super::ollama::backend::BackendKind;
+use super::{docker, ollama};
+use crate::error::AppResult;
+use crate::services::audit;
+use crate::services::auto_heal::AutoHealer;
+use crate::services::gateway::{GatewayServer, GatewaySettings};
+use crate::services::host::Host;
+use crate::services::metrics::{Metrics, MetricsServer};
+use crate::services::profiles::Profile;
+use crate::services::settings::{AppSettings, SettingsStore};
+use crate::services::updates::UpdateChecker;
+
+/// Current settings, with defaults filled in for anything not saved yet.
+/// Secrets are masked; sending them back unchanged keeps them.
+#[tauri::command]
+pub fn get_settings(store: State<'_, SettingsStore>) -> AppSettings {
+    store.get().redacted()
+}
+
+/// Merge a partial settings object (e.g. `{"metrics": {"port": 9500}}`),
+/// validate, save and apply it
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    patch: serde_json::Value,
+) -> AppResult<AppSettings> {
+    // Which settings, not their values: some are secrets
+    let fields: Vec<String> = patch
+        .as_object()
+        .map(|patch| patch.keys().cloned().collect())
+        .unwrap_or_default();
+    let result = update(&app, &store, patch).await;
+    audit::recorded(
+        "settings.update",
+        serde_json::json!({ "fields": fields }),
+        result,
+    )
+}
+
+async fn update(
+    app: &AppHandle,
+    store: &SettingsStore,
+    patch: serde_json::Value,
+) -> AppResult<AppSettings> {
+    let (previous, updated) = store.update(patch)?;
+    apply_changes(app, &previous, &updated).await?;
+    Ok(updated)
+}
+
+/// Apply saved settings once the app is up: background services first, then
+/// (if enabled) Ollama and the compose stack, then the default model preload
+pub async fn apply_on_startup(app: AppHandle) {
+    let settings = app.state::<SettingsStore>().get();
+    let host = app.state::<Host>().inner().clone();
+    host.set_runtime_preference(settings.container_runtime);
+    host.activate(&settings.profile());
+
+    if let Err(e) = apply_metrics(&app, &settings).await {
+        tracing::error!("Failed to start metrics server: {}", e);
+    }
+    // Before the stack starts, so the webui is pointed at it
+    if let Err(e) = apply_gateway(&app, &settings.gateway).await {
+        tracing::error!("Failed to start API gateway: {}", e);
+    }
+    if let Err(e) = app
+        .state::<AutoHealer>()
+        .apply(&app, settings.auto_heal.clone())
+    {
+        tracing::error!("Failed to apply auto-heal policy: {}", e);
+    }
+    app.state::<UpdateChecker>().apply(&app, &settings);
+
+    if !settings.auto_start_services {
+        // A stack the last session left running reaches Ollama directly
+        if settings.gateway.enabled {
+            if let Err(e) = docker::recreate_services(&app, &["webui"]).await {
+                tracing::error!("Failed to route Open-WebUI through the gateway: {}", e);
+            }
+        }
+        return;
+    }
+
+    tracing::info!("Auto-starting services");
+    let profile = settings.profile();
+    // Other model servers are the user's to run. Without a model server the
+    // stack still starts, and the webui says what it can't reach.
+    if profile.backend == BackendKind::Ollama {
+        if let Err(e) = ollama::ensure_running(&host).await {
+            tracing::error!("Auto-start: {}", e.describe());
+        }
+    }
+    if let Err(e) = docker::start_services(app.clone()).await {
+        tracing::error!("Auto-start: failed to start services: {}", e.describe());
+    }
+    if let Err(e) = ollama::preload_model(&host, &profile.default_model).await {
+        tracing::warn!("Auto-start: {}", e.describe());
+    }
+}
+
+/// Apply the difference between two settings values
+pub(crate) async fn apply_changes(
+    app: &AppHandle,
+    previous: &AppSettings,
+    updated: &AppSettings,
+) -> Result<(), String> {
+    let host = app.state::<Host>();
+    if previous.container_runtime != updated.container_runtime {
+        host.set_runtime_preference(updated.container_runtime);
+    }
+    let (before, after) = (previous.profile(), updated.profile());
+    if before != after {
+        host.activate(&after);
+    }
+    if previous.metrics != updated.metrics {
+        apply_metrics(app, updated).await?;
+    }
+    if previous.gateway != updated.gateway {
+        apply_gateway(app, &updated.gateway).await?;
+    }
+    if previous.auto_heal != updated.auto_heal {
+        app.state::<AutoHealer>()
+            .apply(app, updated.auto_heal.clone())?;
+    }
+
+    if update_schedule_changed(previous, updated) {
+        app.state::<UpdateChecker>().apply(app, updated);
+    }
+
+    // Another profile or project is another stack: nothing to recreate
+    if before.name == after.name && before.project == after.project {
+        let mut services = services_affected_by(&before, &after);
+        if webui_route_changed(&previous.gateway, &updated.gateway) && !services.contains(&"webui")
+        {
+            services.push("webui");
+        }
+        if !services.is_empty() {
+            docker::recreate_services(app, &services).await?;
+        }
+    }
+
+    if before.default_model != after.default_model {
+        let model = after.default_model;
+        let host = app.state::<Host>().inner().clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = ollama::preload_model(&host, &model).await {
+                tracing::warn!("{}", e.describe());
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Compose services whose environment depends on a changed profile field
+/// (see `Profile::compose_env`)
+fn services_affected_by(previous: &Profile, updated: &Profile) -> Vec<&'static str> {
+    let mut services = Vec::new();
+    if previous.default_model != updated.default_model
+        || previous.ollama_url != updated.ollama_url
+        || previous.backend != updated.backend
+        || previous.api_key != updated.api_key
+    {
+        services.push("webui");
+    }
+    if previous.https_port != updated.https_port
+        || previous.http_port != updated.http_port
+        || previous.hostname != updated.hostname
+    {
+        services.push("caddy");
+    }
+    services
+}
+
+/// Whether Open-WebUI reaches Ollama another way (see `gateway::webui_endpoint`).
+/// Key changes alone are picked up by the running gateway.
+fn webui_route_changed(previous: &GatewaySettings, updated: &GatewaySettings) -> bool {
+    previous.enabled != updated.enabled
+        || (updated.enabled
+            && (previous.port != updated.port || previous.webui_api_key != updated.webui_api_key))
+}
+
+/// Dismissing a version doesn't reschedule (and so re-run) the check
+fn update_schedule_changed(previous: &AppSettings, updated: &AppSettings) -> bool {
+    previous.check_updates != updated.check_updates
+        || previous.offline != updated.offline
+        || previous.updates.manifest_url != updated.updates.manifest_url
+        || previous.updates.interval_hours != updated.updates.interval_hours
+}
+
+async fn apply_metrics(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let server = app.state::<MetricsServer>();
+    if settings.metrics.enabled {
+        let metrics = app.state::<Metrics>().inner().clone();
+        let host = app.state::<Host>().inner().clone();
+        server
+            .start(
+                metrics,
+                host,
+                &settings.metrics.bind_address,
+                settings.metrics.port,
+            )
+            .await?;
+    } else {
+        server.stop();
+    }
+    Ok(())
+}
+
+async fn apply_gateway(app: &AppHandle, settings: &GatewaySettings) -> Result<(), String> {
+    let host = app.state::<Host>().inner().clone();
+    app.state::<GatewayServer>().apply(host, settings).await?;
+    Ok(())
+}
@@ -1,20 +1,51 @@
 // Setup wizard commands
 
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
-use tokio::process::Command as TokioCommand;
-use tokio::time::{timeout, Duration};
-
-const CMD_TIMEOUT: Duration = Duration::from_secs(5);
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager, State, Window};
+use tokio::time::Duration;
+
+use super::gpu::{self, GpuInfo};
+use super::health::{self, HealthStatus};
+use super::{docker, installer, ollama};
+use crate::services::container::{self, RuntimeKind};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::audit;
+use crate::services::host::Host;
+use crate::services::settings::{AppSettings, SettingsStore};
+use crate::services::setup_wizard::{SetupStep, SetupWizard, StepRecord, StepStatus, STEPS};
+use crate::utils::paths::AppPaths;
+
+const PODMAN_DOWNLOAD_URL: &str = "https://podman.io/docs/installation";
+
+/// Hostname Caddy serves for the active profile
+pub(crate) fn https_domain(host: &Host) -> String {
+    host.profile().hostname
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Prerequisites {
     pub os: OsInfo,
     pub docker: DependencyStatus,
+    pub podman: DependencyStatus,
+    /// Runtime the app will use, per the `container_runtime` setting
+    pub container_runtime: RuntimeKind,
     pub ollama: DependencyStatus,
     pub model_dolphin: bool,
     pub installed_models: Vec<String>,
     pub https_configured: bool,
+    pub gpu: GpuInfo,
+}
+
+impl Prerequisites {
+    /// Status of the container runtime the app will use
+    pub fn runtime(&self) -> &DependencyStatus {
+        match self.container_runtime {
+            RuntimeKind::Docker => &self.docker,
+            RuntimeKind::Podman => &self.podman,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,13 +71,10 @@ pub struct SetupState {
     pub prerequisites_ok: bool,
     pub model_downloaded: bool,
     pub services_configured: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AppSettings {
-    pub default_model: String,
-    pub auto_start_services: bool,
-    pub check_updates: bool,
+    pub step: SetupStep,
+    pub steps: Vec<StepRecord>,
+    /// A step is running right now
+    pub busy: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -55,36 +83,47 @@ pub struct AvailableModel {
     pub size: String,
     pub ram_required: String,
     pub recommended: bool,
+    /// VRAM needed to run fully on GPU (weights + context)
+    pub vram_required_mb: u64,
+    /// Whether it fits the largest detected GPU; None when no GPU was found
+    pub fits_vram: Option<bool>,
 }
 
-/// Get list of available Dolphin models
+/// Get list of available Dolphin models, flagged against the detected GPU
 #[tauri::command]
-pub fn get_available_models() -> Vec<AvailableModel> {
-    vec![
-        AvailableModel {
-            name: "dolphin-phi".into(),
-            size: "1.6 GB".into(),
-            ram_required: "4 GB".into(),
-            recommended: false,
-        },
-        AvailableModel {
-            name: "dolphin-llama3:8b".into(),
-            size: "4.7 GB".into(),
-            ram_required: "8 GB".into(),
-            recommended: true,
-        },
+pub async fn get_available_models(host: State<'_, Host>) -> AppResult<Vec<AvailableModel>> {
+    Ok(available_models(&host).await)
+}
+
+pub(crate) async fn available_models(host: &Host) -> Vec<AvailableModel> {
+    let gpu = gpu::detect(host).await;
+    let max_vram = gpu.max_vram_mb();
+
+    let model = |name: &str, size: &str, ram: &str, recommended: bool, vram_mb: u64| {
         AvailableModel {
-            name: "dolphin-mixtral:8x7b".into(),
-            size: "26 GB".into(),
-            ram_required: "32 GB".into(),
-            recommended: false,
-        },
+            name: name.into(),
+            size: size.into(),
+            ram_required: ram.into(),
+            recommended,
+            vram_required_mb: vram_mb,
+            fits_vram: max_vram.map(|available| available >= vram_mb),
+        }
+    };
+
+    vec![
+        model("dolphin-phi", "1.6 GB", "4 GB", false, 3 * 1024),
+        model("dolphin-llama3:8b", "4.7 GB", "8 GB", true, 6 * 1024),
+        model("dolphin-mixtral:8x7b", "26 GB", "32 GB", false, 28 * 1024),
     ]
 }
 
 /// Detect system prerequisites
 #[tauri::command]
-pub async fn detect_prerequisites() -> Result<Prerequisites, String> {
+pub async fn detect_prerequisites(host: State<'_, Host>) -> AppResult<Prerequisites> {
+    prerequisites(&host).await
+}
+
+pub(crate) async fn prerequisites(host: &Host) -> AppResult<Prerequisites> {
     tracing::info!("Detecting system prerequisites");
 
     // OS Info
@@ -94,11 +133,14 @@ pub async fn detect_prerequisites() -> Result<Prerequisites, String> {
         arch: std::env::consts::ARCH.to_string(),
     };
 
-    // Run Docker and Ollama detection concurrently
-    let (docker, ollama, installed_models) = tokio::join!(
-        detect_docker(),
-        detect_ollama(),
-        get_installed_dolphin_models(),
+    // Run runtime and Ollama detection concurrently
+    let (docker, podman, active, ollama, installed_models, gpu) = tokio::join!(
+        detect_runtime(host, RuntimeKind::Docker),
+        detect_runtime(host, RuntimeKind::Podman),
+        container::current(host),
+        detect_ollama(host),
+        get_installed_dolphin_models(host),
+        gpu::detect(host),
     );
 
     let model_dolphin = installed_models
@@ -106,68 +148,49 @@ pub async fn detect_prerequisites() -> Result<Prerequisites, String> {
         .any(|m| m.starts_with("dolphin-llama3:8b"));
 
     // Check HTTPS configuration
-    let https_configured = check_https_configured();
+    let https_configured = check_https_configured(host);
 
     Ok(Prerequisites {
         os,
         docker,
+        podman,
+        container_runtime: active.kind(),
         ollama,
         model_dolphin,
         installed_models,
         https_configured,
+        gpu,
     })
 }
 
-async fn detect_docker() -> DependencyStatus {
-    // Use tokio::process::Command (non-blocking) with a timeout
-    let output = timeout(
-        CMD_TIMEOUT,
-        TokioCommand::new("docker")
-            .args(["version", "--format", "{{.Server.Version}}"])
-            .output(),
-    )
-    .await;
-
-    match output {
-        Ok(Ok(o)) if o.status.success() => {
-            let version = String::from_utf8_lossy(&o.stdout).trim().to_string();
-
-            // Check if daemon is responsive (also with timeout)
-            let running = timeout(
-                CMD_TIMEOUT,
-                TokioCommand::new("docker").args(["info"]).output(),
-            )
-            .await
-            .map(|r| r.map(|o| o.status.success()).unwrap_or(false))
-            .unwrap_or(false);
-
-            DependencyStatus {
-                installed: true,
-                running,
-                version: Some(version),
-                download_url: None,
-            }
-        }
-        _ => DependencyStatus {
-            installed: false,
+async fn detect_runtime(host: &Host, kind: RuntimeKind) -> DependencyStatus {
+    let runtime = container::runtime(host, kind).await;
+    match runtime.version().await {
+        Some(version) => DependencyStatus {
+            installed: true,
+            running: runtime.ping().await,
+            version: Some(version),
+            download_url: None,
+        },
+        None => DependencyStatus {
+            installed: runtime.installed(),
             running: false,
             version: None,
-            download_url: Some(get_docker_download_url()),
+            download_url: Some(match kind {
+                RuntimeKind::Docker => get_docker_download_url(),
+                RuntimeKind::Podman => PODMAN_DOWNLOAD_URL.to_string(),
+            }),
         },
     }
 }
 
-async fn detect_ollama() -> DependencyStatus {
+async fn detect_ollama(host: &Host) -> DependencyStatus {
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(2))
         .build()
         .unwrap();
 
-    match client
-        .get("http://localhost:11434/api/version")
-        .send()
-        .await
-    {
+    match client.get(host.ollama("/api/version")).send().await {
         Ok(response) if response.status().is_success() => {
             let version: serde_json::Value = response.json().await.unwrap_or_default();
             DependencyStatus {
@@ -177,49 +200,22 @@ async fn detect_ollama() -> DependencyStatus {
                 download_url: None,
             }
         }
-        _ => {
-            // Check if binary exists (with timeout)
-            let installed = check_binary_exists("ollama").await;
-
-            DependencyStatus {
-                installed,
-                running: false,
-                version: None,
-                download_url: Some(get_ollama_download_url()),
-            }
-        }
+        _ => DependencyStatus {
+            installed: host.on_path("ollama"),
+            running: false,
+            version: None,
+            download_url: Some(get_ollama_download_url()),
+        },
     }
 }
 
-/// Check if a binary exists on the system PATH (non-blocking + timeout)
-async fn check_binary_exists(name: &str) -> bool {
-    // Try `where` on Windows, `which` on Unix
-    let cmd = if cfg!(target_os = "windows") {
-        "where"
-    } else {
-        "which"
-    };
-
-    timeout(
-        Duration::from_secs(2),
-        TokioCommand::new(cmd).arg(name).output(),
-    )
-    .await
-    .map(|r| r.map(|o| o.status.success()).unwrap_or(false))
-    .unwrap_or(false)
-}
-
-async fn get_installed_dolphin_models() -> Vec<String> {
+async fn get_installed_dolphin_models(host: &Host) -> Vec<String> {
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(2))
         .build()
         .unwrap();
 
-    if let Ok(response) = client
-        .get("http://localhost:11434/api/tags")
-        .send()
-        .await
-    {
+    if let Ok(response) = client.get(host.ollama("/api/tags")).send().await {
         if let Ok(data) = response.json::<serde_json::Value>().await {
             if let Some(models) = data["models"].as_array() {
                 return models
@@ -233,16 +229,17 @@ async fn get_installed_dolphin_models() -> Vec<String> {
     vec![]
 }
 
-fn check_https_configured() -> bool {
-    // Check if /etc/hosts has dark-gpt.local
+pub(crate) fn check_https_configured(host: &Host) -> bool {
+    // Check if /etc/hosts has the active profile's hostname
+    let domain = https_domain(host);
     if let Ok(content) = std::fs::read_to_string("/etc/hosts") {
-        return content.contains("dark-gpt.local");
+        return content.contains(&domain);
     }
     // Windows hosts file
     if let Ok(content) =
         std::fs::read_to_string("C:\\Windows\\System32\\drivers\\etc\\hosts")
     {
-        return content.contains("dark-gpt.local");
+        return content.contains(&domain);
     }
     false
 }
@@ -300,52 +297,375 @@ fn get_ollama_download_url() -> String {
     }
 }
 
-/// Get current setup state
+/// Get current setup state, reconciled with what is installed
 #[tauri::command]
-pub async fn get_setup_state(app: AppHandle) -> Result<SetupState, String> {
-    let config_dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("Config dir error: {}", e))?;
-
-    let setup_file = config_dir.join("setup_complete");
-    let completed = setup_file.exists();
-
-    let prereqs = detect_prerequisites().await?;
-
-    Ok(SetupState {
-        completed,
-        current_step: if completed { 4 } else { 0 },
-        total_steps: 4,
-        prerequisites_ok: prereqs.docker.running && prereqs.ollama.running,
-        model_downloaded: prereqs.model_dolphin,
-        services_configured: prereqs.https_configured,
-    })
+pub async fn get_setup_state(
+    app: AppHandle,
+    host: State<'_, Host>,
+    wizard: State<'_, SetupWizard>,
+    store: State<'_, SettingsStore>,
+) -> AppResult<SetupState> {
+    let prereqs = prerequisites(&host).await?;
+    if !wizard.is_busy() {
+        let reality =
+            detect_reality(&app, &host, &prereqs, &store.get().profile().default_model).await;
+        let mut state = wizard.snapshot();
+        if state.reconcile(reality) {
+            wizard.update(|stored| *stored = state)?;
+        }
+    }
+    Ok(setup_state(&wizard, &prereqs))
 }
 
-/// Save app settings
+/// Run the current setup step. A failing step is recorded with its error in
+/// the returned state rather than returned as an error.
+#[tauri::command]
+pub async fn advance(
+    window: Window,
+    wizard: State<'_, SetupWizard>,
+    store: State<'_, SettingsStore>,
+) -> AppResult<SetupState> {
+    let step = wizard.snapshot().current;
+    let result = run_step(&window, &wizard, &store, step).await;
+    audit::recorded("setup.step", serde_json::json!({ "step": step }), result)
+}
+
+/// Run a failed (or interrupted) step again; earlier steps must be done
+#[tauri::command]
+pub async fn retry_step(
+    window: Window,
+    wizard: State<'_, SetupWizard>,
+    store: State<'_, SettingsStore>,
+    step: SetupStep,
+) -> AppResult<SetupState> {
+    if step == SetupStep::Done {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Nothing to retry"));
+    }
+    if !wizard.snapshot().can_run(step) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Finish the steps before {:?} first", step),
+        ));
+    }
+    let result = run_step(&window, &wizard, &store, step).await;
+    audit::recorded(
+        "setup.step",
+        serde_json::json!({ "step": step, "retry": true }),
+        result,
+    )
+}
+
+/// Start the wizard over from the first step
 #[tauri::command]
-pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
-    let config_dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("Config dir error: {}", e))?;
+pub async fn reset_setup(
+    host: State<'_, Host>,
+    wizard: State<'_, SetupWizard>,
+) -> AppResult<SetupState> {
+    if wizard.is_busy() {
+        return Err(AppError::new(ErrorCode::Busy, "A setup step is running"));
+    }
+    audit::recorded("setup.reset", serde_json::json!({}), wizard.reset().map_err(Into::into))?;
+    let prereqs = prerequisites(&host).await?;
+    Ok(setup_state(&wizard, &prereqs))
+}
+
+async fn run_step(
+    window: &Window,
+    wizard: &SetupWizard,
+    store: &SettingsStore,
+    step: SetupStep,
+) -> AppResult<SetupState> {
+    let host = window.state::<Host>();
+    if step == SetupStep::Done {
+        let prereqs = prerequisites(&host).await?;
+        return Ok(setup_state(wizard, &prereqs));
+    }
+    let _busy = wizard
+        .try_begin()
+        .ok_or_else(|| AppError::new(ErrorCode::Busy, "A setup step is already running"))?;
+
+    tracing::info!("Running setup step {:?}", step);
+    wizard.update(|s| s.set(step, StepStatus::Running, None))?;
+
+    let result = match step {
+        SetupStep::Prerequisites => prerequisites(&host).await.map(|_| ()),
+        SetupStep::Installs => step_installs(window, &host).await,
+        SetupStep::Certs => step_certs(window.app_handle(), &host).await,
+        SetupStep::Model => step_model(window, &host, &store.get().profile().default_model).await,
+        SetupStep::Services => docker::start_services(window.app_handle().clone())
+            .await
+            .map(|_| ()),
+        SetupStep::Done => Ok(()),
+    };
+
+    match result {
+        Ok(()) => {
+            tracing::info!("Setup step {:?} completed", step);
+            wizard.update(|s| s.set(step, StepStatus::Completed, None))?;
+        }
+        Err(e) => {
+            tracing::warn!("Setup step {:?} failed: {}", step, e);
+            wizard.update(|s| s.set(step, StepStatus::Failed, Some(e.describe())))?;
+        }
+    }
+
+    let prereqs = prerequisites(&host).await?;
+    Ok(setup_state(wizard, &prereqs))
+}
+
+/// A container runtime and Ollama installed, Ollama serving, runtime up.
+/// Docker is only installed when no runtime is; an existing Podman is used as is.
+async fn step_installs(window: &Window, host: &Host) -> AppResult<()> {
+    let (runtime, ollama) = tokio::join!(container::current(host), detect_ollama(host));
+    if !ollama.installed {
+        installer::install_ollama(window.clone()).await?;
+    }
+    match runtime.kind() {
+        _ if runtime.installed() => {}
+        RuntimeKind::Docker => installer::install_docker(window.clone()).await?,
+        RuntimeKind::Podman => {
+            return Err(
+                AppError::new(ErrorCode::DockerNotInstalled, "Podman is selected but not installed")
+                    .remediation(format!(
+                        "Install it ({}) or switch the container runtime to Docker, \
+                         then retry this step.",
+                        PODMAN_DOWNLOAD_URL
+                    )),
+            )
+        }
+    }
+
+    ollama::ensure_running(host).await?;
+    let runtime = container::current(host).await;
+    if !runtime.ping().await {
+        return Err(AppError::new(
+            ErrorCode::DaemonDown,
+            format!("{} is installed but not running", runtime.display_name()),
+        )
+        .remediation(format!(
+            "Start {}, then retry this step.",
+            runtime.display_name()
+        )));
+    }
+    Ok(())
+}
+
+/// mkcert certificates for the active profile's hostname and the hosts entry
+async fn step_certs(app: &AppHandle, host: &Host) -> AppResult<()> {
+    let domain = https_domain(host);
+    let certs_dir = certs_dir(app)?;
+    if !certs_present(&certs_dir, &domain) {
+        let mkcert = |args: &[&str]| {
+            let mut cmd = host.command("mkcert");
+            cmd.args(args).current_dir(&certs_dir);
+            async move { cmd.output().await }
+        };
+        std::fs::create_dir_all(&certs_dir)
+            .map_err(|e| format!("Failed to create {:?}: {}", certs_dir, e))?;
+
+        let install = mkcert(&["-install"]).await.map_err(|_| {
+            AppError::new(ErrorCode::Io, "mkcert not found").remediation(
+                "Install mkcert (https://github.com/FiloSottile/mkcert), then retry this step.",
+            )
+        })?;
+        if !install.status.success() {
+            let stderr = String::from_utf8_lossy(&install.stderr);
+            return Err(AppError::new(ErrorCode::Io, "mkcert -install failed").cause(stderr));
+        }
+
+        let generate = mkcert(&[
+            "-cert-file",
+            &format!("{}.pem", domain),
+            "-key-file",
+            &format!("{}-key.pem", domain),
+            &domain,
+            "localhost",
+            "127.0.0.1",
+            "::1",
+        ])
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Io, "Failed to run mkcert").cause(e))?;
+        if !generate.status.success() {
+            let stderr = String::from_utf8_lossy(&generate.stderr);
+            return Err(AppError::new(ErrorCode::Io, "Certificate generation failed").cause(stderr));
+        }
+    }
 
-    std::fs::create_dir_all(&config_dir)
-        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    if !check_https_configured(host) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("{} is missing from the hosts file", domain),
+        )
+        .remediation(format!(
+            "Add \"127.0.0.1 {}\" to your hosts file (needs administrator rights), \
+             then retry this step.",
+            domain
+        )));
+    }
+    Ok(())
+}
+
+/// Pull the default model. Ollama keeps partially downloaded layers, so
+/// running this again after an interruption resumes the download.
+async fn step_model(window: &Window, host: &Host, model: &str) -> AppResult<()> {
+    ollama::ensure_running(host).await?;
+    if installed_models(host).await.iter().any(|m| m == model) {
+        return Ok(());
+    }
+    ollama::pull_model(window.clone(), model.to_string()).await
+}
+
+/// What can be checked on the machine for each step (None = trust the
+/// stored state). The prerequisites step is the detection that produced
+/// `prereqs`, so it holds whenever that succeeded. Stopped services don't
+/// undo setup, so the services step is only ever confirmed, never reopened.
+async fn detect_reality(
+    app: &AppHandle,
+    host: &Host,
+    prereqs: &Prerequisites,
+    default_model: &str,
+) -> impl Fn(SetupStep) -> Option<bool> {
+    let installs = prereqs.runtime().installed && prereqs.ollama.installed;
+    let domain = https_domain(host);
+    let certs =
+        certs_dir(app).is_ok_and(|dir| certs_present(&dir, &domain)) && prereqs.https_configured;
+    let model = if prereqs.ollama.running {
+        Some(installed_models(host).await.iter().any(|m| m == default_model))
+    } else {
+        None
+    };
+    let services = prereqs.runtime().running
+        && health::collect_health(host)
+            .await
+            .is_ok_and(|(h, _)| h.webui.status == HealthStatus::Healthy);
+
+    move |step| match step {
+        SetupStep::Installs => Some(installs),
+        SetupStep::Certs => Some(certs),
+        SetupStep::Model => model,
+        SetupStep::Services => services.then_some(true),
+        SetupStep::Prerequisites => Some(true),
+        SetupStep::Done => None,
+    }
+}
+
+fn setup_state(wizard: &SetupWizard, prereqs: &Prerequisites) -> SetupState {
+    let wizard_state = wizard.snapshot();
+    let done = |step| {
+        wizard_state
+            .record(step)
+            .is_some_and(|r| r.status == StepStatus::Completed)
+    };
+
+    SetupState {
+        completed: wizard_state.current == SetupStep::Done,
+        current_step: wizard_state.current.index(),
+        total_steps: STEPS.len() as u32,
+        prerequisites_ok: prereqs.runtime().running && prereqs.ollama.running,
+        model_downloaded: done(SetupStep::Model),
+        services_configured: done(SetupStep::Services),
+        step: wizard_state.current,
+        steps: wizard_state.steps,
+        busy: wizard.is_busy(),
+    }
+}
+
+async fn installed_models(host: &Host) -> Vec<String> {
+    ollama::models(host)
+        .await
+        .map(|models| models.into_iter().map(|m| m.name).collect())
+        .unwrap_or_default()
+}
 
-    let settings_file = config_dir.join("settings.json");
-    let json = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+pub(crate) fn certs_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(AppPaths::from_app(app)?.certs_dir())
+}
 
-    std::fs::write(&settings_file, json)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
+fn certs_present(dir: &Path, domain: &str) -> bool {
+    dir.join(format!("{}.pem", domain)).exists() && dir.join(format!("{}-key.pem", domain)).exists()
+}
 
-    // Mark setup as complete
-    let setup_file = config_dir.join("setup_complete");
-    std::fs::write(&setup_file, "1")
-        .map_err(|e| format!("Failed to mark setup complete: {}", e))?;
+/// Save app settings
+#[tauri::command]
+pub async fn save_settings(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    settings: AppSettings,
+) -> AppResult<()> {
+    let result = save(&app, &store, settings).await;
+    audit::recorded("settings.save", serde_json::json!({}), result)
+}
 
-    tracing::info!("Settings saved to {:?}", settings_file);
+async fn save(app: &AppHandle, store: &SettingsStore, settings: AppSettings) -> AppResult<()> {
+    let previous = store.get();
+    let saved = store.save(settings)?;
+    super::settings::apply_changes(app, &previous, &saved).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    #[tokio::test]
+    async fn detects_runtimes_from_their_cli() {
+        let fake = FakeRunner::new();
+        fake.installed("docker")
+            .on("docker version", "27.3.1")
+            .on("docker info", "ok")
+            .installed("podman");
+        let host = fake.host("http://127.0.0.1:9");
+
+        let docker = detect_runtime(&host, RuntimeKind::Docker).await;
+        assert!(docker.installed && docker.running);
+        assert_eq!(docker.version.as_deref(), Some("27.3.1"));
+        assert!(docker.download_url.is_none());
+
+        // On PATH but `podman version` fails: installed, not running
+        let podman = detect_runtime(&host, RuntimeKind::Podman).await;
+        assert!(podman.installed && !podman.running);
+        assert_eq!(podman.download_url.as_deref(), Some(PODMAN_DOWNLOAD_URL));
+    }
+
+    #[tokio::test]
+    async fn detects_ollama_and_dolphin_models() {
+        let stub = StubOllama::start().await;
+        stub.route(Method::GET, "/api/version", 200, r#"{"version":"0.5.7"}"#)
+            .route(
+                Method::GET,
+                "/api/tags",
+                200,
+                r#"{"models":[{"name":"dolphin-llama3:8b"},{"name":"llama3.2:3b"}]}"#,
+            );
+        let host = FakeRunner::new().host(&stub.url);
+
+        let ollama = detect_ollama(&host).await;
+        assert!(ollama.running);
+        assert_eq!(ollama.version.as_deref(), Some("0.5.7"));
+        assert_eq!(
+            get_installed_dolphin_models(&host).await,
+            ["dolphin-llama3:8b"]
+        );
+
+        let fake = FakeRunner::new();
+        fake.installed("ollama");
+        let ollama = detect_ollama(&fake.host("http://127.0.0.1:9")).await;
+        assert!(ollama.installed && !ollama.running);
+        assert!(ollama.download_url.is_some());
+    }
+
+    #[tokio::test]
+    async fn model_list_is_flagged_against_gpu_vram() {
+        let fake = FakeRunner::new();
+        fake.on("nvidia-smi", "NVIDIA GeForce RTX 3060, 12288, 550.54");
+        let models = available_models(&fake.host("http://127.0.0.1:9")).await;
+        let fits = |name: &str| models.iter().find(|m| m.name == name).unwrap().fits_vram;
+        assert_eq!(fits("dolphin-llama3:8b"), Some(true));
+        assert_eq!(fits("dolphin-mixtral:8x7b"), Some(false));
+
+        let models = available_models(&FakeRunner::new().host("http://127.0.0.1:9")).await;
+        assert!(models.iter().all(|m| m.fits_vram.is_none()));
+    }
+}
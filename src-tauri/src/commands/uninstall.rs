@@ -0,0 +1,888 @@
+// Uninstall commands
+// Plans (dry run) and removes what Dark-GPT created, by category
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+use tokio::time::{timeout, Duration};
+
+use super::setup;
+use super::{docker, installer, ollama};
+use crate::error::AppResult;
+use crate::services::audit;
+use crate::services::container::{self, ContainerRuntime};
+use crate::services::host::{Cmd, Host};
+use crate::services::instance;
+use crate::services::settings::{self, SettingsStore};
+use crate::services::setup_wizard::SetupWizard;
+use crate::utils::paths::AppPaths;
+
+const CMD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Groups the user can pick, in removal order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UninstallCategory {
+    Containers,
+    Network,
+    Volumes,
+    Certs,
+    HostsEntry,
+    Models,
+    /// Settings (with profiles and gateway keys), wizard state, app data
+    Settings,
+    Logs,
+    /// Kept unless picked on its own: the record of what the app did
+    AuditLog,
+}
+
+const CATEGORIES: [UninstallCategory; 9] = [
+    UninstallCategory::Containers,
+    UninstallCategory::Network,
+    UninstallCategory::Volumes,
+    UninstallCategory::Certs,
+    UninstallCategory::HostsEntry,
+    UninstallCategory::Models,
+    UninstallCategory::Settings,
+    UninstallCategory::Logs,
+    UninstallCategory::AuditLog,
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    pub category: UninstallCategory,
+    /// Container/volume/network/model name, path, or hosts line
+    pub name: String,
+    /// None when the size can't be measured (network, hosts entry, or the
+    /// runtime doesn't report it)
+    pub size_bytes: Option<u64>,
+    /// What's inside, when the name doesn't say (e.g. "2 profiles")
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategorySummary {
+    pub category: UninstallCategory,
+    pub count: usize,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UninstallPlan {
+    pub artifacts: Vec<Artifact>,
+    pub categories: Vec<CategorySummary>,
+    pub total_bytes: u64,
+    /// Things that couldn't be inspected (e.g. runtime or Ollama down)
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Leftover {
+    pub artifact: Artifact,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UninstallReport {
+    pub removed: Vec<Artifact>,
+    pub leftovers: Vec<Leftover>,
+    /// Settings were removed; the app should be restarted
+    pub restart_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UninstallProgress {
+    pub category: UninstallCategory,
+    pub artifact: String,
+    /// 1-based position among the artifacts being removed
+    pub index: usize,
+    pub total: usize,
+    pub phase: String, // removing | removed | failed
+    pub message: String,
+}
+
+/// List every artifact Dark-GPT created, with sizes. Changes nothing.
+#[tauri::command]
+pub async fn plan_uninstall(
+    app: AppHandle,
+    host: State<'_, Host>,
+    store: State<'_, SettingsStore>,
+) -> AppResult<UninstallPlan> {
+    let paths = AppPaths::from_app(&app)?;
+    let runtime = container::current(&host).await;
+    let default_model = store.get().profile().default_model;
+    Ok(build_plan(
+        &host,
+        runtime.as_ref(),
+        &paths,
+        &hosts_file(),
+        &default_model,
+    )
+    .await)
+}
+
+/// Remove the artifacts in the chosen categories. Failures don't stop the
+/// run; whatever is still there afterwards is reported as a leftover.
+#[tauri::command]
+pub async fn uninstall(
+    window: Window,
+    store: State<'_, SettingsStore>,
+    wizard: State<'_, SetupWizard>,
+    categories: Vec<UninstallCategory>,
+) -> AppResult<UninstallReport> {
+    let result = remove_selected(&window, &store, &wizard, &categories).await;
+    audit::recorded(
+        "uninstall",
+        serde_json::json!({ "categories": categories }),
+        result,
+    )
+}
+
+async fn remove_selected(
+    window: &Window,
+    store: &SettingsStore,
+    wizard: &SetupWizard,
+    categories: &[UninstallCategory],
+) -> AppResult<UninstallReport> {
+    let app = window.app_handle().clone();
+    let host = app.state::<Host>().inner().clone();
+    let paths = AppPaths::from_app(&app)?;
+    let hosts = hosts_file();
+    let runtime = container::current(&host).await;
+    let default_model = store.get().profile().default_model;
+    let plan = build_plan(&host, runtime.as_ref(), &paths, &hosts, &default_model).await;
+    let selected: Vec<&Artifact> = plan
+        .artifacts
+        .iter()
+        .filter(|a| categories.contains(&a.category))
+        .collect();
+    tracing::info!(
+        "Uninstalling {:?} ({} artifacts)",
+        categories,
+        selected.len()
+    );
+
+    let mut removed = Vec::new();
+    let mut leftovers = Vec::new();
+    for (i, artifact) in selected.iter().enumerate() {
+        let progress = |phase: &str, message: String| {
+            let _ = window.emit(
+                "uninstall-progress",
+                UninstallProgress {
+                    category: artifact.category,
+                    artifact: artifact.name.clone(),
+                    index: i + 1,
+                    total: selected.len(),
+                    phase: phase.to_string(),
+                    message,
+                },
+            );
+        };
+
+        progress("removing", format!("Removing {}", artifact.name));
+        match remove(&host, runtime.as_ref(), &paths, &hosts, artifact).await {
+            Ok(()) => {
+                progress("removed", format!("Removed {}", artifact.name));
+                removed.push((*artifact).clone());
+            }
+            Err(reason) => {
+                tracing::warn!("Failed to remove {}: {}", artifact.name, reason);
+                progress("failed", reason.clone());
+                leftovers.push(Leftover {
+                    artifact: (*artifact).clone(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    let restart_required = removed
+        .iter()
+        .any(|a| a.category == UninstallCategory::Settings);
+    if restart_required {
+        wizard.reset()?;
+    }
+
+    // Anything reported removed but still found is a leftover too
+    let after = build_plan(&host, runtime.as_ref(), &paths, &hosts, &default_model).await;
+    removed.retain(|a| {
+        let still_there = after
+            .artifacts
+            .iter()
+            .any(|b| b.category == a.category && b.name == a.name);
+        if still_there {
+            leftovers.push(Leftover {
+                artifact: a.clone(),
+                reason: "Still present after removal".to_string(),
+            });
+        }
+        !still_there
+    });
+
+    tracing::info!(
+        "Uninstall finished: {} removed, {} left over",
+        removed.len(),
+        leftovers.len()
+    );
+    Ok(UninstallReport {
+        removed,
+        leftovers,
+        restart_required,
+    })
+}
+
+async fn build_plan(
+    host: &Host,
+    runtime: &dyn ContainerRuntime,
+    paths: &AppPaths,
+    hosts: &Path,
+    default_model: &str,
+) -> UninstallPlan {
+    let mut artifacts = Vec::new();
+    let mut warnings = Vec::new();
+
+    match runtime_artifacts(runtime, &paths.compose_file()).await {
+        Ok(found) => artifacts.extend(found),
+        Err(e) => warnings.push(format!(
+            "{} not available, containers, network and volumes were not checked: {}",
+            runtime.display_name(),
+            e
+        )),
+    }
+
+    for entry in std::fs::read_dir(paths.certs_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "pem") {
+            artifacts.push(path_artifact(UninstallCategory::Certs, &path));
+        }
+    }
+
+    for line in hosts_lines(hosts, &setup::https_domain(host)) {
+        artifacts.push(Artifact {
+            category: UninstallCategory::HostsEntry,
+            name: line,
+            size_bytes: None,
+            detail: None,
+        });
+    }
+
+    match model_artifacts(host, default_model).await {
+        Ok(found) => artifacts.extend(found),
+        Err(e) => warnings.push(format!("Models were not checked: {}", e)),
+    }
+
+    artifacts.extend(file_artifacts(paths));
+
+    let categories: Vec<CategorySummary> = CATEGORIES
+        .iter()
+        .map(|category| {
+            let items = artifacts.iter().filter(|a| a.category == *category);
+            CategorySummary {
+                category: *category,
+                count: items.clone().count(),
+                size_bytes: items.filter_map(|a| a.size_bytes).sum(),
+            }
+        })
+        .collect();
+    let total_bytes = categories.iter().map(|c| c.size_bytes).sum();
+
+    UninstallPlan {
+        artifacts,
+        categories,
+        total_bytes,
+        warnings,
+    }
+}
+
+/// Containers of the compose project, its network and named volumes
+async fn runtime_artifacts(
+    runtime: &dyn ContainerRuntime,
+    compose_file: &Path,
+) -> Result<Vec<Artifact>, String> {
+    let config = docker::rendered_compose_config(runtime, compose_file).await?;
+    let project = config["name"]
+        .as_str()
+        .ok_or("Compose config has no project name")?;
+    let sizes = disk_usage(runtime).await;
+    let size_of = |kind: &str, name: &str| {
+        sizes
+            .iter()
+            .find(|(k, n, _)| k == kind && n == name)
+            .map(|(_, _, size)| *size)
+    };
+
+    let mut artifacts = Vec::new();
+    let label = format!("label=com.docker.compose.project={}", project);
+    let containers =
+        run(runtime
+            .command()
+            .args(["ps", "-a", "--filter", &label, "--format", "{{.Names}}"]))
+        .await?;
+    for name in containers.lines().filter(|l| !l.is_empty()) {
+        artifacts.push(Artifact {
+            category: UninstallCategory::Containers,
+            name: name.to_string(),
+            size_bytes: size_of("container", name),
+            detail: None,
+        });
+    }
+
+    for name in docker::compose_names(&config, "networks") {
+        if run(runtime.command().args(["network", "inspect", &name]))
+            .await
+            .is_ok()
+        {
+            artifacts.push(Artifact {
+                category: UninstallCategory::Network,
+                name,
+                size_bytes: None,
+                detail: None,
+            });
+        }
+    }
+
+    for name in docker::compose_names(&config, "volumes") {
+        if run(runtime.command().args(["volume", "inspect", &name]))
+            .await
+            .is_ok()
+        {
+            artifacts.push(Artifact {
+                category: UninstallCategory::Volumes,
+                size_bytes: size_of("volume", &name),
+                name,
+                detail: None,
+            });
+        }
+    }
+    Ok(artifacts)
+}
+
+/// (kind, name, bytes) for containers and volumes, from `system df -v`.
+/// Empty when the runtime can't report it.
+async fn disk_usage(runtime: &dyn ContainerRuntime) -> Vec<(String, String, u64)> {
+    let Ok(output) = run(runtime
+        .command()
+        .args(["system", "df", "-v", "--format", "json"]))
+    .await
+    else {
+        return vec![];
+    };
+    let Ok(df) = serde_json::from_str::<serde_json::Value>(&output) else {
+        return vec![];
+    };
+
+    let mut sizes = Vec::new();
+    for (kind, section, name_key) in [
+        ("container", "Containers", "Names"),
+        ("volume", "Volumes", "Name"),
+    ] {
+        for entry in df[section].as_array().into_iter().flatten() {
+            let size = entry["Size"].as_str().and_then(parse_size);
+            if let (Some(name), Some(size)) = (entry[name_key].as_str(), size) {
+                sizes.push((kind.to_string(), name.to_string(), size));
+            }
+        }
+    }
+    sizes
+}
+
+/// Parse Docker's human sizes ("1.2GB", "512kB", "0B (virtual 1GB)");
+/// Docker uses decimal units
+fn parse_size(text: &str) -> Option<u64> {
+    let text = text.split_whitespace().next()?;
+    let split = text.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "B" => 1.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+/// Models offered by the setup wizard (plus the default) that are installed
+async fn model_artifacts(host: &Host, default_model: &str) -> Result<Vec<Artifact>, String> {
+    let installed = ollama::models(host).await.map_err(|e| e.describe())?;
+    let offered: Vec<String> = setup::available_models(host)
+        .await
+        .into_iter()
+        .map(|m| m.name)
+        .chain(std::iter::once(default_model.to_string()))
+        .collect();
+    let base = |name: &str| name.strip_suffix(":latest").unwrap_or(name).to_string();
+
+    Ok(installed
+        .into_iter()
+        .filter(|m| offered.iter().any(|o| base(o) == base(&m.name)))
+        .map(|m| Artifact {
+            category: UninstallCategory::Models,
+            name: m.name,
+            size_bytes: Some(m.size),
+            detail: None,
+        })
+        .collect())
+}
+
+/// Each file and folder in the app's own directories, so the dry run shows
+/// what goes (and how big it is) rather than just the directories
+fn file_artifacts(paths: &AppPaths) -> Vec<Artifact> {
+    let mut artifacts = Vec::new();
+    let mut seen: Vec<&PathBuf> = Vec::new();
+    for dir in [&paths.config_dir, &paths.data_dir, &paths.log_dir] {
+        if seen.contains(&dir) {
+            continue;
+        }
+        seen.push(dir);
+
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            // Listed under their own categories
+            .filter(|path| *path != paths.certs_dir() && *path != paths.log_dir)
+            // Held by the running app, removed when it exits
+            .filter(|path| !path.ends_with(instance::LOCK_FILE))
+            .collect();
+        entries.sort();
+        for path in entries {
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let category = if dir == &paths.log_dir {
+                UninstallCategory::Logs
+            } else if audit::FILES.contains(&file_name.as_str()) {
+                UninstallCategory::AuditLog
+            } else {
+                UninstallCategory::Settings
+            };
+            let mut artifact = path_artifact(category, &path);
+            if file_name == settings::SETTINGS_FILE {
+                artifact.detail = settings_detail(&path);
+            }
+            artifacts.push(artifact);
+        }
+    }
+    artifacts
+}
+
+/// "2 profiles, 1 gateway API key": what deleting settings.json loses
+fn settings_detail(path: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let settings: serde_json::Value = serde_json::from_str(&text).ok()?;
+    let count = |value: &serde_json::Value| value.as_array().map_or(0, Vec::len);
+    let plural = |n: usize, what: &str| format!("{} {}{}", n, what, if n == 1 { "" } else { "s" });
+    Some(format!(
+        "{}, {}",
+        plural(count(&settings["profiles"]), "profile"),
+        plural(count(&settings["gateway"]["keys"]), "gateway API key")
+    ))
+}
+
+async fn remove(
+    host: &Host,
+    runtime: &dyn ContainerRuntime,
+    paths: &AppPaths,
+    hosts: &Path,
+    artifact: &Artifact,
+) -> Result<(), String> {
+    let name = artifact.name.as_str();
+    match artifact.category {
+        UninstallCategory::Containers => {
+            run(runtime.command().args(["rm", "-f", "-v", name])).await?;
+        }
+        UninstallCategory::Network => {
+            run(runtime.command().args(["network", "rm", name])).await?;
+        }
+        UninstallCategory::Volumes => {
+            run(runtime.command().args(["volume", "rm", name])).await?;
+        }
+        UninstallCategory::Certs => {
+            std::fs::remove_file(name).map_err(|e| format!("Failed to delete: {}", e))?;
+            // Gone with its last certificate
+            let _ = std::fs::remove_dir(paths.certs_dir());
+        }
+        UninstallCategory::HostsEntry => remove_hosts_entry(host, hosts).await?,
+        UninstallCategory::Models => ollama::delete_model(host, name)
+            .await
+            .map_err(|e| e.describe())?,
+        UninstallCategory::Settings | UninstallCategory::Logs | UninstallCategory::AuditLog => {
+            let path = Path::new(name);
+            // The app keeps running; only delete what it owns
+            let owned = [&paths.config_dir, &paths.data_dir, &paths.log_dir];
+            if !owned.iter().any(|dir| path.parent() == Some(dir.as_path())) {
+                return Err("Not in an app directory".to_string());
+            }
+            if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            }
+            .map_err(|e| format!("Failed to delete: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a runtime CLI command, returning stdout
+async fn run(cmd: &Cmd) -> Result<String, String> {
+    let output = timeout(CMD_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| "Timed out".to_string())?
+        .map_err(|e| format!("Failed to run: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn path_artifact(category: UninstallCategory, path: &Path) -> Artifact {
+    Artifact {
+        category,
+        name: path.to_string_lossy().to_string(),
+        size_bytes: Some(disk_size(path)),
+        detail: None,
+    }
+}
+
+/// Size of a file, or of a directory tree
+fn disk_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| disk_size(&entry.path()))
+        .sum()
+}
+
+fn hosts_file() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        let root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+        Path::new(&root).join("System32\\drivers\\etc\\hosts")
+    } else {
+        PathBuf::from("/etc/hosts")
+    }
+}
+
+/// Hosts file lines mapping `domain`
+fn hosts_lines(hosts: &Path, domain: &str) -> Vec<String> {
+    std::fs::read_to_string(hosts)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| {
+            !line.trim_start().starts_with('#')
+                && line.split_whitespace().skip(1).any(|h| h == domain)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Drops the lines mapping `$1` from hosts file `$2`. Same match as
+/// `hosts_lines`: the domain as a hostname field of a non-comment line.
+/// Rewrites in place (cat >) to keep the file's owner and mode, and only
+/// once the filtered copy was written in full.
+const HOSTS_REWRITE_SCRIPT: &str = "set -e\n\
+    DOMAIN=\"$1\" awk '$1 !~ /^#/ { for (i = 2; i <= NF; i++) \
+    if ($i == ENVIRON[\"DOMAIN\"]) next } { print }' \"$2\" > \"$2.dark-gpt\"\n\
+    cat \"$2.dark-gpt\" > \"$2\"\n\
+    rm -f \"$2.dark-gpt\"";
+
+/// Drop our hosts lines. The hosts file is root/admin-owned, so this asks
+/// for elevation (pkexec, the macOS admin prompt, or UAC).
+async fn remove_hosts_entry(host: &Host, hosts: &Path) -> Result<(), String> {
+    let domain = setup::https_domain(host);
+    let hosts = hosts.to_string_lossy();
+
+    if cfg!(target_os = "linux") {
+        return installer::run_elevated(host, HOSTS_REWRITE_SCRIPT, &[&domain, &hosts])
+            .await
+            .map_err(|e| e.describe());
+    }
+
+    let output = if cfg!(target_os = "macos") {
+        let script = format!(
+            "do shell script \"sed -i '' '/[[:space:]]{}$/d; /[[:space:]]{}[[:space:]]/d' {}\" \
+             with administrator privileges",
+            domain.replace('.', "\\\\."),
+            domain.replace('.', "\\\\."),
+            hosts
+        );
+        host.command("osascript")
+            .args(["-e", &script])
+            .output()
+            .await
+    } else {
+        // The elevated PowerShell reads the domain and path from its own
+        // arguments, so nothing is interpolated into the command
+        let inner = "param($d, $p) \
+                     $lines = Get-Content -LiteralPath $p | \
+                     Where-Object { ($_ -split '\\s+' | Select-Object -Skip 1) -notcontains $d }; \
+                     Set-Content -LiteralPath $p -Value $lines";
+        let command = format!(
+            "Start-Process powershell -Verb RunAs -Wait -WindowStyle Hidden \
+             -ArgumentList '-NoProfile','-Command','& {{ {} }}','{}','{}'",
+            inner.replace('\'', "''"),
+            domain,
+            hosts.replace('\'', "''")
+        );
+        host.command("powershell")
+            .args(["-NoProfile", "-Command", &command])
+            .output()
+            .await
+    }
+    .map_err(|e| format!("Failed to request elevation: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Could not edit {} (authorization cancelled?): {}",
+            hosts,
+            stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::container::RuntimeKind;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    const COMPOSE_CONFIG: &str = include_str!("../../tests/fixtures/ports/compose-config.json");
+    const HOSTS: &str = include_str!("../../tests/fixtures/uninstall/hosts");
+    const HOSTS_CLEANED: &str = include_str!("../../tests/fixtures/uninstall/hosts-cleaned");
+
+    /// App directories under `root`, laid out like on Linux (logs in the
+    /// data dir), with a bit of everything the app writes
+    fn app_dirs(root: &Path) -> AppPaths {
+        let paths = AppPaths {
+            resource_dir: root.join("resources"),
+            config_dir: root.join("config"),
+            data_dir: root.join("data"),
+            log_dir: root.join("data/logs"),
+        };
+        let files = [
+            (
+                paths.config_dir.join("settings.json"),
+                r#"{"profiles":[{"name":"default"},{"name":"work"}],"gateway":{"keys":[{"id":"ci"}]}}"#,
+            ),
+            (paths.config_dir.join("setup_state.json"), "{}"),
+            (paths.config_dir.join("audit.genesis"), "{}"),
+            (paths.data_dir.join("audit.jsonl"), "{}\n{}\n"),
+            (paths.data_dir.join("audit.head"), "{}"),
+            (paths.data_dir.join("benchmarks.jsonl"), "{}\n"),
+            (paths.data_dir.join("instance.lock"), "{}"),
+            (paths.certs_dir().join("dark-gpt.local.pem"), "cert"),
+            (paths.log_dir.join("dark-gpt.2026-10-19.log"), "line\n"),
+        ];
+        for (path, content) in files {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        paths
+    }
+
+    fn names(plan: &UninstallPlan, category: UninstallCategory) -> Vec<String> {
+        plan.artifacts
+            .iter()
+            .filter(|a| a.category == category)
+            .map(|a| {
+                let name = Path::new(&a.name).file_name().map(|n| n.to_string_lossy());
+                name.map_or(a.name.clone(), |n| n.to_string())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn plans_every_artifact_with_its_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = app_dirs(dir.path());
+        let hosts = dir.path().join("hosts");
+        std::fs::write(&hosts, HOSTS).unwrap();
+
+        let stub = StubOllama::start().await;
+        stub.route(
+            Method::GET,
+            "/api/tags",
+            200,
+            r#"{"models":[
+                {"name":"dolphin-phi:latest","size":1600000000,"modified_at":"2026-10-01T12:00:00Z"},
+                {"name":"llama3:8b","size":4700000000,"modified_at":"2026-10-01T12:00:00Z"}]}"#,
+        );
+        let fake = FakeRunner::new();
+        fake.installed("docker")
+            .on("docker compose -p dark-gpt -f", COMPOSE_CONFIG)
+            .on("docker ps -a", "dark-gpt-caddy\ndark-gpt-webui\n")
+            .on("docker network inspect dark-gpt_dark-gpt-network", "[]")
+            .on("docker volume inspect docker_webui_data", "[]")
+            .on(
+                "docker system df",
+                r#"{"Containers":[{"Names":"dark-gpt-webui","Size":"12kB"}],
+                    "Volumes":[{"Name":"docker_webui_data","Size":"1.5GB"}]}"#,
+            );
+        let host = fake.host(&stub.url);
+        let runtime = container::runtime(&host, RuntimeKind::Docker).await;
+
+        let plan = build_plan(&host, runtime.as_ref(), &paths, &hosts, "dolphin-llama3:8b").await;
+        assert!(plan.warnings.is_empty(), "{:?}", plan.warnings);
+        assert_eq!(
+            names(&plan, UninstallCategory::Containers),
+            ["dark-gpt-caddy", "dark-gpt-webui"]
+        );
+        assert_eq!(
+            names(&plan, UninstallCategory::Network),
+            ["dark-gpt_dark-gpt-network"]
+        );
+        assert_eq!(
+            names(&plan, UninstallCategory::Volumes),
+            ["docker_webui_data"]
+        );
+        assert_eq!(
+            names(&plan, UninstallCategory::Certs),
+            ["dark-gpt.local.pem"]
+        );
+        assert_eq!(names(&plan, UninstallCategory::HostsEntry).len(), 2);
+        assert_eq!(
+            names(&plan, UninstallCategory::Models),
+            ["dolphin-phi:latest"]
+        );
+        assert_eq!(
+            names(&plan, UninstallCategory::Settings),
+            ["settings.json", "setup_state.json", "benchmarks.jsonl"]
+        );
+        assert_eq!(
+            names(&plan, UninstallCategory::Logs),
+            ["dark-gpt.2026-10-19.log"]
+        );
+        assert_eq!(
+            names(&plan, UninstallCategory::AuditLog),
+            ["audit.genesis", "audit.head", "audit.jsonl"]
+        );
+
+        let settings = plan
+            .artifacts
+            .iter()
+            .find(|a| a.name.ends_with("settings.json"))
+            .unwrap();
+        assert_eq!(
+            settings.detail.as_deref(),
+            Some("2 profiles, 1 gateway API key")
+        );
+        let volumes = &plan.categories[2];
+        assert_eq!(volumes.category, UninstallCategory::Volumes);
+        assert_eq!(volumes.size_bytes, 1_500_000_000);
+        let audit = plan.categories.last().unwrap();
+        assert_eq!(audit.category, UninstallCategory::AuditLog);
+        assert_eq!((audit.count, audit.size_bytes), (3, 10));
+    }
+
+    #[tokio::test]
+    async fn plans_local_files_when_the_runtime_is_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = app_dirs(dir.path());
+        let fake = FakeRunner::new();
+        fake.fail("docker compose", "Cannot connect to the Docker daemon");
+        let host = fake.host("http://127.0.0.1:9");
+        let runtime = container::runtime(&host, RuntimeKind::Docker).await;
+
+        let hosts = dir.path().join("missing-hosts");
+        let plan = build_plan(&host, runtime.as_ref(), &paths, &hosts, "dolphin-phi").await;
+        assert_eq!(plan.warnings.len(), 2, "{:?}", plan.warnings);
+        assert!(names(&plan, UninstallCategory::Containers).is_empty());
+        assert_eq!(names(&plan, UninstallCategory::Settings).len(), 3);
+        assert_eq!(names(&plan, UninstallCategory::AuditLog).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn only_deletes_files_in_app_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = app_dirs(dir.path());
+        let host = FakeRunner::new().host("http://127.0.0.1:9");
+        let runtime = container::runtime(&host, RuntimeKind::Docker).await;
+        let hosts = dir.path().join("hosts");
+        let artifact = |category, path: &Path| Artifact {
+            category,
+            name: path.to_string_lossy().to_string(),
+            size_bytes: None,
+            detail: None,
+        };
+
+        let log = artifact(
+            UninstallCategory::Logs,
+            &paths.log_dir.join("dark-gpt.2026-10-19.log"),
+        );
+        remove(&host, runtime.as_ref(), &paths, &hosts, &log)
+            .await
+            .unwrap();
+        assert!(!Path::new(&log.name).exists());
+
+        let outside = artifact(UninstallCategory::Settings, &hosts);
+        std::fs::write(&hosts, HOSTS).unwrap();
+        let err = remove(&host, runtime.as_ref(), &paths, &hosts, &outside)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Not in an app directory");
+        assert!(hosts.exists());
+    }
+
+    #[test]
+    fn finds_the_hosts_lines_for_our_domain() {
+        let dir = tempfile::tempdir().unwrap();
+        let hosts = dir.path().join("hosts");
+        std::fs::write(&hosts, HOSTS).unwrap();
+        assert_eq!(
+            hosts_lines(&hosts, "dark-gpt.local"),
+            [
+                "127.0.0.1 dark-gpt.local",
+                "127.0.0.1\tdark-gpt.local www.dark-gpt.local"
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn rewrites_the_hosts_file_without_our_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let hosts = dir.path().join("hosts");
+        std::fs::write(&hosts, HOSTS).unwrap();
+        let fake = FakeRunner::new();
+        fake.on("pkexec sh -c", "");
+
+        remove_hosts_entry(&fake.host("http://127.0.0.1:9"), &hosts)
+            .await
+            .unwrap();
+        let calls = fake.calls_to("pkexec sh -c");
+        assert_eq!(calls.len(), 1);
+        let args = format!("sh dark-gpt.local {}", hosts.display());
+        assert!(calls[0].line.ends_with(&args), "{}", calls[0].line);
+
+        // What pkexec would have run, without the elevation
+        let status = std::process::Command::new("sh")
+            .args(["-c", HOSTS_REWRITE_SCRIPT, "sh", "dark-gpt.local"])
+            .arg(&hosts)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(std::fs::read_to_string(&hosts).unwrap(), HOSTS_CLEANED);
+        assert!(!dir.path().join("hosts.dark-gpt").exists());
+    }
+}
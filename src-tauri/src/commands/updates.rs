@@ -0,0 +1,52 @@
+// Update commands
+
+use tauri::{AppHandle, State};
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::settings::{AppSettings, SettingsStore};
+use crate::services::updates::{self, UpdateChecker, UpdateInfo, UpdateSettings};
+
+/// Check the release manifest now, regardless of the schedule
+#[tauri::command]
+pub async fn check_for_updates(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    checker: State<'_, UpdateChecker>,
+) -> AppResult<UpdateInfo> {
+    let info = updates::check(&store.get())
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Network, e).context("Checking for updates"))?;
+    checker.record(&app, info.clone());
+    Ok(info)
+}
+
+/// Result of the last check, scheduled or manual
+#[tauri::command]
+pub fn get_update_status(checker: State<'_, UpdateChecker>) -> Option<UpdateInfo> {
+    checker.last()
+}
+
+/// Stop notifying about `version`
+#[tauri::command]
+pub fn dismiss_update(
+    store: State<'_, SettingsStore>,
+    checker: State<'_, UpdateChecker>,
+    version: String,
+) -> AppResult<()> {
+    let settings = store.get();
+    let mut dismissed_versions = settings.updates.dismissed_versions.clone();
+    if !dismissed_versions.contains(&version) {
+        dismissed_versions.push(version.clone());
+    }
+    store
+        .save(AppSettings {
+            updates: UpdateSettings {
+                dismissed_versions,
+                ..settings.updates.clone()
+            },
+            ..settings
+        })?;
+    checker.dismiss(&version);
+    tracing::info!("Dismissed update {}", version);
+    Ok(())
+}
@@ -0,0 +1,255 @@
+// Error type shared by all commands
+// Serialized to the frontend as `{ code, message, context, remediation,
+// cause, details }`; the TypeScript side is generated (see the test below)
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::commands::ports::PortConflict;
+use crate::services::settings::SettingsError;
+
+/// Stable, machine-readable error codes. Never rename one: the frontend
+/// matches on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum ErrorCode {
+    /// The container runtime CLI (Docker or Podman) is missing
+    DockerNotInstalled,
+    /// The container engine is installed but not answering
+    DaemonDown,
+    /// A compose command ran and failed
+    ComposeFailed,
+    /// A port the stack publishes is held by something else
+    PortInUse,
+    /// Nothing answers on the Ollama API
+    OllamaUnreachable,
+    /// The requested model isn't installed or doesn't exist
+    ModelNotFound,
+    /// An operation didn't finish in time
+    Timeout,
+    /// Filesystem or process I/O
+    Io,
+    /// An HTTP request failed (downloads, update checks)
+    Network,
+    /// A downloaded file failed its checksum or signature check
+    IntegrityCheckFailed,
+    /// A value from the user or a setting was rejected
+    InvalidInput,
+    /// Administrator authorization was refused or cancelled
+    PermissionDenied,
+    /// Another operation is already running
+    Busy,
+    /// Stopped because the app is closing
+    Cancelled,
+    /// Anything not classified above
+    Internal,
+}
+
+impl ErrorCode {
+    /// Exit status for the CLI, grouped by category. Stable like the codes
+    /// themselves; 2 is also what clap uses for usage errors.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCode::Internal => 1,
+            ErrorCode::InvalidInput => 2,
+            ErrorCode::DockerNotInstalled => 10,
+            ErrorCode::DaemonDown => 11,
+            ErrorCode::ComposeFailed => 12,
+            ErrorCode::PortInUse => 13,
+            ErrorCode::OllamaUnreachable => 20,
+            ErrorCode::ModelNotFound => 21,
+            ErrorCode::Network => 30,
+            ErrorCode::IntegrityCheckFailed => 31,
+            ErrorCode::Timeout => 40,
+            ErrorCode::Io => 41,
+            ErrorCode::PermissionDenied => 42,
+            ErrorCode::Busy => 43,
+            ErrorCode::Cancelled => 44,
+        }
+    }
+}
+
+/// Extra data for codes that carry more than a message
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ErrorDetails {
+    /// With `PortInUse`: each conflict and how to fix it
+    PortConflicts { conflicts: Vec<PortConflict> },
+    /// With `InvalidInput`: dotted path of the rejected field
+    Field { field: String },
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, thiserror::Error)]
+#[error("{message}")]
+pub struct AppError {
+    pub code: ErrorCode,
+    /// What went wrong, in one sentence
+    pub message: String,
+    /// What the app was doing, e.g. "starting services"
+    pub context: Option<String>,
+    /// What the user can do about it
+    pub remediation: Option<String>,
+    /// Underlying error (stderr, OS error)
+    pub cause: Option<String>,
+    // Boxed to keep `AppResult` small
+    pub details: Option<Box<ErrorDetails>>,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            context: None,
+            remediation: None,
+            cause: None,
+            details: None,
+        }
+    }
+
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    pub fn remediation(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+
+    pub fn cause(mut self, cause: impl ToString) -> Self {
+        let cause = cause.to_string();
+        self.cause = Some(cause.trim().to_string()).filter(|c| !c.is_empty());
+        self
+    }
+
+    pub fn details(mut self, details: ErrorDetails) -> Self {
+        self.details = Some(Box::new(details));
+        self
+    }
+
+    pub fn timeout(what: &str, secs: u64) -> Self {
+        Self::new(
+            ErrorCode::Timeout,
+            format!("Timed out {} ({}s)", what, secs),
+        )
+    }
+
+    pub fn ollama_unreachable(cause: impl ToString) -> Self {
+        Self::new(ErrorCode::OllamaUnreachable, "Ollama is not reachable")
+            .cause(cause)
+            .remediation("Start Ollama (`ollama serve`) and try again.")
+    }
+
+    /// A container runtime command that couldn't be spawned
+    pub fn runtime_spawn(runtime: &str, e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            Self::new(
+                ErrorCode::DockerNotInstalled,
+                format!("{} is not installed", runtime),
+            )
+            .remediation(format!("Install {} from the setup wizard.", runtime))
+        } else {
+            Self::new(ErrorCode::Io, format!("Failed to run {}", runtime)).cause(e)
+        }
+    }
+
+    /// A compose command that exited non-zero, told apart from an engine
+    /// that isn't running by its stderr
+    pub fn compose_failed(runtime: &str, stderr: &str) -> Self {
+        let lower = stderr.to_lowercase();
+        let daemon_down = [
+            "cannot connect to the docker daemon",
+            "is the docker daemon running",
+            "error during connect",
+            "cannot connect to podman",
+        ]
+        .iter()
+        .any(|pattern| lower.contains(pattern));
+
+        if daemon_down {
+            Self::new(
+                ErrorCode::DaemonDown,
+                format!("{} engine is not running", runtime),
+            )
+            .remediation(format!("Start {}, then try again.", runtime))
+            .cause(stderr)
+        } else {
+            Self::new(
+                ErrorCode::ComposeFailed,
+                format!("{} compose failed", runtime),
+            )
+            .cause(stderr)
+        }
+    }
+
+    /// Message with context, cause and remediation, for logs and
+    /// plain-text surfaces
+    pub fn describe(&self) -> String {
+        let mut text = match &self.context {
+            Some(context) => format!("{}: {}", context, self.message),
+            None => self.message.clone(),
+        };
+        if let Some(cause) = &self.cause {
+            text.push_str(&format!(" ({})", cause));
+        }
+        if let Some(remediation) = &self.remediation {
+            text.push('\n');
+            text.push_str(remediation);
+        }
+        text
+    }
+}
+
+/// Internal helpers still report plain strings; they surface as `Internal`
+/// unless the command classifies them
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new(ErrorCode::Io, e.to_string())
+    }
+}
+
+impl From<SettingsError> for AppError {
+    fn from(e: SettingsError) -> Self {
+        match e {
+            SettingsError::Invalid { field, message } => Self::new(
+                ErrorCode::InvalidInput,
+                format!("Invalid setting `{}`: {}", field, message),
+            )
+            .details(ErrorDetails::Field { field }),
+            SettingsError::Storage { message } => Self::new(ErrorCode::Io, message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ts_bindings;
+
+    const BINDINGS: &str = "../src/lib/bindings/AppError.ts";
+
+    /// Regenerate with `UPDATE_BINDINGS=1 cargo test`
+    #[test]
+    fn typescript_bindings_are_current() {
+        let generated = ts_bindings::render(&schemars::schema_for!(AppError));
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(BINDINGS);
+        if std::env::var_os("UPDATE_BINDINGS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &generated).unwrap();
+        }
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            current == generated,
+            "{} is out of date; run `UPDATE_BINDINGS=1 cargo test`",
+            BINDINGS
+        );
+    }
+}
@@ -1,50 +1,144 @@
 // Dark-GPT Library
 // Core functionality for the Tauri application
 
+pub mod cli;
 mod commands;
+mod error;
 mod services;
 mod utils;
 
+use std::path::PathBuf;
+
 use tauri::Manager;
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+use services::instance::Startup;
+use utils::log_buffer::RecentLogs;
+use utils::paths::AppPaths;
+
+/// Log lines kept in memory for diagnostic bundles
+const RECENT_LOG_CAPACITY: usize = 2000;
 
 pub fn run() {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(fmt::layer())
-        .with(EnvFilter::from_default_env().add_directive("dark_gpt=debug".parse().unwrap()))
-        .init();
+    let context = context();
+    // Initialize logging (stdout, in-memory buffer for diagnostics, log files)
+    let recent_logs = RecentLogs::new(RECENT_LOG_CAPACITY);
+    let log_dir = AppPaths::headless(&context).ok().map(|paths| paths.log_dir);
+    services::logging::init(log_dir.as_deref(), recent_logs.clone());
+
+    // Two instances would run compose against the same project at once
+    let instance = match services::instance::acquire(&context.config().identifier) {
+        Ok(Startup::Primary(instance)) => Some(instance),
+        Ok(Startup::Forwarded) => {
+            tracing::info!("Dark-GPT is already running, handed over to it");
+            return;
+        }
+        Ok(Startup::Unresponsive(e)) => {
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            tracing::warn!("Single-instance check failed, starting anyway: {}", e);
+            None
+        }
+    };
 
     tracing::info!("Starting Dark-GPT Desktop v0.6.2");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .manage(recent_logs)
+        .manage(services::host::Host::system())
+        .manage(services::metrics::Metrics::default())
+        .manage(services::metrics::MetricsServer::default())
+        .manage(services::gateway::GatewayServer::default())
+        .manage(services::auto_heal::AutoHealer::default())
+        .manage(services::updates::UpdateChecker::default())
         .invoke_handler(tauri::generate_handler![
             commands::docker::check_docker,
             commands::docker::start_services,
             commands::docker::stop_services,
             commands::docker::get_service_logs,
+            commands::diagnostics::export_diagnostics,
+            commands::audit::verify_audit_log,
+            commands::audit::export_audit_log,
+            commands::backup::create_backup,
+            commands::benchmark::benchmark_model,
+            commands::benchmark::get_benchmark_history,
             commands::ollama::check_ollama,
             commands::ollama::list_models,
             commands::ollama::pull_model,
             commands::ollama::get_model_info,
+            commands::ollama::chat,
+            commands::gpu::detect_gpu,
             commands::health::check_all_services,
             commands::health::get_webui_url,
+            commands::health::get_auto_heal_policy,
+            commands::health::set_auto_heal_policy,
+            commands::ports::check_port_conflicts,
             commands::installer::install_ollama,
             commands::installer::install_docker,
+            commands::logs::read_logs,
+            commands::logs::get_log_level,
+            commands::logs::set_log_level,
+            commands::metrics::start_metrics_server,
+            commands::metrics::stop_metrics_server,
+            commands::metrics::get_metrics_status,
+            commands::gateway::start_gateway,
+            commands::gateway::stop_gateway,
+            commands::gateway::get_gateway_status,
+            commands::gateway::list_api_keys,
+            commands::gateway::create_api_key,
+            commands::gateway::revoke_api_key,
+            commands::settings::get_settings,
+            commands::settings::update_settings,
+            commands::profiles::list_profiles,
+            commands::profiles::create_profile,
+            commands::profiles::clone_profile,
+            commands::profiles::switch_profile,
+            commands::profiles::delete_profile,
             commands::setup::detect_prerequisites,
             commands::setup::get_setup_state,
+            commands::setup::advance,
+            commands::setup::retry_step,
+            commands::setup::reset_setup,
             commands::setup::save_settings,
             commands::setup::get_available_models,
+            commands::uninstall::plan_uninstall,
+            commands::uninstall::uninstall,
+            commands::updates::check_for_updates,
+            commands::updates::get_update_status,
+            commands::updates::dismiss_update,
         ])
-        .setup(|app| {
+        .setup(move |app| {
             // Log app data directory
             if let Ok(data_dir) = app.path().app_data_dir() {
                 tracing::info!("App data directory: {:?}", data_dir);
             }
+
+            if let Some(instance) = instance {
+                instance.listen(app.handle());
+            }
+
+            let paths = AppPaths::from_app(app.handle())?;
+            services::audit::init(
+                &paths.data_dir,
+                &paths.config_dir,
+                services::audit::Source::App,
+            );
+            app.state::<services::host::Host>()
+                .set_certs_dir(paths.certs_dir());
+            app.manage(services::settings::SettingsStore::load(&paths.config_dir));
+            app.manage(services::setup_wizard::SetupWizard::load(&paths.config_dir));
+            // Some Linux desktops have no tray; the app works without it
+            if let Err(e) = services::tray::init(app.handle()) {
+                tracing::warn!("System tray unavailable: {}", e);
+            }
+            tauri::async_runtime::spawn(commands::settings::apply_on_startup(
+                app.handle().clone(),
+            ));
             Ok(())
         })
-        .run(tauri::generate_context!())
+        .build(context)
         .unwrap_or_else(|e| {
             panic!(
                 "Failed to start Dark-GPT: {}\n\n\
@@ -52,5 +146,17 @@ pub fn run() {
                  Install it from: https://developer.microsoft.com/en-us/microsoft-edge/webview2/",
                 e
             );
-        });
+        })
+        .run(services::lifecycle::on_run_event);
+}
+
+/// Where log files and the crash log go, if this user has one
+pub fn log_dir() -> Option<PathBuf> {
+    AppPaths::headless(&context()).ok().map(|paths| paths.log_dir)
+}
+
+/// App config and assets, shared by the desktop app and the CLI (which
+/// only needs it to resolve the same directories)
+pub(crate) fn context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
 }
@@ -6,22 +6,37 @@
     windows_subsystem = "windows"
 )]
 
+use std::path::PathBuf;
+
+const CRASH_LOG: &str = "dark-gpt-crash.log";
+
 fn main() {
-    setup_panic_handler();
+    setup_panic_handler(dark_gpt_lib::log_dir());
+    // `dark-gpt <subcommand>` runs headless; no arguments opens the app
+    if let Some(code) = dark_gpt_lib::cli::run() {
+        std::process::exit(code);
+    }
     dark_gpt_lib::run();
 }
 
 /// Install a panic hook that writes a crash log and shows a native
 /// error dialog on Windows. Without this, `windows_subsystem = "windows"`
 /// + `panic = "abort"` causes silent death with zero user feedback.
-fn setup_panic_handler() {
-    std::panic::set_hook(Box::new(|info| {
+fn setup_panic_handler(log_dir: Option<PathBuf>) {
+    std::panic::set_hook(Box::new(move |info| {
         let message = format!("Dark-GPT crashed unexpectedly:\n\n{}", info);
+        tracing::error!("{}", message);
 
-        // Write crash log next to executable
-        if let Ok(exe) = std::env::current_exe() {
-            let log_path = exe.with_file_name("dark-gpt-crash.log");
-            let _ = std::fs::write(&log_path, &message);
+        // Crash log with the other logs; next to the executable if there's
+        // no log dir or it isn't writable
+        let written = log_dir.as_ref().is_some_and(|dir| {
+            std::fs::create_dir_all(dir).is_ok()
+                && std::fs::write(dir.join(CRASH_LOG), &message).is_ok()
+        });
+        if !written {
+            if let Ok(exe) = std::env::current_exe() {
+                let _ = std::fs::write(exe.with_file_name(CRASH_LOG), &message);
+            }
         }
 
         // Show native error dialog on Windows
@@ -0,0 +1,535 @@
+// Audit log
+// Append-only JSONL record of state-changing commands (who, from where,
+// what, when, and whether it worked). Each entry holds the hash of the one
+// before it, and a separate head file the hash of the last one, so edits,
+// removed entries and truncation all show up in `verify`. The first entry's
+// hash is also kept with the settings, so deleting or regenerating the whole
+// log shows up too. The desktop app and the CLI append to the same file,
+// taking turns through a lock file.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppResult;
+use crate::utils::fs;
+use crate::utils::time::unix_now;
+
+const LOG_FILE: &str = "audit.jsonl";
+/// Sequence number and hash of the last entry
+const HEAD_FILE: &str = "audit.head";
+/// Held while appending
+const LOCK_FILE: &str = "audit.lock";
+/// Time and hash of the first entry, outside the data dir
+const GENESIS_FILE: &str = "audit.genesis";
+/// Everything the log keeps on disk, for the uninstaller
+pub const FILES: [&str; 4] = [LOG_FILE, HEAD_FILE, LOCK_FILE, GENESIS_FILE];
+/// `prev_hash` of the first entry
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    App,
+    Cli,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, from 0
+    pub seq: u64,
+    pub time: u64,
+    /// OS account that ran the app or CLI
+    pub actor: String,
+    pub source: Source,
+    /// Dotted name, e.g. `services.start` or `gateway.key.revoke`
+    pub action: String,
+    /// Arguments worth keeping; never secrets
+    pub details: serde_json::Value,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub prev_hash: String,
+    /// SHA-256 of this entry with `hash` empty, hex
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn digest(&self) -> String {
+        let unhashed = AuditEntry {
+            hash: String::new(),
+            ..self.clone()
+        };
+        let json = serde_json::to_vec(&unhashed).unwrap_or_default();
+        hex::encode(Sha256::digest(&json))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Head {
+    seq: u64,
+    hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Genesis {
+    time: u64,
+    hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    pub valid: bool,
+    /// Entries read, up to the first problem
+    pub entries: u64,
+    pub problem: Option<Problem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    /// 1-based line of the log; none for problems with the log as a whole
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+    head_path: PathBuf,
+    lock_path: PathBuf,
+    genesis_path: PathBuf,
+}
+
+impl AuditLog {
+    /// The log in `dir`, its genesis record in `genesis_dir`
+    pub fn new(dir: &Path, genesis_dir: &Path) -> Self {
+        Self {
+            path: dir.join(LOG_FILE),
+            head_path: dir.join(HEAD_FILE),
+            lock_path: dir.join(LOCK_FILE),
+            genesis_path: genesis_dir.join(GENESIS_FILE),
+        }
+    }
+
+    /// Append an entry chained to the current head. The head is re-read
+    /// under an exclusive lock, since another process may have appended.
+    pub fn append(
+        &self,
+        source: Source,
+        action: &str,
+        details: serde_json::Value,
+        error: Option<String>,
+    ) -> Result<AuditEntry, String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create audit log dir: {}", e))?;
+        }
+        // Released when dropped
+        let lock = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.lock_path)
+            .map_err(|e| format!("Failed to open audit lock: {}", e))?;
+        lock.lock()
+            .map_err(|e| format!("Failed to lock audit log: {}", e))?;
+
+        let head = self.head()?;
+        let mut entry = AuditEntry {
+            seq: head.as_ref().map_or(0, |h| h.seq + 1),
+            time: unix_now(),
+            actor: actor(),
+            source,
+            action: action.to_string(),
+            details,
+            ok: error.is_none(),
+            error,
+            prev_hash: head.map_or(GENESIS.to_string(), |h| h.hash),
+            hash: String::new(),
+        };
+        entry.hash = entry.digest();
+
+        let mut line = serde_json::to_string(&entry)
+            .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        line.push('\n');
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|e| format!("Failed to write audit log: {}", e))?;
+
+        let head = Head {
+            seq: entry.seq,
+            hash: entry.hash.clone(),
+        };
+        let head = serde_json::to_vec(&head).unwrap_or_default();
+        fs::write_atomic(&self.head_path, &head)
+            .map_err(|e| format!("Failed to write audit head: {}", e))?;
+
+        if entry.seq == 0 {
+            let genesis = Genesis {
+                time: entry.time,
+                hash: entry.hash.clone(),
+            };
+            let genesis = serde_json::to_vec(&genesis).unwrap_or_default();
+            if let Some(dir) = self.genesis_path.parent() {
+                std::fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create audit genesis dir: {}", e))?;
+            }
+            fs::write_atomic(&self.genesis_path, &genesis)
+                .map_err(|e| format!("Failed to write audit genesis: {}", e))?;
+        }
+        Ok(entry)
+    }
+
+    fn genesis(&self) -> Result<Option<Genesis>, String> {
+        match std::fs::read(&self.genesis_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| format!("Audit genesis record is corrupt: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read audit genesis record: {}", e)),
+        }
+    }
+
+    fn head(&self) -> Result<Option<Head>, String> {
+        match std::fs::read(&self.head_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| format!("Audit head is corrupt: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read audit head: {}", e)),
+        }
+    }
+
+    /// Walk the chain and compare its end with the head
+    pub fn verify(&self) -> Result<Verification, String> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read audit log: {}", e)),
+        };
+        let broken = |entries, line, message: String| Verification {
+            valid: false,
+            entries,
+            problem: Some(Problem { line, message }),
+        };
+
+        let genesis = match self.genesis() {
+            Ok(genesis) => genesis,
+            Err(e) => return Ok(broken(0, None, e)),
+        };
+        let mut last: Option<Head> = None;
+        let mut entries = 0;
+        for (i, line) in text.split_inclusive('\n').enumerate() {
+            let number = Some(i as u64 + 1);
+            if !line.ends_with('\n') {
+                return Ok(broken(entries, number, "incomplete last line".into()));
+            }
+            let entry: AuditEntry = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                Err(e) => return Ok(broken(entries, number, format!("unreadable entry: {}", e))),
+            };
+            let expected_prev = last.as_ref().map_or(GENESIS, |h| h.hash.as_str());
+            if entry.seq != i as u64 || entry.prev_hash != expected_prev {
+                return Ok(broken(
+                    entries,
+                    number,
+                    "entries before this one were removed or reordered".into(),
+                ));
+            }
+            if entry.digest() != entry.hash {
+                return Ok(broken(entries, number, "entry was modified".into()));
+            }
+            if entry.seq == 0 {
+                match &genesis {
+                    None => {
+                        return Ok(broken(
+                            entries,
+                            None,
+                            "the genesis record is missing".into(),
+                        ))
+                    }
+                    Some(genesis) if genesis.hash != entry.hash => {
+                        return Ok(broken(
+                            entries,
+                            number,
+                            "the log was replaced since it was started".into(),
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+            entries += 1;
+            last = Some(Head {
+                seq: entry.seq,
+                hash: entry.hash,
+            });
+        }
+
+        let head = match self.head() {
+            Ok(head) => head,
+            Err(e) => return Ok(broken(entries, None, e)),
+        };
+        match (head, last) {
+            (None, None) if genesis.is_some() => {
+                return Ok(broken(
+                    entries,
+                    None,
+                    "the log and its head were deleted".into(),
+                ));
+            }
+            (None, None) => {}
+            (None, Some(_)) => {
+                return Ok(broken(entries, None, "the head file is missing".into()));
+            }
+            (Some(head), last) if Some(&head) != last.as_ref() => {
+                let message = match last {
+                    Some(last) if last.seq > head.seq => {
+                        "the log continues past its recorded head".to_string()
+                    }
+                    Some(last) if last.seq == head.seq => "the last entry was replaced".to_string(),
+                    last => format!(
+                        "the log was truncated: {} entries missing at the end",
+                        head.seq - last.map_or(0, |l| l.seq + 1) + 1
+                    ),
+                };
+                return Ok(broken(entries, None, message));
+            }
+            _ => {}
+        }
+        Ok(Verification {
+            valid: true,
+            entries,
+            problem: None,
+        })
+    }
+
+    /// Copy the log and its head to `dest` (the head as `<dest>.head`),
+    /// along with a verification of what was copied
+    pub fn export(&self, dest: &Path) -> Result<Verification, String> {
+        let verification = self.verify()?;
+        if let Some(dir) = dest.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create destination dir: {}", e))?;
+        }
+        let log = std::fs::read(&self.path).unwrap_or_default();
+        std::fs::write(dest, log).map_err(|e| format!("Failed to export audit log: {}", e))?;
+        if let Ok(head) = std::fs::read(&self.head_path) {
+            let mut head_dest = dest.as_os_str().to_os_string();
+            head_dest.push(".head");
+            std::fs::write(head_dest, head)
+                .map_err(|e| format!("Failed to export audit head: {}", e))?;
+        }
+        Ok(verification)
+    }
+}
+
+static LOG: Mutex<Option<(AuditLog, Source)>> = Mutex::new(None);
+
+/// Record to `dir` from now on, as `source`, with the genesis record in
+/// `genesis_dir` (the config dir)
+pub fn init(dir: &Path, genesis_dir: &Path, source: Source) {
+    if let Ok(mut log) = LOG.lock() {
+        *log = Some((AuditLog::new(dir, genesis_dir), source));
+    }
+}
+
+/// Run `f` on the log, if `init` was called
+pub fn with_log<T>(f: impl FnOnce(&AuditLog) -> Result<T, String>) -> Result<T, String> {
+    let log = LOG.lock().map_err(|_| "Audit log unavailable")?;
+    match log.as_ref() {
+        Some((log, _)) => f(log),
+        None => Err("Audit log not initialized".to_string()),
+    }
+}
+
+/// Record the outcome of a command and pass it through. Failing to write
+/// the entry is logged, not returned: the action itself already happened.
+pub fn recorded<T>(action: &str, details: serde_json::Value, result: AppResult<T>) -> AppResult<T> {
+    let error = result.as_ref().err().map(|e| e.describe());
+    if let Ok(log) = LOG.lock() {
+        if let Some((log, source)) = log.as_ref() {
+            if let Err(e) = log.append(*source, action, details, error) {
+                tracing::error!("Audit log: {} ({})", e, action);
+            }
+        }
+    }
+    result
+}
+
+/// The OS account name
+fn actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_with(entries: u64) -> (tempfile::TempDir, AuditLog) {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(&dir.path().join("data"), &dir.path().join("config"));
+        for i in 0..entries {
+            let error = (i == 1).then(|| "port 443 in use".to_string());
+            log.append(
+                Source::App,
+                "services.start",
+                serde_json::json!({ "try": i }),
+                error,
+            )
+            .unwrap();
+        }
+        (dir, log)
+    }
+
+    fn lines(log: &AuditLog) -> Vec<String> {
+        std::fs::read_to_string(&log.path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn rewrite(log: &AuditLog, lines: &[String]) {
+        let text: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+        std::fs::write(&log.path, text).unwrap();
+    }
+
+    fn problem(log: &AuditLog) -> (Option<u64>, String) {
+        let verification = log.verify().unwrap();
+        assert!(!verification.valid);
+        let problem = verification.problem.unwrap();
+        (problem.line, problem.message)
+    }
+
+    #[test]
+    fn chains_entries_and_verifies() {
+        let (_dir, log) = log_with(3);
+        let verification = log.verify().unwrap();
+        assert!(verification.valid, "{:?}", verification.problem);
+        assert_eq!(verification.entries, 3);
+
+        let entries: Vec<AuditEntry> = lines(&log)
+            .iter()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(entries[0].prev_hash, GENESIS);
+        assert_eq!(entries[2].prev_hash, entries[1].hash);
+        assert!(!entries[1].ok);
+
+        let (_empty_dir, empty) = log_with(0);
+        assert!(empty.verify().unwrap().valid);
+    }
+
+    #[test]
+    fn detects_edits_and_removed_entries() {
+        let (_dir, log) = log_with(3);
+        let original = lines(&log);
+
+        let mut edited = original.clone();
+        edited[1] = edited[1].replace(r#""ok":false"#, r#""ok":true"#);
+        rewrite(&log, &edited);
+        assert_eq!(problem(&log), (Some(2), "entry was modified".to_string()));
+
+        rewrite(&log, &[original[0].clone(), original[2].clone()]);
+        assert_eq!(problem(&log).0, Some(2));
+
+        rewrite(&log, &original[1..]);
+        assert_eq!(problem(&log).0, Some(1));
+    }
+
+    #[test]
+    fn detects_truncation() {
+        let (_dir, log) = log_with(3);
+        let original = lines(&log);
+
+        rewrite(&log, &original[..1]);
+        let (line, message) = problem(&log);
+        assert_eq!(line, None);
+        assert!(message.contains("2 entries missing"), "{}", message);
+
+        std::fs::remove_file(&log.path).unwrap();
+        assert!(problem(&log).1.contains("3 entries missing"));
+
+        rewrite(&log, &original);
+        std::fs::remove_file(&log.head_path).unwrap();
+        assert_eq!(problem(&log).1, "the head file is missing");
+    }
+
+    #[test]
+    fn detects_a_deleted_or_regenerated_log() {
+        let (_dir, log) = log_with(2);
+        std::fs::remove_file(&log.path).unwrap();
+        std::fs::remove_file(&log.head_path).unwrap();
+        assert_eq!(problem(&log).1, "the log and its head were deleted");
+
+        // A fresh chain would verify on its own, but not against the genesis
+        let genesis = std::fs::read(&log.genesis_path).unwrap();
+        log.append(Source::Cli, "services.stop", serde_json::json!({}), None)
+            .unwrap();
+        std::fs::write(&log.genesis_path, genesis).unwrap();
+        assert_eq!(
+            problem(&log),
+            (
+                Some(1),
+                "the log was replaced since it was started".to_string()
+            )
+        );
+
+        std::fs::remove_file(&log.genesis_path).unwrap();
+        assert_eq!(problem(&log).1, "the genesis record is missing");
+    }
+
+    #[test]
+    fn concurrent_appends_keep_the_chain() {
+        let (_dir, log) = log_with(0);
+        let log = std::sync::Arc::new(log);
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let log = log.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        // A second handle, like another process
+                        AuditLog::new(
+                            log.path.parent().unwrap(),
+                            log.genesis_path.parent().unwrap(),
+                        )
+                        .append(Source::Cli, "models.pull", serde_json::json!({}), None)
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let verification = log.verify().unwrap();
+        assert!(verification.valid, "{:?}", verification.problem);
+        assert_eq!(verification.entries, 40);
+    }
+
+    #[test]
+    fn exports_log_and_head() {
+        let (dir, log) = log_with(2);
+        let dest = dir.path().join("export/audit.jsonl");
+        assert!(log.export(&dest).unwrap().valid);
+        assert!(
+            AuditLog {
+                path: dest.clone(),
+                head_path: dir.path().join("export/audit.jsonl.head"),
+                lock_path: dir.path().join("export/audit.lock"),
+                genesis_path: log.genesis_path.clone(),
+            }
+            .verify()
+            .unwrap()
+            .valid
+        );
+    }
+}
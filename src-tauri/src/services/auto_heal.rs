@@ -0,0 +1,539 @@
+// Auto-heal monitor
+// Opt-in background loop that restarts Ollama / Open-WebUI after repeated
+// failed health checks, with exponential backoff and an attempt cap
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+use crate::commands::docker;
+use crate::commands::health::{self, HealthStatus, ServiceHealth};
+use crate::commands::ollama::backend::BackendKind;
+use crate::commands::ports::{self, PortHolder};
+use super::host::Host;
+use super::{container, lifecycle};
+
+const OLLAMA_PORT: u16 = 11434;
+const RESTART_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long a relaunched Ollama gets to answer
+const OLLAMA_START_TIMEOUT: Duration = Duration::from_secs(20);
+/// Installed by the Linux installer
+const OLLAMA_UNIT: &str = "ollama.service";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoHealPolicy {
+    pub enabled: bool,
+    /// Consecutive failed checks before acting
+    pub failure_threshold: u32,
+    pub check_interval_secs: u64,
+    /// Wait after the first restart; doubled after each further attempt
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    /// Restarts per outage before giving up
+    pub max_attempts: u32,
+}
+
+impl Default for AutoHealPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            failure_threshold: 3,
+            check_interval_secs: 30,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 300,
+            max_attempts: 5,
+        }
+    }
+}
+
+impl AutoHealPolicy {
+    /// Check the policy; on error returns the offending field and why
+    pub fn validate(&self) -> Result<(), (&'static str, &'static str)> {
+        if self.failure_threshold == 0 {
+            return Err(("failure_threshold", "must be at least 1"));
+        }
+        if self.check_interval_secs < 5 {
+            return Err(("check_interval_secs", "must be at least 5"));
+        }
+        if self.max_attempts == 0 {
+            return Err(("max_attempts", "must be at least 1"));
+        }
+        if self.initial_backoff_secs > self.max_backoff_secs {
+            return Err(("initial_backoff_secs", "must not exceed max_backoff_secs"));
+        }
+        Ok(())
+    }
+
+    /// Backoff after the given (1-based) attempt
+    fn backoff(&self, attempt: u32) -> Duration {
+        let secs = self
+            .initial_backoff_secs
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(32));
+        Duration::from_secs(secs.min(self.max_backoff_secs))
+    }
+}
+
+/// Emitted as `auto-heal` for every action taken, for auditing
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoHealEvent {
+    pub service: String,
+    /// restart_container | relaunch_ollama | gave_up | recovered
+    pub action: String,
+    pub attempt: u32,
+    pub success: bool,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+/// Policy + monitor task. Held in Tauri state.
+#[derive(Default)]
+pub struct AutoHealer {
+    policy: Mutex<AutoHealPolicy>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AutoHealer {
+    pub fn policy(&self) -> AutoHealPolicy {
+        self.policy.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    /// Replace the policy and (re)start or stop the monitor accordingly
+    pub fn apply(&self, app: &AppHandle, policy: AutoHealPolicy) -> Result<(), String> {
+        policy
+            .validate()
+            .map_err(|(field, message)| format!("{} {}", field, message))?;
+
+        if let Some(task) = self.task.lock().ok().and_then(|mut t| t.take()) {
+            task.abort();
+        }
+        if let Ok(mut current) = self.policy.lock() {
+            *current = policy.clone();
+        }
+
+        if policy.enabled {
+            tracing::info!("Auto-heal enabled: {:?}", policy);
+            let handle = tauri::async_runtime::spawn(monitor(app.clone(), policy));
+            if let Ok(mut task) = self.task.lock() {
+                *task = Some(handle);
+            }
+        } else {
+            tracing::info!("Auto-heal disabled");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Target {
+    Ollama,
+    WebUi,
+}
+
+impl Target {
+    fn name(self) -> &'static str {
+        match self {
+            Target::Ollama => "ollama",
+            Target::WebUi => "webui",
+        }
+    }
+}
+
+/// Per-service outage bookkeeping
+#[derive(Default)]
+struct Tracker {
+    consecutive_failures: u32,
+    attempts: u32,
+    next_attempt_at: Option<Instant>,
+    gave_up: bool,
+}
+
+async fn monitor(app: AppHandle, policy: AutoHealPolicy) {
+    let mut trackers = [
+        (Target::Ollama, Tracker::default()),
+        (Target::WebUi, Tracker::default()),
+    ];
+
+    let host = app.state::<Host>().inner().clone();
+    loop {
+        sleep(Duration::from_secs(policy.check_interval_secs)).await;
+
+        let Ok((report, _)) = health::collect_health(&host).await else {
+            continue;
+        };
+        // Only heal the webui when the stack is deployed: a stopped stack is
+        // intentional, and restarts can't work without the daemon anyway
+        let webui = format!("{}-webui", host.profile().project);
+        let webui_deployed =
+            report.docker.status == HealthStatus::Healthy && container_exists(&host, &webui).await;
+
+        for (target, tracker) in trackers.iter_mut() {
+            let health: &ServiceHealth = match target {
+                Target::Ollama => &report.ollama,
+                Target::WebUi => &report.webui,
+            };
+
+            if health.status == HealthStatus::Healthy {
+                if tracker.attempts > 0 {
+                    emit(
+                        &app,
+                        *target,
+                        "recovered",
+                        tracker.attempts,
+                        true,
+                        "Service is healthy again",
+                    );
+                }
+                *tracker = Tracker::default();
+                continue;
+            }
+            if *target == Target::WebUi && !webui_deployed {
+                continue;
+            }
+            // Only Ollama is ours to relaunch; other model servers are the user's
+            if *target == Target::Ollama && host.profile().backend != BackendKind::Ollama {
+                continue;
+            }
+
+            tracker.consecutive_failures += 1;
+            tracing::debug!(
+                "{} unhealthy ({} consecutive)",
+                target.name(),
+                tracker.consecutive_failures
+            );
+
+            if tracker.consecutive_failures < policy.failure_threshold
+                || tracker
+                    .next_attempt_at
+                    .is_some_and(|at| Instant::now() < at)
+            {
+                continue;
+            }
+
+            if tracker.attempts >= policy.max_attempts {
+                if !tracker.gave_up {
+                    tracker.gave_up = true;
+                    emit(
+                        &app,
+                        *target,
+                        "gave_up",
+                        tracker.attempts,
+                        false,
+                        &format!(
+                            "Still unhealthy after {} restart attempts, manual action needed",
+                            tracker.attempts
+                        ),
+                    );
+                }
+                continue;
+            }
+
+            tracker.attempts += 1;
+            let (action, result) = remediate(&app, &host, *target).await;
+            let (success, message) = match result {
+                Ok(message) => (true, message),
+                Err(message) => (false, message),
+            };
+            emit(&app, *target, action, tracker.attempts, success, &message);
+
+            tracker.next_attempt_at = Some(Instant::now() + policy.backoff(tracker.attempts));
+        }
+    }
+}
+
+/// Restart the service; returns the action taken and its outcome
+async fn remediate(
+    app: &AppHandle,
+    host: &Host,
+    target: Target,
+) -> (&'static str, Result<String, String>) {
+    match target {
+        Target::WebUi => ("restart_container", restart_webui(app, host).await),
+        Target::Ollama => match ports::port_holder(host, OLLAMA_PORT).await {
+            Some(PortHolder::Container { name }) => {
+                ("restart_container", restart_container(host, &name).await)
+            }
+            holder => ("relaunch_ollama", relaunch_ollama(host, holder).await),
+        },
+    }
+}
+
+async fn container_exists(host: &Host, name: &str) -> bool {
+    timeout(
+        Duration::from_secs(5),
+        container::current(host)
+            .await
+            .command()
+            .args(["container", "inspect", name])
+            .output(),
+    )
+    .await
+    .map(|r| r.map(|o| o.status.success()).unwrap_or(false))
+    .unwrap_or(false)
+}
+
+async fn restart_webui(app: &AppHandle, host: &Host) -> Result<String, String> {
+    let compose_file = docker::compose_file_path(app)?;
+    let output = timeout(
+        RESTART_TIMEOUT,
+        container::current(host)
+            .await
+            .compose(&compose_file)
+            .args(["restart", "webui"])
+            .output(),
+    )
+    .await
+    .map_err(|_| "Timed out restarting webui".to_string())?
+    .map_err(|e| format!("Failed to restart webui: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("compose restart failed: {}", stderr.trim()));
+    }
+    Ok("Restarted webui container".to_string())
+}
+
+async fn restart_container(host: &Host, name: &str) -> Result<String, String> {
+    let output = timeout(
+        RESTART_TIMEOUT,
+        container::current(host)
+            .await
+            .command()
+            .args(["restart", name])
+            .output(),
+    )
+    .await
+    .map_err(|_| format!("Timed out restarting {}", name))?
+    .map_err(|e| format!("Failed to restart {}: {}", name, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("restart {} failed: {}", name, stderr.trim()));
+    }
+    Ok(format!("Restarted container {}", name))
+}
+
+/// Bring a hung host Ollama back. A systemd unit is restarted through
+/// systemd, which would otherwise race us to respawn it; anything else is
+/// killed and replaced with `ollama serve`. Only succeeds once the API
+/// answers again.
+async fn relaunch_ollama(host: &Host, holder: Option<PortHolder>) -> Result<String, String> {
+    let action = if has_systemd_unit(host).await {
+        restart_unit(host).await?
+    } else {
+        if let Some(holder) = &holder {
+            stop_holder(host, holder).await?;
+        }
+        let child = host
+            .command("ollama")
+            .arg("serve")
+            .to_tokio()
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to launch `ollama serve`: {}", e))?;
+        lifecycle::record_ollama_spawned(child);
+        "Relaunched `ollama serve`".to_string()
+    };
+    wait_for_api(host).await?;
+    Ok(action)
+}
+
+/// Whether Ollama runs as a systemd service (the Linux installer sets one up)
+async fn has_systemd_unit(host: &Host) -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+    let cat = timeout(
+        Duration::from_secs(5),
+        host.command("systemctl")
+            .args(["cat", OLLAMA_UNIT])
+            .output(),
+    )
+    .await;
+    matches!(cat, Ok(Ok(o)) if o.status.success())
+}
+
+async fn restart_unit(host: &Host) -> Result<String, String> {
+    tracing::warn!("Restarting the {} service", OLLAMA_UNIT);
+    let output = timeout(
+        RESTART_TIMEOUT,
+        host.command("systemctl")
+            .args(["restart", OLLAMA_UNIT])
+            .output(),
+    )
+    .await
+    .map_err(|_| format!("Timed out restarting {}", OLLAMA_UNIT))?
+    .map_err(|e| format!("Failed to run systemctl: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "systemctl restart {} failed: {} (try `sudo systemctl restart ollama`)",
+            OLLAMA_UNIT,
+            stderr.trim()
+        ));
+    }
+    Ok(format!("Restarted the {} service", OLLAMA_UNIT))
+}
+
+/// Kill whatever holds the Ollama port, if it is an Ollama we can identify
+async fn stop_holder(host: &Host, holder: &PortHolder) -> Result<(), String> {
+    let (pid, name) = match holder {
+        PortHolder::Process { pid, name } => (pid, name),
+        PortHolder::Container { name } => {
+            return Err(format!("Port {} is held by container {}", OLLAMA_PORT, name));
+        }
+        // Typically a service running as another user, whose process we
+        // can't see, let alone kill; a new `ollama serve` couldn't bind
+        PortHolder::Unknown => {
+            return Err(format!(
+                "Port {} is held by a process that can't be identified; restart Ollama yourself",
+                OLLAMA_PORT
+            ));
+        }
+    };
+    if !name.to_lowercase().contains("ollama") {
+        return Err(format!(
+            "Port {} is held by '{}' (pid {}), not Ollama",
+            OLLAMA_PORT, name, pid
+        ));
+    }
+    tracing::warn!("Killing unresponsive Ollama process {}", pid);
+    let kill = if cfg!(target_os = "windows") {
+        host.command("taskkill")
+            .args(["/F", "/PID", &pid.to_string()])
+            .output()
+            .await
+    } else {
+        host.command("kill").arg(pid.to_string()).output().await
+    };
+    let output = kill.map_err(|e| format!("Failed to stop Ollama (pid {}): {}", pid, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Failed to stop Ollama (pid {}): {}",
+            pid,
+            stderr.trim()
+        ));
+    }
+    sleep(Duration::from_secs(2)).await;
+    Ok(())
+}
+
+/// Poll `/api/version` until it answers or `OLLAMA_START_TIMEOUT` passes
+async fn wait_for_api(host: &Host) -> Result<(), String> {
+    let client = reqwest::Client::new();
+    let deadline = Instant::now() + OLLAMA_START_TIMEOUT;
+    loop {
+        let probe = client
+            .get(host.ollama("/api/version"))
+            .timeout(Duration::from_secs(2))
+            .send()
+            .await;
+        if probe.is_ok_and(|r| r.status().is_success()) {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Ollama was restarted but its API didn't answer within {}s",
+                OLLAMA_START_TIMEOUT.as_secs()
+            ));
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+}
+
+fn emit(app: &AppHandle, target: Target, action: &str, attempt: u32, success: bool, message: &str) {
+    if success {
+        tracing::info!(
+            "auto-heal {} {} (attempt {}): {}",
+            target.name(),
+            action,
+            attempt,
+            message
+        );
+    } else {
+        tracing::warn!(
+            "auto-heal {} {} (attempt {}): {}",
+            target.name(),
+            action,
+            attempt,
+            message
+        );
+    }
+
+    let _ = app.emit(
+        "auto-heal",
+        AutoHealEvent {
+            service: target.name().to_string(),
+            action: action.to_string(),
+            attempt,
+            success,
+            message: message.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn restarts_ollama_through_systemd_and_waits_for_the_api() {
+        let stub = StubOllama::start().await;
+        stub.route(Method::GET, "/api/version", 200, r#"{"version":"0.5.7"}"#);
+        let fake = FakeRunner::new();
+        fake.on("systemctl cat ollama.service", "[Service]")
+            .on("systemctl restart ollama.service", "");
+        let holder = Some(PortHolder::Unknown);
+
+        let message = relaunch_ollama(&fake.host(&stub.url), holder)
+            .await
+            .unwrap();
+        assert_eq!(message, "Restarted the ollama.service service");
+        assert!(fake.calls_to("kill").is_empty());
+
+        let refused = FakeRunner::new();
+        refused
+            .on("systemctl cat ollama.service", "[Service]")
+            .fail("systemctl restart", "Interactive authentication required.");
+        let err = relaunch_ollama(&refused.host(&stub.url), None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("sudo systemctl restart ollama"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn refuses_holders_it_cannot_stop() {
+        let fake = FakeRunner::new();
+        fake.fail("kill", "Operation not permitted");
+        let host = fake.host("http://127.0.0.1:9");
+
+        let err = stop_holder(&host, &PortHolder::Unknown).await.unwrap_err();
+        assert!(err.contains("can't be identified"), "{}", err);
+        let other = PortHolder::Process {
+            pid: 42,
+            name: "python3".into(),
+        };
+        assert!(stop_holder(&host, &other).await.is_err());
+        assert!(fake.calls_to("kill").is_empty());
+
+        let ollama = PortHolder::Process {
+            pid: 42,
+            name: "ollama".into(),
+        };
+        let err = stop_holder(&host, &ollama).await.unwrap_err();
+        assert!(err.contains("Operation not permitted"), "{}", err);
+    }
+}
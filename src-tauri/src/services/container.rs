@@ -0,0 +1,271 @@
+// Container runtime abstraction
+// Docker and Podman behind one trait; the active one follows the
+// `container_runtime` setting
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::time::{timeout, Duration};
+
+use super::host::{Cmd, Host};
+use super::profiles::Profile;
+
+const CMD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which runtime the user asked for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimePreference {
+    /// Docker when installed, otherwise Podman
+    #[default]
+    Auto,
+    Docker,
+    Podman,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeKind {
+    Docker,
+    Podman,
+}
+
+/// Everything the app needs from a container engine
+#[async_trait]
+pub trait ContainerRuntime: Send + Sync {
+    fn kind(&self) -> RuntimeKind;
+
+    /// Name shown to the user
+    fn display_name(&self) -> &'static str;
+
+    /// The host it runs commands on
+    fn host(&self) -> &Host;
+
+    /// The runtime CLI, for container-level commands (ps, inspect, restart)
+    fn command(&self) -> Cmd;
+
+    /// A compose invocation on `compose_file` for the active profile, ready
+    /// for subcommand args
+    fn compose(&self, compose_file: &Path) -> Cmd {
+        self.compose_profile(compose_file, &self.host().profile())
+    }
+
+    /// A compose invocation on `profile`'s project, with its environment
+    fn compose_profile(&self, compose_file: &Path, profile: &Profile) -> Cmd;
+
+    /// Whether the CLI is on PATH
+    fn installed(&self) -> bool;
+
+    /// Engine version, None when the CLI is missing or the engine is down
+    async fn version(&self) -> Option<String>;
+
+    async fn compose_version(&self) -> Option<String>;
+
+    /// Whether the engine answers (daemon for Docker, API socket or CLI for Podman)
+    async fn ping(&self) -> bool;
+}
+
+pub struct Docker {
+    host: Host,
+}
+
+#[async_trait]
+impl ContainerRuntime for Docker {
+    fn kind(&self) -> RuntimeKind {
+        RuntimeKind::Docker
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Docker"
+    }
+
+    fn host(&self) -> &Host {
+        &self.host
+    }
+
+    fn command(&self) -> Cmd {
+        self.host.command("docker")
+    }
+
+    fn installed(&self) -> bool {
+        self.host.on_path("docker")
+    }
+
+    fn compose_profile(&self, compose_file: &Path, profile: &Profile) -> Cmd {
+        let mut cmd = self.command();
+        cmd.args(["compose", "-p", &profile.project, "-f"])
+            .arg(compose_file)
+            .envs(profile.compose_env(self.host.certs_dir().as_deref()));
+        cmd
+    }
+
+    async fn version(&self) -> Option<String> {
+        stdout(
+            self.command()
+                .args(["version", "--format", "{{.Server.Version}}"]),
+        )
+        .await
+    }
+
+    async fn compose_version(&self) -> Option<String> {
+        stdout(self.command().args(["compose", "version", "--short"])).await
+    }
+
+    async fn ping(&self) -> bool {
+        stdout(self.command().arg("info")).await.is_some()
+    }
+}
+
+/// Podman, with either the `podman compose` wrapper (Podman 4.7+) or the
+/// standalone `podman-compose`
+pub struct Podman {
+    host: Host,
+    standalone_compose: bool,
+}
+
+impl Podman {
+    async fn detect(host: &Host) -> Self {
+        let standalone_compose = *host
+            .podman_standalone
+            .get_or_init(|| async {
+                let builtin = stdout(host.command("podman").args(["compose", "version"])).await;
+                builtin.is_none() && host.on_path("podman-compose")
+            })
+            .await;
+        Self {
+            host: host.clone(),
+            standalone_compose,
+        }
+    }
+}
+
+#[async_trait]
+impl ContainerRuntime for Podman {
+    fn kind(&self) -> RuntimeKind {
+        RuntimeKind::Podman
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Podman"
+    }
+
+    fn host(&self) -> &Host {
+        &self.host
+    }
+
+    fn command(&self) -> Cmd {
+        self.host.command("podman")
+    }
+
+    fn installed(&self) -> bool {
+        self.host.on_path("podman")
+    }
+
+    fn compose_profile(&self, compose_file: &Path, profile: &Profile) -> Cmd {
+        let mut cmd = if self.standalone_compose {
+            self.host.command("podman-compose")
+        } else {
+            let mut cmd = self.command();
+            cmd.arg("compose");
+            cmd
+        };
+        cmd.args(["-p", &profile.project, "-f"])
+            .arg(compose_file)
+            .envs(profile.compose_env(self.host.certs_dir().as_deref()));
+        cmd
+    }
+
+    async fn version(&self) -> Option<String> {
+        stdout(
+            self.command()
+                .args(["version", "--format", "{{.Client.Version}}"]),
+        )
+        .await
+    }
+
+    async fn compose_version(&self) -> Option<String> {
+        if self.standalone_compose {
+            stdout(self.host.command("podman-compose").arg("--version")).await
+        } else {
+            stdout(self.command().args(["compose", "version", "--short"])).await
+        }
+    }
+
+    async fn ping(&self) -> bool {
+        #[cfg(unix)]
+        for socket in podman_sockets() {
+            if ping_socket(&socket).await {
+                return true;
+            }
+        }
+        // Podman is daemonless: the CLI works even without the API socket
+        stdout(self.command().arg("info")).await.is_some()
+    }
+}
+
+/// The runtime selected by the host's `container_runtime` setting
+pub async fn current(host: &Host) -> Box<dyn ContainerRuntime> {
+    let kind = match host.runtime_preference() {
+        RuntimePreference::Docker => RuntimeKind::Docker,
+        RuntimePreference::Podman => RuntimeKind::Podman,
+        RuntimePreference::Auto if !host.on_path("docker") && host.on_path("podman") => {
+            RuntimeKind::Podman
+        }
+        RuntimePreference::Auto => RuntimeKind::Docker,
+    };
+    runtime(host, kind).await
+}
+
+pub async fn runtime(host: &Host, kind: RuntimeKind) -> Box<dyn ContainerRuntime> {
+    match kind {
+        RuntimeKind::Docker => Box::new(Docker { host: host.clone() }),
+        RuntimeKind::Podman => Box::new(Podman::detect(host).await),
+    }
+}
+
+/// Trimmed stdout of a successful command, None on failure or timeout
+async fn stdout(cmd: &Cmd) -> Option<String> {
+    match timeout(CMD_TIMEOUT, cmd.output()).await {
+        Ok(Ok(o)) if o.status.success() => {
+            Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Podman API sockets to try: $CONTAINER_HOST, rootless, then rootful
+#[cfg(unix)]
+fn podman_sockets() -> Vec<std::path::PathBuf> {
+    let mut sockets = Vec::new();
+    if let Some(path) = std::env::var("CONTAINER_HOST")
+        .ok()
+        .and_then(|h| h.strip_prefix("unix://").map(std::path::PathBuf::from))
+    {
+        sockets.push(path);
+    }
+    if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        sockets.push(Path::new(&runtime_dir).join("podman/podman.sock"));
+    }
+    sockets.push("/run/podman/podman.sock".into());
+    sockets
+}
+
+/// `GET /_ping` on a Podman (Docker-compatible) API socket
+#[cfg(unix)]
+async fn ping_socket(socket: &Path) -> bool {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let ping = async {
+        let mut stream = tokio::net::UnixStream::connect(socket).await.ok()?;
+        stream
+            .write_all(b"GET /_ping HTTP/1.0\r\nHost: podman\r\n\r\n")
+            .await
+            .ok()?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.ok()?;
+        let response = String::from_utf8_lossy(&response);
+        Some(response.split_whitespace().nth(1) == Some("200"))
+    };
+    matches!(timeout(Duration::from_secs(2), ping).await, Ok(Some(true)))
+}
@@ -0,0 +1,817 @@
+// Authenticated API gateway
+// Optional reverse proxy in front of the model server: every request needs
+// one of the API keys created in the app, each with its own rate limit and
+// model allowlist, and every request is logged. Open-WebUI talks to Ollama
+// through it with a key of its own, passed in the URL.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::Response;
+use axum::Router;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::oneshot;
+
+use super::container::{self, RuntimeKind};
+use super::host::Host;
+use super::instance::random_token;
+use crate::utils::time::unix_now;
+
+pub const DEFAULT_GATEWAY_PORT: u16 = 11435;
+/// Request bodies are read to find the model; blob uploads are streamed
+const MAX_BODY: usize = 16 * 1024 * 1024;
+/// Model management; keys with an allowlist can't use these
+const MANAGEMENT_PATHS: [&str; 6] = [
+    "/api/pull",
+    "/api/push",
+    "/api/create",
+    "/api/copy",
+    "/api/delete",
+    "/api/blobs",
+];
+const WEBUI_KEY_ID: &str = "webui";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GatewaySettings {
+    pub enabled: bool,
+    /// None listens only where the webui container reaches the host (see
+    /// `bind_address`). Anything else, e.g. 0.0.0.0 to serve other
+    /// machines, is an explicit choice and shows a warning.
+    pub bind_address: Option<String>,
+    pub port: u16,
+    pub keys: Vec<ApiKey>,
+    /// Open-WebUI's own key, set by `start_gateway`; without one the webui
+    /// keeps talking to Ollama directly
+    pub webui_api_key: Option<String>,
+}
+
+impl Default for GatewaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: None,
+            port: DEFAULT_GATEWAY_PORT,
+            keys: Vec::new(),
+            webui_api_key: None,
+        }
+    }
+}
+
+impl GatewaySettings {
+    pub fn validate(&self) -> Result<(), (&'static str, &'static str)> {
+        if self
+            .bind_address
+            .as_deref()
+            .is_some_and(|a| a.parse::<IpAddr>().is_err())
+        {
+            return Err(("bind_address", "must be an IP address"));
+        }
+        if self.port < 1024 {
+            return Err(("port", "must be 1024 or higher"));
+        }
+        for (i, key) in self.keys.iter().enumerate() {
+            if key.name.trim().is_empty() {
+                return Err(("keys", "every key needs a name"));
+            }
+            if key.id == WEBUI_KEY_ID || self.keys[..i].iter().any(|k| k.id == key.id) {
+                return Err(("keys", "key ids must be unique"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A client key. Only its hash is stored; the key itself is shown once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    /// SHA-256 of the key, hex
+    pub hash: String,
+    /// Start of the key, to tell keys apart in the app
+    pub prefix: String,
+    /// Requests per minute; 0 is unlimited
+    #[serde(default)]
+    pub rate_limit: u32,
+    /// Models the key may use (`name` or `name:tag`); empty allows all
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    pub created_at: u64,
+}
+
+impl ApiKey {
+    /// A new key and its secret
+    pub fn generate(name: &str, rate_limit: u32, allowed_models: Vec<String>) -> (Self, String) {
+        let secret = format!("dgk_{}", random_token());
+        let key = Self {
+            id: random_token()[..12].to_string(),
+            name: name.trim().to_string(),
+            hash: hash(&secret),
+            prefix: secret[..8].to_string(),
+            rate_limit,
+            allowed_models,
+            created_at: unix_now(),
+        };
+        (key, secret)
+    }
+
+    fn permits(&self, path: &str, model: Option<&str>) -> Result<(), String> {
+        if self.allowed_models.is_empty() {
+            return Ok(());
+        }
+        if MANAGEMENT_PATHS.iter().any(|p| path.starts_with(p)) {
+            return Err("this key can't manage models".to_string());
+        }
+        match model {
+            Some(model) if !self.allows(model) => Err(format!("this key may not use {}", model)),
+            _ => Ok(()),
+        }
+    }
+
+    fn allows(&self, model: &str) -> bool {
+        self.allowed_models
+            .iter()
+            .any(|allowed| with_tag(allowed) == with_tag(model))
+    }
+}
+
+/// `dolphin-phi` and `dolphin-phi:latest` are the same model
+fn with_tag(model: &str) -> String {
+    let name = model.rsplit('/').next().unwrap_or(model);
+    if name.contains(':') {
+        model.to_string()
+    } else {
+        format!("{}:latest", model)
+    }
+}
+
+fn hash(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+static WEBUI_ENDPOINT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Where Open-WebUI should reach Ollama while the gateway runs, with its
+/// key as URL credentials (sent as Basic auth)
+pub fn webui_endpoint() -> Option<String> {
+    WEBUI_ENDPOINT.read().ok().and_then(|e| e.clone())
+}
+
+fn set_webui_endpoint(endpoint: Option<String>) {
+    if let Ok(mut current) = WEBUI_ENDPOINT.write() {
+        *current = endpoint;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KeyUsage {
+    pub requests: u64,
+    /// Over the rate limit or outside the allowlist
+    pub rejected: u64,
+    pub last_used: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayStatus {
+    pub running: bool,
+    pub address: Option<String>,
+    /// By key id, since the app started
+    pub usage: BTreeMap<String, KeyUsage>,
+    /// Set when clients can get around the gateway
+    pub warning: Option<String>,
+}
+
+/// Handle to the running gateway, if any. Held in Tauri state.
+#[derive(Default)]
+pub struct GatewayServer {
+    running: Mutex<Option<(SocketAddr, oneshot::Sender<()>)>>,
+    keys: Arc<RwLock<Vec<ApiKey>>>,
+    usage: Arc<Mutex<BTreeMap<String, KeyUsage>>>,
+    warning: Mutex<Option<String>>,
+}
+
+impl GatewayServer {
+    pub fn status(&self) -> GatewayStatus {
+        let addr = self
+            .running
+            .lock()
+            .ok()
+            .and_then(|r| r.as_ref().map(|(addr, _)| *addr));
+        GatewayStatus {
+            running: addr.is_some(),
+            address: addr.map(|a| format!("http://{}", a)),
+            usage: self.usage.lock().map(|u| u.clone()).unwrap_or_default(),
+            warning: self.warning.lock().ok().and_then(|w| w.clone()),
+        }
+    }
+
+    /// Start, restart or stop the gateway to match `settings`
+    pub async fn apply(
+        &self,
+        host: Host,
+        settings: &GatewaySettings,
+    ) -> Result<GatewayStatus, String> {
+        self.set_keys(settings);
+        if !settings.enabled {
+            self.stop();
+            return Ok(self.status());
+        }
+        let (address, mut warnings) = bind_address(&host, settings).await?;
+        let wanted = SocketAddr::new(address, settings.port);
+        let current = self
+            .running
+            .lock()
+            .ok()
+            .and_then(|r| r.as_ref().map(|(a, _)| *a));
+        if current != Some(wanted) {
+            self.start(host.clone(), wanted).await?;
+        }
+        warnings.extend(ollama_exposure(&host).await);
+        for warning in &warnings {
+            tracing::warn!("{}", warning);
+        }
+        if let Ok(mut current) = self.warning.lock() {
+            *current = (!warnings.is_empty()).then(|| warnings.join(" "));
+        }
+        set_webui_endpoint(settings.webui_api_key.as_ref().map(|key| {
+            format!(
+                "http://webui:{}@host.docker.internal:{}",
+                key, settings.port
+            )
+        }));
+        Ok(self.status())
+    }
+
+    /// Keys take effect on the next request, without a restart
+    fn set_keys(&self, settings: &GatewaySettings) {
+        let mut keys = settings.keys.clone();
+        if let Some(secret) = &settings.webui_api_key {
+            keys.push(ApiKey {
+                id: WEBUI_KEY_ID.to_string(),
+                name: "Open-WebUI".to_string(),
+                hash: hash(secret),
+                prefix: String::new(),
+                rate_limit: 0,
+                allowed_models: Vec::new(),
+                created_at: 0,
+            });
+        }
+        if let Ok(mut current) = self.keys.write() {
+            *current = keys;
+        }
+    }
+
+    async fn start(&self, host: Host, address: SocketAddr) -> Result<GatewayStatus, String> {
+        self.stop();
+
+        let listener = tokio::net::TcpListener::bind(address)
+            .await
+            .map_err(|e| format!("Cannot bind gateway on {}: {}", address, e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("Gateway address error: {}", e))?;
+
+        let gate = Gate {
+            host,
+            client: reqwest::Client::new(),
+            keys: self.keys.clone(),
+            usage: self.usage.clone(),
+            buckets: Arc::default(),
+        };
+        let app = Router::new().fallback(proxy).with_state(gate);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            let server = axum::serve(listener, app).with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            });
+            if let Err(e) = server.await {
+                tracing::error!("Gateway stopped: {}", e);
+            }
+        });
+
+        tracing::info!("API gateway listening on http://{}", addr);
+        if let Ok(mut running) = self.running.lock() {
+            *running = Some((addr, shutdown_tx));
+        }
+        Ok(self.status())
+    }
+
+    pub fn stop(&self) {
+        if let Some((addr, shutdown)) = self.running.lock().ok().and_then(|mut r| r.take()) {
+            let _ = shutdown.send(());
+            set_webui_endpoint(None);
+            if let Ok(mut warning) = self.warning.lock() {
+                *warning = None;
+            }
+            tracing::info!("API gateway on {} stopped", addr);
+        }
+    }
+}
+
+/// Where to listen, with warnings. By default that is loopback, which Docker
+/// Desktop forwards host.docker.internal to; on Linux host.docker.internal
+/// is the bridge gateway, which a loopback listener never sees.
+async fn bind_address(
+    host: &Host,
+    settings: &GatewaySettings,
+) -> Result<(IpAddr, Vec<String>), String> {
+    let bridge = if cfg!(target_os = "linux") {
+        bridge_gateway(host).await
+    } else {
+        None
+    };
+    let mut warnings = Vec::new();
+    let address = match (&settings.bind_address, bridge) {
+        (Some(address), _) => address
+            .parse()
+            .map_err(|_| format!("Invalid gateway bind address: {}", address))?,
+        (None, Some(bridge)) => bridge,
+        (None, None) => {
+            if cfg!(target_os = "linux") {
+                warnings.push(
+                    "The container bridge address was not found, so the gateway only \
+                     listens on 127.0.0.1 and Open-WebUI can't reach it. Restart the \
+                     gateway once Docker is running."
+                        .to_string(),
+                );
+            }
+            Ipv4Addr::LOCALHOST.into()
+        }
+    };
+    warnings.extend(lan_exposure(address, bridge));
+    Ok((address, warnings))
+}
+
+/// The gateway speaks plain HTTP: any address but loopback and the
+/// container bridge lets other machines send keys and prompts in the clear
+fn lan_exposure(address: IpAddr, bridge: Option<IpAddr>) -> Option<String> {
+    if address.is_loopback() || Some(address) == bridge {
+        return None;
+    }
+    let reach = if address.is_unspecified() {
+        "every network interface".to_string()
+    } else {
+        address.to_string()
+    };
+    Some(format!(
+        "The gateway listens on {} over plain HTTP, so other machines can reach it \
+         and API keys cross the network unencrypted. Clear the gateway bind address \
+         to only serve Open-WebUI.",
+        reach
+    ))
+}
+
+/// What `host-gateway` (host.docker.internal in the compose file) resolves
+/// to on Linux: the default network's gateway
+async fn bridge_gateway(host: &Host) -> Option<IpAddr> {
+    let runtime = container::current(host).await;
+    let (network, format) = match runtime.kind() {
+        RuntimeKind::Docker => ("bridge", "{{range .IPAM.Config}}{{.Gateway}} {{end}}"),
+        RuntimeKind::Podman => ("podman", "{{range .Subnets}}{{.Gateway}} {{end}}"),
+    };
+    let output = tokio::time::timeout(
+        Duration::from_secs(5),
+        runtime
+            .command()
+            .args(["network", "inspect", network, "--format", format])
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .filter_map(|a| a.parse::<IpAddr>().ok())
+        .find(IpAddr::is_ipv4)
+}
+
+/// A local Ollama that also answers on this machine's network address
+/// (`OLLAMA_HOST=0.0.0.0`, common on Linux so containers can reach it) can
+/// be used without a key. Ollama's default, loopback only, is what the
+/// gateway needs.
+async fn ollama_exposure(host: &Host) -> Option<String> {
+    let url = reqwest::Url::parse(&host.ollama("/")).ok()?;
+    let local = match url.host_str()? {
+        "localhost" => true,
+        other => other
+            .trim_matches(['[', ']'])
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback()),
+    };
+    if !local {
+        return None;
+    }
+    let port = url.port_or_known_default()?;
+
+    // Connecting a UDP socket only picks the outgoing interface; nothing is sent
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+    let address = socket.local_addr().ok()?.ip();
+    if address.is_loopback() || address.is_unspecified() {
+        return None;
+    }
+    let connect = tokio::net::TcpStream::connect((address, port));
+    tokio::time::timeout(Duration::from_secs(1), connect)
+        .await
+        .is_ok_and(|c| c.is_ok())
+        .then(|| {
+            format!(
+                "Ollama also listens on {}:{}, so it can be used without a key. Set \
+                 OLLAMA_HOST=127.0.0.1 and restart Ollama to only allow the gateway.",
+                address, port
+            )
+        })
+}
+
+/// Token bucket holding up to a minute's worth of requests
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Take a request, or say how long until one is available
+    fn take(&mut self, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        let capacity = per_minute as f64;
+        let refilled = now.duration_since(self.updated).as_secs_f64() * capacity / 60.0;
+        self.tokens = (self.tokens + refilled).min(capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) * 60.0 / capacity,
+            ))
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Gate {
+    host: Host,
+    client: reqwest::Client,
+    keys: Arc<RwLock<Vec<ApiKey>>>,
+    usage: Arc<Mutex<BTreeMap<String, KeyUsage>>>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl Gate {
+    /// The key presented as `Authorization: Bearer`, Basic auth (URL
+    /// credentials) or `X-API-Key`
+    fn authenticate(&self, headers: &HeaderMap) -> Option<ApiKey> {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+        let secret = match header(header::AUTHORIZATION.as_str()) {
+            Some(auth) if auth.starts_with("Bearer ") => auth[7..].trim().to_string(),
+            Some(auth) if auth.starts_with("Basic ") => {
+                let decoded = base64::engine::general_purpose::STANDARD
+                    .decode(auth[6..].trim())
+                    .ok()?;
+                let decoded = String::from_utf8(decoded).ok()?;
+                let (user, password) = decoded.split_once(':').unwrap_or((&decoded, ""));
+                if password.is_empty() { user } else { password }.to_string()
+            }
+            _ => header("x-api-key")?.trim().to_string(),
+        };
+        let hash = hash(&secret);
+        self.keys
+            .read()
+            .ok()?
+            .iter()
+            .find(|key| key.hash == hash)
+            .cloned()
+    }
+
+    fn throttle(&self, key: &ApiKey) -> Result<(), Duration> {
+        if key.rate_limit == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().map_err(|_| Duration::ZERO)?;
+        buckets
+            .entry(key.id.clone())
+            .or_insert(Bucket {
+                tokens: key.rate_limit as f64,
+                updated: now,
+            })
+            .take(key.rate_limit, now)
+    }
+
+    fn record(&self, key: &ApiKey, rejected: bool) {
+        if let Ok(mut usage) = self.usage.lock() {
+            let usage = usage.entry(key.id.clone()).or_default();
+            if rejected {
+                usage.rejected += 1;
+            } else {
+                usage.requests += 1;
+                usage.last_used = Some(unix_now());
+            }
+        }
+    }
+}
+
+async fn proxy(State(gate): State<Gate>, request: Request) -> Response {
+    let started = Instant::now();
+    let (parts, body) = request.into_parts();
+    let path = parts.uri.path().to_string();
+
+    let Some(key) = gate.authenticate(&parts.headers) else {
+        tracing::warn!(
+            "Gateway: {} {} rejected, no valid API key",
+            parts.method,
+            path
+        );
+        return reject(StatusCode::UNAUTHORIZED, "missing or unknown API key");
+    };
+    if let Err(wait) = gate.throttle(&key) {
+        gate.record(&key, true);
+        tracing::warn!(
+            "Gateway: {} over its rate limit ({} {})",
+            key.name,
+            parts.method,
+            path
+        );
+        let mut response = reject(StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded");
+        let retry_after = wait.as_secs().max(1).to_string();
+        if let Ok(value) = HeaderValue::from_str(&retry_after) {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+        return response;
+    }
+
+    let (body, model) = if path.starts_with("/api/blobs") {
+        (reqwest::Body::wrap_stream(body.into_data_stream()), None)
+    } else {
+        let Ok(bytes) = axum::body::to_bytes(body, MAX_BODY).await else {
+            return reject(StatusCode::PAYLOAD_TOO_LARGE, "request body too large");
+        };
+        let model = requested_model(&bytes);
+        (reqwest::Body::from(bytes), model)
+    };
+    if let Err(reason) = key.permits(&path, model.as_deref()) {
+        gate.record(&key, true);
+        tracing::warn!("Gateway: {} refused {}: {}", key.name, path, reason);
+        return reject(StatusCode::FORBIDDEN, &reason);
+    }
+
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map_or(path.as_str(), |p| p.as_str());
+    let mut headers = parts.headers.clone();
+    for name in [
+        header::HOST,
+        header::AUTHORIZATION,
+        header::CONTENT_LENGTH,
+        header::CONNECTION,
+        header::TRANSFER_ENCODING,
+    ] {
+        headers.remove(name);
+    }
+    headers.remove("x-api-key");
+
+    let upstream = gate
+        .client
+        .request(parts.method.clone(), gate.host.ollama(path_and_query))
+        .headers(headers)
+        .body(body)
+        .send()
+        .await;
+    let upstream = match upstream {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            tracing::warn!("Gateway: upstream unreachable: {}", e);
+            return reject(StatusCode::BAD_GATEWAY, "the model server is not reachable");
+        }
+    };
+
+    gate.record(&key, false);
+    tracing::info!(
+        "Gateway: {} {} {} model={} -> {} ({} ms)",
+        key.name,
+        parts.method,
+        path,
+        model.as_deref().unwrap_or("-"),
+        upstream.status().as_u16(),
+        started.elapsed().as_millis()
+    );
+
+    let mut response = Response::builder().status(upstream.status());
+    for (name, value) in upstream.headers() {
+        if name != header::CONTENT_LENGTH
+            && name != header::TRANSFER_ENCODING
+            && name != header::CONNECTION
+        {
+            response = response.header(name, value);
+        }
+    }
+    let listing = matches!(path.as_str(), "/api/tags" | "/v1/models");
+    let body = if listing && !key.allowed_models.is_empty() {
+        let bytes = upstream.bytes().await.unwrap_or_default();
+        Body::from(filter_listing(&key, bytes))
+    } else {
+        Body::from_stream(upstream.bytes_stream())
+    };
+    response
+        .body(body)
+        .unwrap_or_else(|_| reject(StatusCode::BAD_GATEWAY, "invalid upstream response"))
+}
+
+/// The model a JSON request names (`model`, or `name` on older endpoints)
+fn requested_model(body: &[u8]) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    json["model"]
+        .as_str()
+        .or(json["name"].as_str())
+        .map(str::to_string)
+}
+
+/// Hide models outside the key's allowlist from model listings
+fn filter_listing(key: &ApiKey, body: Bytes) -> Bytes {
+    let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(&body) else {
+        return body;
+    };
+    // Ollama's /api/tags lists `name`, OpenAI's /v1/models lists `id`
+    for (list, field) in [("models", "name"), ("data", "id")] {
+        if let Some(models) = json[list].as_array_mut() {
+            models.retain(|m| m[field].as_str().is_some_and(|name| key.allows(name)));
+        }
+    }
+    serde_json::to_vec(&json).map(Bytes::from).unwrap_or(body)
+}
+
+fn reject(status: StatusCode, message: &str) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::json!({ "error": message }).to_string(),
+        ))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{FakeRunner, StubOllama};
+    use axum::http::Method;
+
+    async fn gateway(upstream: &StubOllama, keys: Vec<ApiKey>) -> (GatewayServer, String) {
+        let server = GatewayServer::default();
+        let settings = GatewaySettings {
+            enabled: true,
+            keys,
+            ..GatewaySettings::default()
+        };
+        server.set_keys(&settings);
+        let status = server
+            .start(
+                FakeRunner::new().host(&upstream.url),
+                (Ipv4Addr::LOCALHOST, 0).into(),
+            )
+            .await
+            .unwrap();
+        (server, status.address.unwrap())
+    }
+
+    #[tokio::test]
+    async fn requires_a_key_and_forwards_with_it() {
+        let stub = StubOllama::start().await;
+        stub.route(Method::GET, "/api/version", 200, r#"{"version":"0.5.7"}"#);
+        let (key, secret) = ApiKey::generate("scripts", 0, Vec::new());
+        let (server, url) = gateway(&stub, vec![key.clone()]).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .get(format!("{}/api/version", url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+        let response = client
+            .get(format!("{}/api/version", url))
+            .bearer_auth("dgk_wrong")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+        assert!(stub.received().is_empty());
+
+        let response = client
+            .get(format!("{}/api/version", url))
+            .bearer_auth(&secret)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), r#"{"version":"0.5.7"}"#);
+        // URL credentials, as Open-WebUI sends them
+        let response = client
+            .get(format!("{}/api/version", url))
+            .basic_auth("webui", Some(&secret))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.status().usage[&key.id].requests, 2);
+        server.stop();
+    }
+
+    #[tokio::test]
+    async fn enforces_allowed_models_and_rate_limits() {
+        let stub = StubOllama::start().await;
+        stub.route(Method::POST, "/api/chat", 200, r#"{"done":true}"#)
+            .route(
+                Method::GET,
+                "/api/tags",
+                200,
+                r#"{"models":[{"name":"dolphin-phi:latest"},{"name":"llama3:70b"}]}"#,
+            );
+        let (key, secret) = ApiKey::generate("kiosk", 3, vec!["dolphin-phi".into()]);
+        let (server, url) = gateway(&stub, vec![key.clone()]).await;
+        let client = reqwest::Client::new();
+        let chat = |model: &str| {
+            client
+                .post(format!("{}/api/chat", url))
+                .bearer_auth(&secret)
+                .json(&serde_json::json!({ "model": model, "messages": [] }))
+                .send()
+        };
+
+        assert_eq!(chat("llama3:70b").await.unwrap().status(), 403);
+        assert_eq!(chat("dolphin-phi:latest").await.unwrap().status(), 200);
+        let response = client
+            .get(format!("{}/api/tags", url))
+            .bearer_auth(&secret)
+            .send()
+            .await
+            .unwrap();
+        let listing: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(listing["models"].as_array().unwrap().len(), 1);
+
+        // Three requests a minute, refused ones included: the fourth is over
+        let response = chat("dolphin-phi").await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+        let usage = &server.status().usage[&key.id];
+        assert_eq!((usage.requests, usage.rejected), (2, 2));
+        server.stop();
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 1.0,
+            updated: start,
+        };
+        assert!(bucket.take(60, start).is_ok());
+        let wait = bucket.take(60, start).unwrap_err();
+        assert_eq!(wait.as_secs(), 1);
+        assert!(bucket.take(60, start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn listens_on_the_container_bridge_by_default() {
+        let fake = FakeRunner::new();
+        fake.installed("docker")
+            .on("docker network inspect bridge", "172.17.0.1 \n");
+        let host = fake.host("http://127.0.0.1:9");
+
+        let (address, warnings) = bind_address(&host, &GatewaySettings::default())
+            .await
+            .unwrap();
+        assert_eq!(address, IpAddr::from([172, 17, 0, 1]));
+        assert!(warnings.is_empty());
+
+        let lan = GatewaySettings {
+            bind_address: Some("0.0.0.0".to_string()),
+            ..GatewaySettings::default()
+        };
+        let (address, warnings) = bind_address(&host, &lan).await.unwrap();
+        assert!(address.is_unspecified());
+        assert!(warnings[0].contains("every network interface"));
+    }
+
+    #[test]
+    fn warns_when_other_machines_can_connect() {
+        let bridge = Some(IpAddr::from([172, 17, 0, 1]));
+        assert_eq!(lan_exposure(Ipv4Addr::LOCALHOST.into(), bridge), None);
+        assert_eq!(lan_exposure(IpAddr::from([172, 17, 0, 1]), bridge), None);
+        assert!(lan_exposure(Ipv4Addr::UNSPECIFIED.into(), bridge).is_some());
+        assert!(lan_exposure(IpAddr::from([192, 168, 1, 20]), None).is_some());
+    }
+}
@@ -0,0 +1,242 @@
+// Host access
+// Every external program the app runs goes through a `CommandRunner`, and
+// every Ollama request through `Host::ollama`. Both live in one `Host`
+// held in Tauri state (the CLI builds its own), so tests can swap in a
+// scripted runner and a stub Ollama server (see utils/testing.rs). The
+// host also carries what the settings select (container runtime, active
+// profile), so each host follows its own.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use tokio::process::Command as TokioCommand;
+use tokio::sync::OnceCell;
+
+use super::container::RuntimePreference;
+use super::profiles::Profile;
+
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Runs commands to completion and answers PATH lookups
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    async fn output(&self, cmd: &Cmd) -> std::io::Result<Output>;
+
+    /// Whether `program` is on PATH
+    fn on_path(&self, program: &str) -> bool;
+}
+
+/// The real thing: spawns the process
+pub struct SystemRunner;
+
+#[async_trait]
+impl CommandRunner for SystemRunner {
+    async fn output(&self, cmd: &Cmd) -> std::io::Result<Output> {
+        tracing::debug!("Running {}", cmd.line());
+        cmd.to_tokio().output().await
+    }
+
+    fn on_path(&self, program: &str) -> bool {
+        let names: Vec<String> = if cfg!(target_os = "windows") {
+            vec![format!("{}.exe", program), program.to_string()]
+        } else {
+            vec![program.to_string()]
+        };
+        std::env::var_os("PATH").is_some_and(|path| {
+            std::env::split_paths(&path).any(|dir| names.iter().any(|n| dir.join(n).is_file()))
+        })
+    }
+}
+
+/// A command line, built like `tokio::process::Command` and run through
+/// the host's runner
+#[derive(Clone)]
+pub struct Cmd {
+    runner: Arc<dyn CommandRunner>,
+    program: String,
+    args: Vec<String>,
+    envs: Vec<(String, String)>,
+    current_dir: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.args.push(arg.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        for arg in args {
+            self.arg(arg);
+        }
+        self
+    }
+
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.envs
+            .extend(vars.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    pub fn current_dir(&mut self, dir: impl AsRef<Path>) -> &mut Self {
+        self.current_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    #[cfg(test)]
+    pub fn get_envs(&self) -> &[(String, String)] {
+        &self.envs
+    }
+
+    /// Program and arguments joined by spaces, for logs and test scripts
+    pub fn line(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Run to completion through the host's runner
+    pub async fn output(&self) -> std::io::Result<Output> {
+        self.runner.output(self).await
+    }
+
+    /// A real process for streaming or long-running use (`spawn`, piped or
+    /// inherited stdio). Bypasses the runner, so tests don't see it.
+    pub fn to_tokio(&self) -> TokioCommand {
+        let mut cmd = TokioCommand::new(&self.program);
+        cmd.args(&self.args)
+            .envs(self.envs.iter().map(|(k, v)| (k, v)));
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
+        cmd
+    }
+}
+
+/// How the app reaches the machine it manages
+#[derive(Clone)]
+pub struct Host {
+    pub runner: Arc<dyn CommandRunner>,
+    /// Ollama API root; `None` follows the active profile
+    ollama_url: Option<String>,
+    /// Whether Podman compose is the standalone `podman-compose`, probed once
+    pub(crate) podman_standalone: Arc<OnceCell<bool>>,
+    /// Shared by every clone, so a settings change reaches them all
+    selection: Arc<RwLock<Selection>>,
+}
+
+/// What the settings select
+#[derive(Default)]
+struct Selection {
+    runtime: RuntimePreference,
+    /// The default profile until settings are applied
+    profile: Option<Profile>,
+    /// Where Caddy's certificates are mounted from (see `AppPaths::certs_dir`)
+    certs_dir: Option<PathBuf>,
+}
+
+impl Host {
+    pub fn new(runner: Arc<dyn CommandRunner>, ollama_url: Option<String>) -> Self {
+        Self {
+            runner,
+            ollama_url,
+            podman_standalone: Arc::new(OnceCell::new()),
+            selection: Arc::new(RwLock::new(Selection::default())),
+        }
+    }
+
+    /// Real processes and the active profile's Ollama
+    pub fn system() -> Self {
+        Self::new(Arc::new(SystemRunner), None)
+    }
+
+    pub fn command(&self, program: &str) -> Cmd {
+        Cmd {
+            runner: self.runner.clone(),
+            program: program.to_string(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            current_dir: None,
+        }
+    }
+
+    pub fn on_path(&self, program: &str) -> bool {
+        self.runner.on_path(program)
+    }
+
+    /// URL of an Ollama API path, e.g. `/api/tags`
+    pub fn ollama(&self, path: &str) -> String {
+        let base = self
+            .ollama_url
+            .clone()
+            .unwrap_or_else(|| self.profile().ollama_url);
+        format!("{}{}", base.trim_end_matches('/'), path)
+    }
+
+    /// The `container_runtime` setting (see `container::current`)
+    pub fn runtime_preference(&self) -> RuntimePreference {
+        self.selection
+            .read()
+            .map(|selection| selection.runtime)
+            .unwrap_or_default()
+    }
+
+    /// Switch the runtime used from now on
+    pub fn set_runtime_preference(&self, preference: RuntimePreference) {
+        if let Ok(mut selection) = self.selection.write() {
+            if selection.runtime != preference {
+                tracing::info!("Container runtime preference: {:?}", preference);
+            }
+            selection.runtime = preference;
+        }
+    }
+
+    /// The profile compose calls, endpoints and health checks use
+    pub fn profile(&self) -> Profile {
+        self.selection
+            .read()
+            .ok()
+            .and_then(|selection| selection.profile.clone())
+            .unwrap_or_default()
+    }
+
+    /// Use `profile` from now on
+    pub fn activate(&self, profile: &Profile) {
+        if let Ok(mut selection) = self.selection.write() {
+            if selection.profile.as_ref().map(|p| &p.name) != Some(&profile.name) {
+                tracing::info!(
+                    "Active profile: {} (project {})",
+                    profile.name,
+                    profile.project
+                );
+            }
+            selection.profile = Some(profile.clone());
+        }
+    }
+
+    pub fn certs_dir(&self) -> Option<PathBuf> {
+        self.selection
+            .read()
+            .ok()
+            .and_then(|selection| selection.certs_dir.clone())
+    }
+
+    pub fn set_certs_dir(&self, dir: PathBuf) {
+        if let Ok(mut selection) = self.selection.write() {
+            selection.certs_dir = Some(dir);
+        }
+    }
+}
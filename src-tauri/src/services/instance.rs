@@ -0,0 +1,355 @@
+// Single instance
+// The first instance writes a lock file (owner-only, in the app's local
+// data dir) naming a loopback port it listens on. Later launches hand their arguments
+// (including deep links) to it over that socket and exit; it focuses its
+// window. A lock whose port doesn't answer was left by a crashed instance
+// and is taken over.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+/// A running instance that takes longer than this to answer is hung
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Upper bound for one handoff message
+const MAX_MESSAGE: u64 = 64 * 1024;
+/// In the app's local data dir
+pub const LOCK_FILE: &str = "instance.lock";
+
+/// Contents of the lock file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Lock {
+    pid: u32,
+    port: u16,
+    /// Only clients that can read the lock file may hand over arguments
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Handoff {
+    token: String,
+    #[serde(flatten)]
+    launch: Launch,
+}
+
+/// Sent as `second-instance` when another launch hands over to this one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Launch {
+    /// Arguments after the program name, e.g. a `dark-gpt://` link
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+}
+
+pub enum Startup {
+    /// This is the only instance; call `Instance::listen` once the app is up
+    Primary(Instance),
+    /// Handed over to the running instance; exit now
+    Forwarded,
+    /// Another instance holds the lock but doesn't answer
+    Unresponsive(String),
+}
+
+/// Holds the lock and the socket later launches connect to
+pub struct Instance {
+    listener: TcpListener,
+    token: String,
+}
+
+enum ForwardError {
+    /// Nothing, or something else, answers on the lock's port
+    Stale,
+    Unresponsive(String),
+}
+
+/// Lock file of this instance, removed on exit
+static LOCK: Mutex<Option<(PathBuf, Lock)>> = Mutex::new(None);
+
+/// Take the lock, or hand this launch to the instance that has it. `Err`
+/// means the lock couldn't be checked at all (e.g. unwritable data dir).
+pub fn acquire(identifier: &str) -> Result<Startup, String> {
+    let path = lock_path(identifier)?;
+    let launch = Launch {
+        args: std::env::args().skip(1).collect(),
+        cwd: std::env::current_dir()
+            .ok()
+            .map(|dir| dir.to_string_lossy().to_string()),
+    };
+
+    // Twice: once more if we lose a race for a stale lock
+    for _ in 0..2 {
+        if let Some(lock) = read_lock(&path) {
+            match forward(&lock, &launch) {
+                Ok(()) => return Ok(Startup::Forwarded),
+                Err(ForwardError::Unresponsive(e)) => return Ok(Startup::Unresponsive(e)),
+                Err(ForwardError::Stale) => {
+                    tracing::warn!("Removing stale instance lock (pid {})", lock.pid);
+                    // Someone else may have replaced it meanwhile
+                    if read_lock(&path).as_ref() == Some(&lock) {
+                        let _ = std::fs::remove_file(&path);
+                    }
+                }
+            }
+        }
+        if let Some(instance) = create(&path)? {
+            return Ok(Startup::Primary(instance));
+        }
+    }
+    Err(format!("Could not take the instance lock {:?}", path))
+}
+
+/// Remove our lock file, if it is still ours
+pub fn release() {
+    let Some((path, lock)) = LOCK.lock().ok().and_then(|mut lock| lock.take()) else {
+        return;
+    };
+    if read_lock(&path).as_ref() == Some(&lock) {
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+/// Show, unminimize and focus the main window
+pub fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+impl Instance {
+    /// Accept handoffs from later launches for the rest of the session
+    pub fn listen(self, app: &AppHandle) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let listener = match self
+                .listener
+                .set_nonblocking(true)
+                .and_then(|_| tokio::net::TcpListener::from_std(self.listener))
+            {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::error!("Instance socket unusable: {}", e);
+                    return;
+                }
+            };
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let (app, token) = (app.clone(), self.token.clone());
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = receive(&app, stream, &token).await {
+                                tracing::warn!("Rejected instance handoff: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!("Instance socket accept failed: {}", e),
+                }
+            }
+        });
+    }
+}
+
+async fn receive(
+    app: &AppHandle,
+    stream: tokio::net::TcpStream,
+    token: &str,
+) -> Result<(), String> {
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    tokio::time::timeout(
+        REPLY_TIMEOUT,
+        tokio::io::BufReader::new(read.take(MAX_MESSAGE)).read_line(&mut line),
+    )
+    .await
+    .map_err(|_| "timed out".to_string())?
+    .map_err(|e| e.to_string())?;
+
+    let handoff: Handoff = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+    if handoff.token != token {
+        return Err("wrong token".to_string());
+    }
+    write.write_all(b"ok\n").await.map_err(|e| e.to_string())?;
+
+    tracing::info!("Second launch handed over: {:?}", handoff.launch.args);
+    focus_main_window(app);
+    let _ = app.emit("second-instance", handoff.launch);
+    Ok(())
+}
+
+fn forward(lock: &Lock, launch: &Launch) -> Result<(), ForwardError> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, lock.port));
+    let mut stream =
+        TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).map_err(|_| ForwardError::Stale)?;
+    let handoff = Handoff {
+        token: lock.token.clone(),
+        launch: launch.clone(),
+    };
+    let mut message = serde_json::to_vec(&handoff).map_err(|_| ForwardError::Stale)?;
+    message.push(b'\n');
+
+    let unresponsive = |e: std::io::Error| {
+        ForwardError::Unresponsive(format!(
+            "Dark-GPT is already running (pid {}) but not responding: {}",
+            lock.pid, e
+        ))
+    };
+    stream
+        .set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(unresponsive)?;
+    stream.write_all(&message).map_err(unresponsive)?;
+
+    let mut reply = String::new();
+    match BufReader::new(stream.take(16)).read_line(&mut reply) {
+        Ok(_) if reply.trim() == "ok" => Ok(()),
+        Ok(_) => Err(ForwardError::Stale),
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) =>
+        {
+            Err(unresponsive(e))
+        }
+        Err(_) => Err(ForwardError::Stale),
+    }
+}
+
+/// Bind the socket and write the lock. `None` if another launch created the
+/// lock first.
+fn create(path: &Path) -> Result<Option<Instance>, String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(|e| format!("Failed to open instance socket: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to open instance socket: {}", e))?
+        .port();
+    let lock = Lock {
+        pid: std::process::id(),
+        port,
+        token: random_token(),
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {:?}: {}", dir, e))?;
+    }
+    // Written in full under a private name, then linked into place, so no
+    // other launch ever reads a half-written lock
+    let staging = path.with_extension(format!("lock.{}", lock.pid));
+    let content = serde_json::to_vec(&lock).map_err(|e| e.to_string())?;
+    // Left by a crashed launch that had our pid
+    let _ = std::fs::remove_file(&staging);
+    write_private(&staging, &content).map_err(|e| format!("Cannot write {:?}: {}", staging, e))?;
+    let linked = std::fs::hard_link(&staging, path);
+    let _ = std::fs::remove_file(&staging);
+    match linked {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(None),
+        Err(e) => return Err(format!("Cannot create {:?}: {}", path, e)),
+    }
+
+    tracing::info!("Holding instance lock {:?}", path);
+    if let Ok(mut current) = LOCK.lock() {
+        *current = Some((path.to_path_buf(), lock.clone()));
+    }
+    Ok(Some(Instance {
+        listener,
+        token: lock.token,
+    }))
+}
+
+fn read_lock(path: &Path) -> Option<Lock> {
+    let content = std::fs::read(path).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// Per user, unlike the temp dir the runtime dir falls back to elsewhere
+fn lock_path(identifier: &str) -> Result<PathBuf, String> {
+    let dir = dirs::data_local_dir().ok_or("No local data directory for this user")?;
+    Ok(dir.join(identifier).join(LOCK_FILE))
+}
+
+/// Create `path` readable by its owner only: the token in it is what lets
+/// a launch hand over arguments
+fn write_private(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content)
+}
+
+/// 256 bits from the OS CSPRNG, hex encoded
+pub(crate) fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect("the OS random number generator is unavailable");
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch() -> Launch {
+        Launch {
+            args: vec!["dark-gpt://open".to_string()],
+            cwd: None,
+        }
+    }
+
+    #[test]
+    fn lock_with_a_dead_port_is_stale() {
+        // Bind then drop to get a port nothing listens on
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let lock = Lock {
+            pid: 1,
+            port,
+            token: random_token(),
+        };
+        assert!(matches!(
+            forward(&lock, &launch()),
+            Err(ForwardError::Stale)
+        ));
+    }
+
+    #[test]
+    fn second_lock_is_refused_and_forwarded_with_the_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.lock");
+        let instance = create(&path).unwrap().expect("first lock");
+        assert!(create(&path).unwrap().is_none());
+
+        let lock = read_lock(&path).unwrap();
+        assert_eq!(lock.pid, std::process::id());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let server = std::thread::spawn(move || {
+            let (stream, _) = instance.listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let handoff: Handoff = serde_json::from_str(&line).unwrap();
+            assert_eq!(handoff.token, instance.token);
+            (&stream).write_all(b"ok\n").unwrap();
+            handoff.launch.args
+        });
+        assert!(forward(&lock, &launch()).is_ok());
+        assert_eq!(server.join().unwrap(), ["dark-gpt://open"]);
+    }
+}
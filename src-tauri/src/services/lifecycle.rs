@@ -0,0 +1,210 @@
+// App lifecycle
+// Remembers what this session started (the compose stack, `ollama serve`)
+// and applies the exit policy when the app quits. Closing the window only
+// hides it to the tray.
+// Long-running work races `cancelled()` so it stops as soon as shutdown
+// begins.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, RunEvent, WindowEvent};
+use tokio::process::Child;
+use tokio::sync::Notify;
+use tokio::time::{timeout, Duration};
+
+use crate::commands::docker;
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::services::gateway::{self, GatewayServer};
+use crate::services::host::Host;
+use crate::services::instance;
+use crate::services::settings::SettingsStore;
+use crate::services::tray::Tray;
+
+/// Upper bound for each shutdown step, so a hung engine can't keep the
+/// app from exiting
+const STEP_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// What to do with running services when the app exits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitPolicy {
+    /// Leave the stack and Ollama running
+    #[default]
+    LeaveRunning,
+    /// `compose down`, whoever started the stack
+    StopStack,
+    /// Stop the stack and `ollama serve`, but only if this session started them
+    StopAll,
+}
+
+/// Sent as `app-shutdown` when shutdown starts, so the window can say so
+#[derive(Debug, Clone, Serialize)]
+pub struct ShutdownNotice {
+    pub policy: ExitPolicy,
+}
+
+static STACK_STARTED: AtomicBool = AtomicBool::new(false);
+static OLLAMA: Mutex<Option<Child>> = Mutex::new(None);
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_DONE: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN: Notify = Notify::const_new();
+
+pub fn record_stack_started() {
+    STACK_STARTED.store(true, Ordering::SeqCst);
+}
+
+pub fn record_stack_stopped() {
+    STACK_STARTED.store(false, Ordering::SeqCst);
+}
+
+/// Keep the `ollama serve` we launched. Holding the handle rather than its
+/// pid means StopAll can only ever signal that process: until it is reaped
+/// its pid can't be reused.
+pub fn record_ollama_spawned(child: Child) {
+    if let Ok(mut current) = OLLAMA.lock() {
+        *current = Some(child);
+    }
+}
+
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Resolves once shutdown has begun
+pub async fn cancelled() {
+    // Created before the check: `notify_waiters` reaches it even unpolled
+    let notified = SHUTDOWN.notified();
+    if is_shutting_down() {
+        return;
+    }
+    notified.await;
+}
+
+/// Run `work`, abandoning it (and dropping its connections or child
+/// processes) if the app starts shutting down
+pub async fn cancellable<T>(what: &str, work: impl Future<Output = AppResult<T>>) -> AppResult<T> {
+    tokio::select! {
+        result = work => result,
+        _ = cancelled() => {
+            tracing::info!("{} cancelled by shutdown", what);
+            Err(AppError::new(
+                ErrorCode::Cancelled,
+                format!("{} cancelled: the app is closing", what),
+            ))
+        }
+    }
+}
+
+/// Hook for `App::run`: closing the window hides it to the tray, and the
+/// saved exit policy applies on exit (tray Quit is `quit` with its own
+/// policy). Without a tray, closing is the only way out, so it exits.
+pub fn on_run_event(app: &AppHandle, event: RunEvent) {
+    match event {
+        RunEvent::WindowEvent {
+            label,
+            event: WindowEvent::CloseRequested { api, .. },
+            ..
+        } if label == "main" && !SHUTDOWN_DONE.load(Ordering::SeqCst) => {
+            api.prevent_close();
+            match app.get_webview_window("main") {
+                Some(window) if app.try_state::<Tray>().is_some() && !is_shutting_down() => {
+                    let _ = window.hide();
+                }
+                _ => quit(app, saved_policy(app)),
+            }
+        }
+        RunEvent::ExitRequested { api, .. } if !SHUTDOWN_DONE.load(Ordering::SeqCst) => {
+            api.prevent_exit();
+            quit(app, saved_policy(app));
+        }
+        RunEvent::Exit => instance::release(),
+        _ => {}
+    }
+}
+
+/// Shut down with `policy`, then exit. Later calls while shutting down are
+/// ignored.
+pub fn quit(app: &AppHandle, policy: ExitPolicy) {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    SHUTDOWN.notify_waiters();
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tracing::info!("Shutting down ({:?})", policy);
+        let _ = app.emit("app-shutdown", ShutdownNotice { policy });
+        shutdown(&app, policy).await;
+        SHUTDOWN_DONE.store(true, Ordering::SeqCst);
+        app.exit(0);
+    });
+}
+
+fn saved_policy(app: &AppHandle) -> ExitPolicy {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().exit_policy)
+        .unwrap_or_default()
+}
+
+async fn shutdown(app: &AppHandle, policy: ExitPolicy) {
+    let stop_stack = match policy {
+        ExitPolicy::LeaveRunning => false,
+        ExitPolicy::StopStack => true,
+        ExitPolicy::StopAll => STACK_STARTED.load(Ordering::SeqCst),
+    };
+    // The gateway dies with the app; a webui left running must not keep
+    // pointing at it
+    if !stop_stack && gateway::webui_endpoint().is_some() {
+        if let Some(server) = app.try_state::<GatewayServer>() {
+            server.stop();
+        }
+        match timeout(STEP_TIMEOUT, docker::recreate_services(app, &["webui"])).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!("Failed to route Open-WebUI back to Ollama: {}", e),
+            Err(_) => tracing::error!("Routing Open-WebUI back to Ollama timed out"),
+        }
+    }
+    if stop_stack {
+        match timeout(STEP_TIMEOUT, docker::stop_services(app.clone())).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!("Failed to stop services on exit: {}", e.describe()),
+            Err(_) => tracing::error!("Stopping services on exit timed out, leaving them"),
+        }
+    }
+
+    if policy == ExitPolicy::StopAll {
+        let child = OLLAMA.lock().ok().and_then(|mut child| child.take());
+        if let Some(child) = child {
+            match timeout(STEP_TIMEOUT, stop_ollama(child)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => tracing::error!("Failed to stop Ollama on exit: {}", e),
+                Err(_) => tracing::error!("Stopping Ollama on exit timed out"),
+            }
+        }
+    }
+}
+
+async fn stop_ollama(mut child: Child) -> Result<(), String> {
+    if child.try_wait().map_err(|e| e.to_string())?.is_some() {
+        tracing::info!("`ollama serve` had already exited");
+        return Ok(());
+    }
+    tracing::info!("Stopping `ollama serve` (pid {:?})", child.id());
+    // SIGTERM first so Ollama can unload models; Windows has no equivalent
+    if let (false, Some(pid)) = (cfg!(target_os = "windows"), child.id()) {
+        let term = Host::system()
+            .command("kill")
+            .arg(pid.to_string())
+            .output()
+            .await;
+        if term.is_ok_and(|o| o.status.success())
+            && timeout(Duration::from_secs(5), child.wait()).await.is_ok()
+        {
+            return Ok(());
+        }
+    }
+    child.kill().await.map_err(|e| e.to_string())
+}
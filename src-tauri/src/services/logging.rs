@@ -0,0 +1,354 @@
+// Logging
+// stdout, the in-memory buffer used by diagnostics, and daily JSON log
+// files in the app log dir. The level can be changed while running, and
+// the files can be read back and filtered for the in-app log viewer.
+
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{fmt, prelude::*, reload, EnvFilter, Registry};
+
+use crate::utils::log_buffer::RecentLogs;
+
+/// Files are named `dark-gpt.<yyyy-mm-dd>.log`
+const FILE_PREFIX: &str = "dark-gpt";
+const FILE_SUFFIX: &str = "log";
+/// One file per day, a week kept
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+
+    fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_lowercase().as_str() {
+            "error" => Some(LogLevel::Error),
+            "warn" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            "trace" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// Filters for `read`. Everything is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    /// Include this level and anything more severe
+    pub level: Option<LogLevel>,
+    /// Substring of the target, e.g. `docker` or `dark_gpt_lib::services`
+    pub target: Option<String>,
+    /// Unix seconds, inclusive
+    pub since: Option<u64>,
+    /// Unix seconds, exclusive
+    pub until: Option<u64>,
+    /// Newest entries to return (default 500)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    /// RFC 3339, UTC
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+    /// Structured fields other than the message
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A line as written by the JSON formatter
+#[derive(Deserialize)]
+struct RawEntry {
+    timestamp: String,
+    level: String,
+    target: String,
+    #[serde(default)]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static LEVEL: Mutex<LogLevel> = Mutex::new(LogLevel::Debug);
+
+/// Install the global subscriber. Without a log dir (or if it can't be
+/// created) only stdout and the in-memory buffer are used.
+pub fn init(log_dir: Option<&Path>, recent_logs: RecentLogs) {
+    let (filter, handle) = reload::Layer::new(filter_for(LogLevel::Debug));
+    let _ = FILTER.set(handle);
+
+    let file = log_dir.map(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(FILE_PREFIX)
+            .filename_suffix(FILE_SUFFIX)
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+    });
+    let (file, file_error) = match file {
+        Some(Ok(appender)) => (Some(appender), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .with(fmt::layer().with_ansi(false).with_writer(recent_logs))
+        .with(file.map(|file| fmt::layer().json().with_ansi(false).with_writer(file)))
+        .init();
+
+    match (log_dir, file_error) {
+        (Some(dir), None) => tracing::info!("Writing logs to {:?}", dir),
+        (Some(dir), Some(e)) => tracing::warn!("Cannot write logs to {:?}: {}", dir, e),
+        (None, _) => tracing::warn!("No log directory, logging to stdout only"),
+    }
+}
+
+/// Current level for the app's own targets
+pub fn level() -> LogLevel {
+    LEVEL.lock().map(|level| *level).unwrap_or(LogLevel::Debug)
+}
+
+/// Change the level for the app's own targets. `RUST_LOG` still applies
+/// to everything else.
+pub fn set_level(level: LogLevel) -> Result<(), String> {
+    let handle = FILTER.get().ok_or("Logging is not initialized")?;
+    handle
+        .reload(filter_for(level))
+        .map_err(|e| format!("Failed to change log level: {}", e))?;
+    if let Ok(mut current) = LEVEL.lock() {
+        *current = level;
+    }
+    tracing::info!("Log level set to {}", level.as_str());
+    Ok(())
+}
+
+fn filter_for(level: LogLevel) -> EnvFilter {
+    let directive = format!("dark_gpt={}", level.as_str());
+    EnvFilter::from_default_env().add_directive(directive.parse().expect("valid directive"))
+}
+
+/// Matching entries from the log files, oldest first, at most `limit` of
+/// the newest
+pub fn read(log_dir: &Path, query: &LogQuery) -> Result<Vec<LogEntry>, String> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    let mut files: Vec<_> = match std::fs::read_dir(log_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_log_file(path))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("Cannot read {:?}: {}", log_dir, e)),
+    };
+    // The date in the name sorts them; newest first
+    files.sort();
+    files.reverse();
+
+    let mut entries = Vec::new();
+    'files: for file in files {
+        let content = match std::fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::warn!("Skipping log file {:?}: {}", file, e);
+                continue;
+            }
+        };
+        for line in content.lines().rev() {
+            if entries.len() >= limit {
+                break 'files;
+            }
+            if let Some(entry) = parse_line(line).filter(|entry| query.matches(entry)) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+fn is_log_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.starts_with(&format!("{}.", FILE_PREFIX))
+                && name.ends_with(&format!(".{}", FILE_SUFFIX))
+        })
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let raw: RawEntry = serde_json::from_str(line).ok()?;
+    let mut fields = raw.fields;
+    let message = match fields.remove("message") {
+        Some(serde_json::Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    Some(LogEntry {
+        level: LogLevel::parse(&raw.level)?,
+        timestamp: raw.timestamp,
+        target: raw.target,
+        message,
+        fields,
+    })
+}
+
+impl LogQuery {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if self.level.is_some_and(|level| entry.level > level) {
+            return false;
+        }
+        if let Some(target) = &self.target {
+            if !entry.target.contains(target.as_str()) {
+                return false;
+            }
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Some(time) = unix_seconds(&entry.timestamp) else {
+                return false;
+            };
+            if self.since.is_some_and(|since| time < since)
+                || self.until.is_some_and(|until| time >= until)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Seconds since the epoch for `YYYY-MM-DDTHH:MM:SS...Z`, the formatter's
+/// UTC timestamp
+fn unix_seconds(timestamp: &str) -> Option<u64> {
+    let number = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+
+    // Days from civil date (Howard Hinnant's algorithm)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    u64::try_from(days * 86_400 + hour * 3_600 + minute * 60 + second).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_and_filters_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let line = |time: &str, level: &str, target: &str, message: &str| {
+            format!(
+                r#"{{"timestamp":"{}","level":"{}","fields":{{"message":"{}","port":3000}},"target":"{}"}}"#,
+                time, level, message, target
+            )
+        };
+        let older = [
+            line(
+                "2026-10-18T23:59:59.000001Z",
+                "INFO",
+                "dark_gpt_lib::commands::docker",
+                "started",
+            ),
+            line(
+                "2026-10-18T23:59:59.500000Z",
+                "DEBUG",
+                "dark_gpt_lib::services::tray",
+                "refresh",
+            ),
+        ];
+        let newer = [
+            "not json".to_string(),
+            line(
+                "2026-10-19T08:00:00.000000Z",
+                "WARN",
+                "dark_gpt_lib::commands::docker",
+                "port busy",
+            ),
+            line(
+                "2026-10-19T09:30:00.000000Z",
+                "ERROR",
+                "dark_gpt_lib::commands::ollama",
+                "pull failed",
+            ),
+        ];
+        std::fs::write(dir.path().join("dark-gpt.2026-10-18.log"), older.join("\n")).unwrap();
+        std::fs::write(dir.path().join("dark-gpt.2026-10-19.log"), newer.join("\n")).unwrap();
+        std::fs::write(dir.path().join("dark-gpt-crash.log"), "boom").unwrap();
+
+        let messages = |query: LogQuery| -> Vec<String> {
+            read(dir.path(), &query)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.message)
+                .collect()
+        };
+
+        assert_eq!(
+            messages(LogQuery::default()),
+            ["started", "refresh", "port busy", "pull failed"]
+        );
+        assert_eq!(
+            messages(LogQuery {
+                level: Some(LogLevel::Warn),
+                ..Default::default()
+            }),
+            ["port busy", "pull failed"]
+        );
+        assert_eq!(
+            messages(LogQuery {
+                target: Some("docker".to_string()),
+                ..Default::default()
+            }),
+            ["started", "port busy"]
+        );
+        // 2026-10-19T00:00:00Z .. 09:00:00Z
+        assert_eq!(
+            messages(LogQuery {
+                since: Some(1_792_368_000),
+                until: Some(1_792_400_400),
+                ..Default::default()
+            }),
+            ["port busy"]
+        );
+        assert_eq!(
+            messages(LogQuery {
+                limit: Some(3),
+                ..Default::default()
+            }),
+            ["refresh", "port busy", "pull failed"]
+        );
+
+        let entry = &read(dir.path(), &LogQuery::default()).unwrap()[0];
+        assert_eq!(entry.level, LogLevel::Info);
+        assert_eq!(entry.fields.get("port"), Some(&serde_json::json!(3000)));
+    }
+}
@@ -0,0 +1,430 @@
+// Prometheus metrics
+// Counters shared with the commands, plus an optional /metrics server
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use serde::Serialize;
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+
+use crate::commands::health::{self, HealthReport, HealthStatus, HealthTimings, ServiceHealth};
+use crate::services::host::Host;
+
+pub const DEFAULT_METRICS_PORT: u16 = 9465;
+
+/// Counters updated by the commands. Held in Tauri state.
+#[derive(Clone)]
+pub struct Metrics {
+    started_at: Instant,
+    pull_bytes: Arc<Mutex<BTreeMap<String, u64>>>,
+    pulls: Arc<Mutex<BTreeMap<(String, &'static str), u64>>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            pull_bytes: Arc::default(),
+            pulls: Arc::default(),
+        }
+    }
+}
+
+impl Metrics {
+    /// Add downloaded bytes to a model's pull counter
+    pub fn add_pull_bytes(&self, model: &str, bytes: u64) {
+        if let Ok(mut counters) = self.pull_bytes.lock() {
+            *counters.entry(model.to_string()).or_default() += bytes;
+        }
+    }
+
+    /// Count a finished pull, `result` being "success" or "error"
+    pub fn record_pull(&self, model: &str, result: &'static str) {
+        if let Ok(mut counters) = self.pulls.lock() {
+            *counters.entry((model.to_string(), result)).or_default() += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsStatus {
+    pub running: bool,
+    pub address: Option<String>,
+}
+
+/// Handle to the running /metrics server, if any. Held in Tauri state.
+#[derive(Default)]
+pub struct MetricsServer {
+    running: Mutex<Option<(SocketAddr, oneshot::Sender<()>)>>,
+}
+
+impl MetricsServer {
+    pub fn status(&self) -> MetricsStatus {
+        let addr = self
+            .running
+            .lock()
+            .ok()
+            .and_then(|r| r.as_ref().map(|(addr, _)| *addr));
+        MetricsStatus {
+            running: addr.is_some(),
+            address: addr.map(|a| format!("http://{}/metrics", a)),
+        }
+    }
+
+    /// Bind `bind_address:port` and serve until `stop` is called.
+    /// Restarts the server if it is already running.
+    pub async fn start(
+        &self,
+        metrics: Metrics,
+        host: Host,
+        bind_address: &str,
+        port: u16,
+    ) -> Result<MetricsStatus, String> {
+        self.stop();
+
+        let listener = tokio::net::TcpListener::bind((bind_address, port))
+            .await
+            .map_err(|e| {
+                format!(
+                    "Cannot bind metrics server on {}:{}: {}",
+                    bind_address, port, e
+                )
+            })?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("Metrics server address error: {}", e))?;
+
+        let app = Router::new()
+            .route("/metrics", get(serve_metrics))
+            .with_state((metrics, host));
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            let server = axum::serve(listener, app).with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            });
+            if let Err(e) = server.await {
+                tracing::error!("Metrics server stopped: {}", e);
+            }
+        });
+
+        tracing::info!("Metrics server listening on http://{}/metrics", addr);
+        if let Ok(mut running) = self.running.lock() {
+            *running = Some((addr, shutdown_tx));
+        }
+        Ok(self.status())
+    }
+
+    pub fn stop(&self) {
+        if let Some((addr, shutdown)) = self.running.lock().ok().and_then(|mut r| r.take()) {
+            let _ = shutdown.send(());
+            tracing::info!("Metrics server on {} stopped", addr);
+        }
+    }
+}
+
+async fn serve_metrics(State((metrics, host)): State<(Metrics, Host)>) -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        render(&metrics, &host).await,
+    )
+}
+
+/// What a scrape samples fresh, next to the counters
+struct Scrape {
+    uptime: Duration,
+    health: Option<(HealthReport, HealthTimings)>,
+    /// (name, size, size_vram); None when Ollama doesn't answer
+    loaded: Option<Vec<(String, u64, u64)>>,
+}
+
+/// Render all metrics in the Prometheus text exposition format.
+/// Health checks and loaded models are sampled fresh on every scrape.
+async fn render(metrics: &Metrics, host: &Host) -> String {
+    let scrape = Scrape {
+        uptime: metrics.started_at.elapsed(),
+        health: health::collect_health(host).await.ok(),
+        loaded: loaded_models(host).await,
+    };
+    exposition(metrics, &scrape)
+}
+
+fn exposition(metrics: &Metrics, scrape: &Scrape) -> String {
+    let mut out = String::new();
+
+    family(
+        &mut out,
+        "darkgpt_app_info",
+        "gauge",
+        "Dark-GPT desktop app version.",
+    );
+    sample(
+        &mut out,
+        "darkgpt_app_info",
+        &[("version", env!("CARGO_PKG_VERSION"))],
+        1,
+    );
+
+    family(
+        &mut out,
+        "darkgpt_app_uptime_seconds",
+        "gauge",
+        "Seconds since the app started.",
+    );
+    sample(
+        &mut out,
+        "darkgpt_app_uptime_seconds",
+        &[],
+        format!("{:.3}", scrape.uptime.as_secs_f64()),
+    );
+
+    if let Some((report, timings)) = &scrape.health {
+        let services: [(&str, &ServiceHealth, Duration); 4] = [
+            ("docker", &report.docker, timings.docker),
+            ("ollama", &report.ollama, timings.ollama),
+            ("webui", &report.webui, timings.webui),
+            ("caddy", &report.caddy, timings.caddy),
+        ];
+
+        family(
+            &mut out,
+            "darkgpt_service_healthy",
+            "gauge",
+            "Whether the service passed its health check (1) or not (0).",
+        );
+        for (service, health, _) in &services {
+            let (value, status) = match health.status {
+                HealthStatus::Healthy => (1, "healthy"),
+                HealthStatus::Unhealthy => (0, "unhealthy"),
+                HealthStatus::Unknown => (0, "unknown"),
+            };
+            sample(
+                &mut out,
+                "darkgpt_service_healthy",
+                &[("service", service), ("status", status)],
+                value,
+            );
+        }
+
+        family(
+            &mut out,
+            "darkgpt_health_check_duration_seconds",
+            "gauge",
+            "Duration of the health check run for this scrape.",
+        );
+        for (service, _, duration) in &services {
+            sample(
+                &mut out,
+                "darkgpt_health_check_duration_seconds",
+                &[("service", service)],
+                format!("{:.6}", duration.as_secs_f64()),
+            );
+        }
+    }
+
+    family(
+        &mut out,
+        "darkgpt_model_pull_bytes_total",
+        "counter",
+        "Bytes downloaded by model pulls since the app started.",
+    );
+    if let Ok(counters) = metrics.pull_bytes.lock() {
+        for (model, bytes) in counters.iter() {
+            sample(
+                &mut out,
+                "darkgpt_model_pull_bytes_total",
+                &[("model", model)],
+                bytes,
+            );
+        }
+    }
+
+    family(
+        &mut out,
+        "darkgpt_model_pulls_total",
+        "counter",
+        "Finished model pulls by result.",
+    );
+    if let Ok(counters) = metrics.pulls.lock() {
+        for ((model, result), count) in counters.iter() {
+            sample(
+                &mut out,
+                "darkgpt_model_pulls_total",
+                &[("model", model), ("result", result)],
+                count,
+            );
+        }
+    }
+
+    if let Some(loaded) = &scrape.loaded {
+        family(
+            &mut out,
+            "darkgpt_ollama_loaded_models",
+            "gauge",
+            "Models currently loaded in Ollama memory.",
+        );
+        sample(&mut out, "darkgpt_ollama_loaded_models", &[], loaded.len());
+
+        family(
+            &mut out,
+            "darkgpt_ollama_loaded_model_size_bytes",
+            "gauge",
+            "Memory used by a loaded model.",
+        );
+        for (model, size, _) in loaded {
+            sample(
+                &mut out,
+                "darkgpt_ollama_loaded_model_size_bytes",
+                &[("model", model)],
+                size,
+            );
+        }
+
+        family(
+            &mut out,
+            "darkgpt_ollama_loaded_model_vram_bytes",
+            "gauge",
+            "VRAM used by a loaded model.",
+        );
+        for (model, _, vram) in loaded {
+            sample(
+                &mut out,
+                "darkgpt_ollama_loaded_model_vram_bytes",
+                &[("model", model)],
+                vram,
+            );
+        }
+    }
+
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+    let labels = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+        .collect::<Vec<_>>()
+        .join(",");
+    if labels.is_empty() {
+        let _ = writeln!(out, "{} {}", name, value);
+    } else {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+    }
+}
+
+/// (name, size, size_vram) of the models Ollama has in memory, from /api/ps
+async fn loaded_models(host: &Host) -> Option<Vec<(String, u64, u64)>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .ok()?;
+    let data: serde_json::Value = client
+        .get(host.ollama("/api/ps"))
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+
+    Some(
+        data["models"]
+            .as_array()?
+            .iter()
+            .filter_map(|m| {
+                Some((
+                    m["name"].as_str()?.to_string(),
+                    m["size"].as_u64().unwrap_or(0),
+                    m["size_vram"].as_u64().unwrap_or(0),
+                ))
+            })
+            .collect(),
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPOSITION: &str = include_str!("../../tests/fixtures/metrics/exposition.prom");
+
+    fn health(name: &str, status: HealthStatus) -> ServiceHealth {
+        ServiceHealth {
+            name: name.to_string(),
+            status,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn renders_the_exposition_format() {
+        let metrics = Metrics::default();
+        metrics.add_pull_bytes("dolphin-phi", 1024 * 1024);
+        metrics.add_pull_bytes("hf.co/some\\repo:\"q4\"\nx", 42);
+        metrics.record_pull("dolphin-phi", "success");
+        metrics.record_pull("dolphin-phi", "error");
+        metrics.record_pull("dolphin-phi", "error");
+
+        let report = HealthReport {
+            docker: health("Docker", HealthStatus::Healthy),
+            ollama: health("Ollama", HealthStatus::Unhealthy),
+            webui: health("Open-WebUI", HealthStatus::Unknown),
+            caddy: health("Caddy", HealthStatus::Healthy),
+        };
+        let timings = HealthTimings {
+            docker: Duration::from_micros(12_500),
+            ollama: Duration::from_secs(2),
+            webui: Duration::ZERO,
+            caddy: Duration::from_millis(250),
+        };
+        let scrape = Scrape {
+            uptime: Duration::from_millis(12_500),
+            health: Some((report, timings)),
+            loaded: Some(vec![(
+                "dolphin-phi:latest".to_string(),
+                2_000_000_000,
+                1_500_000_000,
+            )]),
+        };
+
+        assert_eq!(
+            exposition(&metrics, &scrape),
+            EXPOSITION.replace("{version}", env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    #[test]
+    fn leaves_out_what_could_not_be_sampled() {
+        let scrape = Scrape {
+            uptime: Duration::ZERO,
+            health: None,
+            loaded: None,
+        };
+        let out = exposition(&Metrics::default(), &scrape);
+        assert!(!out.contains("darkgpt_service_healthy"));
+        assert!(!out.contains("darkgpt_ollama_loaded_models"));
+        // Counters are always declared, even before the first pull
+        assert!(out.contains("# TYPE darkgpt_model_pulls_total counter\n"));
+        assert!(out.contains("darkgpt_app_uptime_seconds 0.000\n"));
+    }
+}
@@ -1,6 +1,21 @@
 // Services module
 // Background services and helpers
 
+pub mod audit;
+pub mod auto_heal;
+pub mod container;
+pub mod gateway;
+pub mod host;
+pub mod instance;
+pub mod lifecycle;
+pub mod logging;
+pub mod metrics;
+pub mod profiles;
+pub mod settings;
+pub mod setup_wizard;
+pub mod tray;
+pub mod updates;
+
 // TODO: Add services as needed
 // pub mod docker_manager;
 // pub mod ollama_client;
@@ -0,0 +1,313 @@
+// Environment profiles
+// Each profile is its own compose project (containers, network and volumes
+// are prefixed with the project name) with its own ports, hostname, default
+// model and Ollama endpoint. The active one is held by the `Host`
+// (`Host::profile`), so every compose call and health check uses it.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::gateway;
+use super::host::DEFAULT_OLLAMA_URL;
+use super::settings::REDACTED_SECRET;
+use crate::commands::ollama::backend::BackendKind;
+
+pub const DEFAULT_PROFILE: &str = "default";
+/// Project of the default profile; matches the container names used before
+/// profiles existed
+pub const DEFAULT_PROJECT: &str = "dark-gpt";
+pub const DEFAULT_HOSTNAME: &str = "dark-gpt.local";
+/// What compose named volumes before projects were set explicitly (the
+/// bundled file's directory); the default profile keeps using them
+const LEGACY_VOLUME_PREFIX: &str = "docker";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    /// Compose project name
+    pub project: String,
+    /// Host ports Caddy publishes
+    pub https_port: u16,
+    pub http_port: u16,
+    /// Served by Caddy; needs a hosts entry and a certificate
+    pub hostname: String,
+    pub default_model: String,
+    /// Model server API root as seen from this machine
+    pub ollama_url: String,
+    /// What speaks at `ollama_url`
+    pub backend: BackendKind,
+    /// Bearer token for OpenAI-compatible servers started with `--api-key`
+    pub api_key: Option<String>,
+}
+
+impl Profile {
+    /// This profile with its API key masked, for the webview
+    pub fn redacted(&self) -> Self {
+        Self {
+            api_key: self.api_key.as_ref().map(|_| REDACTED_SECRET.to_string()),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE.to_string(),
+            project: DEFAULT_PROJECT.to_string(),
+            https_port: 443,
+            http_port: 80,
+            hostname: DEFAULT_HOSTNAME.to_string(),
+            default_model: "dolphin-llama3:8b".to_string(),
+            ollama_url: DEFAULT_OLLAMA_URL.to_string(),
+            backend: BackendKind::Ollama,
+            api_key: None,
+        }
+    }
+}
+
+impl Profile {
+    /// A copy named `name`, in a project of its own
+    pub fn clone_as(&self, name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            project: format!("{}-{}", DEFAULT_PROJECT, name.to_lowercase()),
+            ..self.clone()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), (&'static str, &'static str)> {
+        let name_ok = |s: &str| {
+            !s.is_empty()
+                && s.len() <= 32
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        };
+        if !name_ok(&self.name) {
+            return Err((
+                "name",
+                "must be 1-32 letters, digits, dashes or underscores",
+            ));
+        }
+        // Compose's own rule for project names
+        if !name_ok(&self.project)
+            || self.project.starts_with(['-', '_'])
+            || self.project.chars().any(|c| c.is_ascii_uppercase())
+        {
+            return Err((
+                "project",
+                "must be lowercase letters, digits, dashes or underscores",
+            ));
+        }
+        if self.https_port == 0 || self.http_port == 0 {
+            return Err(("https_port", "ports must not be 0"));
+        }
+        if self.https_port == self.http_port {
+            return Err(("http_port", "must differ from https_port"));
+        }
+        if self.hostname.is_empty()
+            || !self
+                .hostname
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        {
+            return Err((
+                "hostname",
+                "may only contain letters, digits, dots and dashes",
+            ));
+        }
+        let model = self.default_model.trim();
+        if model.is_empty() {
+            return Err(("default_model", "must not be empty"));
+        }
+        if !model
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._:/-".contains(c))
+        {
+            return Err((
+                "default_model",
+                "may only contain letters, digits and . _ : / -",
+            ));
+        }
+        if !(self.ollama_url.starts_with("http://") || self.ollama_url.starts_with("https://")) {
+            return Err(("ollama_url", "must be an http:// or https:// URL"));
+        }
+        Ok(())
+    }
+
+    /// Whether both profiles' stacks can't run at the same time
+    pub fn clashes_with(&self, other: &Profile) -> bool {
+        let ports = [self.https_port, self.http_port];
+        ports.contains(&other.https_port) || ports.contains(&other.http_port)
+    }
+
+    /// The WebUI through Caddy
+    pub fn web_url(&self) -> String {
+        match self.https_port {
+            443 => format!("https://{}", self.hostname),
+            port => format!("https://{}:{}", self.hostname, port),
+        }
+    }
+
+    /// Environment for every compose call on this profile's project, with
+    /// Caddy's certificates mounted from `certs_dir` when known.
+    /// Keep in sync with `services_affected_by` in commands/settings.rs.
+    pub fn compose_env(&self, certs_dir: Option<&Path>) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("COMPOSE_PROJECT_NAME", self.project.clone()),
+            ("HTTPS_PORT", self.https_port.to_string()),
+            ("HTTP_PORT", self.http_port.to_string()),
+            ("DARK_GPT_HOSTNAME", self.hostname.clone()),
+            ("DEFAULT_MODELS", self.default_model.clone()),
+            ("VOLUME_PREFIX", self.volume_prefix().to_string()),
+        ];
+        if let Some(dir) = certs_dir {
+            env.push(("DARK_GPT_CERTS_DIR", dir.display().to_string()));
+        }
+        let url = container_url(&self.ollama_url);
+        match self.backend {
+            BackendKind::Ollama => env.extend([
+                ("ENABLE_OLLAMA_API", "true".to_string()),
+                // Through the gateway while it runs
+                ("OLLAMA_BASE_URL", gateway::webui_endpoint().unwrap_or(url)),
+            ]),
+            // Open WebUI wants the OpenAI base URL with the /v1
+            BackendKind::OpenAi => env.extend([
+                ("ENABLE_OLLAMA_API", "false".to_string()),
+                ("ENABLE_OPENAI_API", "true".to_string()),
+                (
+                    "OPENAI_API_BASE_URL",
+                    format!("{}/v1", url.trim_end_matches('/')),
+                ),
+                ("OPENAI_API_KEY", self.api_key.clone().unwrap_or_default()),
+            ]),
+        }
+        env
+    }
+
+    fn volume_prefix(&self) -> &str {
+        match self.project.as_str() {
+            DEFAULT_PROJECT => LEGACY_VOLUME_PREFIX,
+            project => project,
+        }
+    }
+}
+
+/// The same URL as seen from inside a container: loopback becomes the host
+fn container_url(url: &str) -> String {
+    for local in ["localhost", "127.0.0.1"] {
+        for scheme in ["http://", "https://"] {
+            let prefix = format!("{}{}", scheme, local);
+            if let Some(rest) = url.strip_prefix(&prefix) {
+                if rest.is_empty() || rest.starts_with([':', '/']) {
+                    return format!("{}host.docker.internal{}", scheme, rest);
+                }
+            }
+        }
+    }
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_into_a_separate_project() {
+        let lab = Profile::default().clone_as("Lab");
+        assert_eq!(lab.project, "dark-gpt-lab");
+        assert_eq!(lab.default_model, Profile::default().default_model);
+        assert!(lab.validate().is_ok());
+        assert!(lab.clashes_with(&Profile::default()));
+
+        let demo = Profile {
+            https_port: 8443,
+            http_port: 8080,
+            hostname: "demo.dark-gpt.local".to_string(),
+            ..lab.clone_as("demo")
+        };
+        assert!(!demo.clashes_with(&lab));
+        assert_eq!(demo.web_url(), "https://demo.dark-gpt.local:8443");
+        assert_eq!(Profile::default().web_url(), "https://dark-gpt.local");
+    }
+
+    #[test]
+    fn rejects_invalid_profiles() {
+        let invalid = |profile: Profile| profile.validate().unwrap_err().0;
+        let base = Profile::default();
+        assert_eq!(
+            invalid(Profile {
+                name: "lab setup".into(),
+                ..base.clone()
+            }),
+            "name"
+        );
+        assert_eq!(
+            invalid(Profile {
+                project: "Dark-GPT".into(),
+                ..base.clone()
+            }),
+            "project"
+        );
+        assert_eq!(
+            invalid(Profile {
+                http_port: 443,
+                ..base.clone()
+            }),
+            "http_port"
+        );
+        assert_eq!(
+            invalid(Profile {
+                ollama_url: "localhost:11434".into(),
+                ..base
+            }),
+            "ollama_url"
+        );
+    }
+
+    #[test]
+    fn compose_env_points_containers_at_the_host() {
+        let env = |profile: &Profile, key: &str| {
+            profile
+                .compose_env(None)
+                .into_iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v)
+                .unwrap()
+        };
+        let profile = Profile::default();
+        assert_eq!(
+            env(&profile, "OLLAMA_BASE_URL"),
+            "http://host.docker.internal:11434"
+        );
+        assert_eq!(env(&profile, "COMPOSE_PROJECT_NAME"), "dark-gpt");
+        assert_eq!(env(&profile, "VOLUME_PREFIX"), "docker");
+        assert_eq!(
+            env(&profile.clone_as("lab"), "VOLUME_PREFIX"),
+            "dark-gpt-lab"
+        );
+
+        let remote = Profile {
+            ollama_url: "http://gpu-box.lan:11434".into(),
+            ..Profile::default()
+        };
+        assert_eq!(env(&remote, "OLLAMA_BASE_URL"), "http://gpu-box.lan:11434");
+
+        let vllm = Profile {
+            ollama_url: "http://localhost:8000/".into(),
+            backend: BackendKind::OpenAi,
+            ..Profile::default()
+        };
+        assert_eq!(env(&vllm, "ENABLE_OLLAMA_API"), "false");
+        assert_eq!(
+            env(&vllm, "OPENAI_API_BASE_URL"),
+            "http://host.docker.internal:8000/v1"
+        );
+        assert_eq!(
+            container_url("http://localhost.example:1"),
+            "http://localhost.example:1"
+        );
+    }
+}
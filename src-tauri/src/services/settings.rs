@@ -0,0 +1,550 @@
+// Settings service
+// Versioned settings.json with serde defaults, migrations, validation and
+// atomic writes
+
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use super::auto_heal::AutoHealPolicy;
+use super::container::RuntimePreference;
+use super::gateway::GatewaySettings;
+use super::lifecycle::ExitPolicy;
+use super::metrics::DEFAULT_METRICS_PORT;
+use super::profiles::{Profile, DEFAULT_PROFILE};
+use super::updates::UpdateSettings;
+use crate::utils::fs;
+
+/// Bump when the on-disk shape changes, and add a step to `MIGRATIONS`
+pub const SCHEMA_VERSION: u32 = 2;
+pub(crate) const SETTINGS_FILE: &str = "settings.json";
+/// Stands in for a secret sent to the webview; saving it back keeps the
+/// stored value
+pub const REDACTED_SECRET: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub schema_version: u32,
+    /// Named environments; see services/profiles.rs
+    pub profiles: Vec<Profile>,
+    pub active_profile: String,
+    pub auto_start_services: bool,
+    /// Check for updates on a schedule (see `updates`)
+    pub check_updates: bool,
+    /// Never contact the network on our own, including update checks
+    pub offline: bool,
+    pub updates: UpdateSettings,
+    pub metrics: MetricsSettings,
+    /// Authenticated proxy in front of Ollama; see services/gateway.rs
+    pub gateway: GatewaySettings,
+    pub auto_heal: AutoHealPolicy,
+    /// Docker, Podman, or whichever is installed
+    pub container_runtime: RuntimePreference,
+    /// What happens to running services when the app closes
+    pub exit_policy: ExitPolicy,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            profiles: vec![Profile::default()],
+            active_profile: DEFAULT_PROFILE.to_string(),
+            auto_start_services: false,
+            check_updates: true,
+            offline: false,
+            updates: UpdateSettings::default(),
+            metrics: MetricsSettings::default(),
+            gateway: GatewaySettings::default(),
+            auto_heal: AutoHealPolicy::default(),
+            container_runtime: RuntimePreference::default(),
+            exit_policy: ExitPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    /// Loopback by default: the metrics are unauthenticated. On Linux the
+    /// bundled Prometheus reaches the host through the Docker bridge, so it
+    /// needs the bridge's gateway address here (see prometheus.yml).
+    pub bind_address: String,
+    pub port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1".to_string(),
+            port: DEFAULT_METRICS_PORT,
+        }
+    }
+}
+
+impl AppSettings {
+    /// These settings with profile API keys and the webui's gateway key
+    /// masked, for the webview
+    pub fn redacted(&self) -> Self {
+        self.map_secrets(|_| Some(REDACTED_SECRET.to_string()))
+    }
+
+    /// These settings with every secret removed, for backups
+    pub fn without_secrets(&self) -> Self {
+        self.map_secrets(|_| None)
+    }
+
+    fn map_secrets(&self, f: impl Fn(&String) -> Option<String>) -> Self {
+        let mut settings = self.clone();
+        for profile in &mut settings.profiles {
+            profile.api_key = profile.api_key.as_ref().and_then(&f);
+        }
+        settings.gateway.webui_api_key = settings.gateway.webui_api_key.as_ref().and_then(&f);
+        settings
+    }
+
+    /// Put back secrets that came back from the webview masked
+    fn restore_secrets(&mut self, current: &AppSettings) {
+        let masked = |secret: &Option<String>| secret.as_deref() == Some(REDACTED_SECRET);
+        if masked(&self.gateway.webui_api_key) {
+            self.gateway.webui_api_key = current.gateway.webui_api_key.clone();
+        }
+        for profile in &mut self.profiles {
+            if masked(&profile.api_key) {
+                profile.api_key = current
+                    .profiles
+                    .iter()
+                    .find(|p| p.name == profile.name)
+                    .and_then(|p| p.api_key.clone());
+            }
+        }
+    }
+
+    /// The active profile (validation guarantees there is one)
+    pub fn profile(&self) -> Profile {
+        self.profiles
+            .iter()
+            .find(|p| p.name == self.active_profile)
+            .or(self.profiles.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Check every field; on error names the offending one
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        for (i, profile) in self.profiles.iter().enumerate() {
+            profile.validate().map_err(|(field, message)| {
+                SettingsError::invalid(&format!("profiles.{}.{}", i, field), message)
+            })?;
+            let earlier = &self.profiles[..i];
+            if earlier.iter().any(|p| p.name == profile.name) {
+                return Err(SettingsError::invalid(
+                    &format!("profiles.{}.name", i),
+                    "is already used by another profile",
+                ));
+            }
+            if earlier.iter().any(|p| p.project == profile.project) {
+                return Err(SettingsError::invalid(
+                    &format!("profiles.{}.project", i),
+                    "is already used by another profile",
+                ));
+            }
+        }
+        if !self.profiles.iter().any(|p| p.name == self.active_profile) {
+            return Err(SettingsError::invalid(
+                "active_profile",
+                "must name one of the profiles",
+            ));
+        }
+        if self.metrics.bind_address.parse::<IpAddr>().is_err() {
+            return Err(SettingsError::invalid(
+                "metrics.bind_address",
+                "must be an IP address",
+            ));
+        }
+        if self.metrics.port < 1024 {
+            return Err(SettingsError::invalid(
+                "metrics.port",
+                "must be 1024 or higher",
+            ));
+        }
+        self.gateway.validate().map_err(|(field, message)| {
+            SettingsError::invalid(&format!("gateway.{}", field), message)
+        })?;
+        self.updates.validate().map_err(|(field, message)| {
+            SettingsError::invalid(&format!("updates.{}", field), message)
+        })?;
+        self.auto_heal.validate().map_err(|(field, message)| {
+            SettingsError::invalid(&format!("auto_heal.{}", field), message)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SettingsError {
+    /// A value failed validation; `field` is a dotted path like `metrics.port`
+    Invalid {
+        field: String,
+        message: String,
+    },
+    Storage {
+        message: String,
+    },
+}
+
+impl SettingsError {
+    fn invalid(field: &str, message: &str) -> Self {
+        SettingsError::Invalid {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::Invalid { field, message } => {
+                write!(f, "Invalid setting `{}`: {}", field, message)
+            }
+            SettingsError::Storage { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for SettingsError {
+    fn from(message: String) -> Self {
+        SettingsError::Storage { message }
+    }
+}
+
+/// Upgrade steps, indexed by the version they upgrade *from*
+type Migration = fn(&mut serde_json::Value);
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// v0 (pre-versioning) only had default_model, auto_start_services and
+/// check_updates; the new sections are filled in by serde defaults.
+fn migrate_v0_to_v1(value: &mut serde_json::Value) {
+    value["schema_version"] = 1.into();
+}
+
+/// v1 had a single environment; its default model moves into the
+/// `default` profile
+fn migrate_v1_to_v2(value: &mut serde_json::Value) {
+    let mut profile = serde_json::to_value(Profile::default()).expect("profile serializes");
+    if let Some(model) = value
+        .as_object_mut()
+        .and_then(|settings| settings.remove("default_model"))
+    {
+        profile["default_model"] = model;
+    }
+    value["active_profile"] = profile["name"].clone();
+    value["profiles"] = serde_json::json!([profile]);
+    value["schema_version"] = 2.into();
+}
+
+/// Run every migration needed to bring `value` up to `SCHEMA_VERSION`
+fn migrate(mut value: serde_json::Value) -> Result<serde_json::Value, SettingsError> {
+    if !value.is_object() {
+        return Err(SettingsError::invalid("", "settings must be a JSON object"));
+    }
+
+    let version = value["schema_version"].as_u64().unwrap_or(0) as u32;
+    if version > SCHEMA_VERSION {
+        return Err(SettingsError::invalid(
+            "schema_version",
+            "written by a newer Dark-GPT; refusing to downgrade it",
+        ));
+    }
+
+    for (from, step) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        tracing::info!("Migrating settings from schema v{} to v{}", from, from + 1);
+        step(&mut value);
+    }
+    Ok(value)
+}
+
+/// Settings loaded from disk, held in Tauri state
+pub struct SettingsStore {
+    path: PathBuf,
+    current: Mutex<AppSettings>,
+    /// Set when the file on disk came from a newer version of the app, so we
+    /// never overwrite it with an older schema
+    read_only: Option<String>,
+}
+
+impl SettingsStore {
+    /// Load settings from `config_dir`, migrating older files. A missing file
+    /// gives defaults; an unreadable one is logged and replaced by defaults in
+    /// memory (the file is left untouched until the next save, unless it was
+    /// written by a newer version).
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(SETTINGS_FILE);
+        let mut read_only = None;
+        let settings = match read_settings(&path) {
+            Ok(Some(settings)) => settings,
+            Ok(None) => AppSettings::default(),
+            Err(e) => {
+                tracing::error!("Ignoring {:?}: {}", path, e);
+                if let SettingsError::Invalid { field, .. } = &e {
+                    if field == "schema_version" {
+                        read_only = Some(e.to_string());
+                    }
+                }
+                AppSettings::default()
+            }
+        };
+
+        Self {
+            path,
+            current: Mutex::new(settings),
+            read_only,
+        }
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.current.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Validate and persist a full settings value. Masked secrets (see
+    /// `AppSettings::redacted`) keep their stored values.
+    pub fn save(&self, settings: AppSettings) -> Result<AppSettings, SettingsError> {
+        let mut settings = AppSettings {
+            schema_version: SCHEMA_VERSION,
+            ..settings
+        };
+        settings.restore_secrets(&self.get());
+        settings.validate()?;
+        if let Some(reason) = &self.read_only {
+            return Err(format!("Settings are read-only: {}", reason).into());
+        }
+        write_settings(&self.path, &settings)?;
+
+        if let Ok(mut current) = self.current.lock() {
+            *current = settings.clone();
+        }
+        tracing::info!("Settings saved to {:?}", self.path);
+        Ok(settings)
+    }
+
+    /// Merge a partial JSON object into the current settings, then save.
+    /// Returns (previous, updated).
+    pub fn update(
+        &self,
+        patch: serde_json::Value,
+    ) -> Result<(AppSettings, AppSettings), SettingsError> {
+        let previous = self.get();
+        let serde_json::Value::Object(patch) = patch else {
+            return Err(SettingsError::invalid("", "update must be a JSON object"));
+        };
+
+        let mut merged = serde_json::to_value(&previous)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        for (key, value) in patch {
+            merge(&mut merged, serde_json::json!({ &key: value }), "")?;
+            // Deserialize after each key so a type error names its field
+            serde_json::from_value::<AppSettings>(merged.clone())
+                .map_err(|e| SettingsError::invalid(&key, &e.to_string()))?;
+        }
+
+        let updated: AppSettings = serde_json::from_value(merged)
+            .map_err(|e| format!("Failed to apply settings: {}", e))?;
+        let updated = self.save(updated)?;
+        Ok((previous, updated))
+    }
+}
+
+fn read_settings(path: &Path) -> Result<Option<AppSettings>, SettingsError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read settings: {}", e).into()),
+    };
+
+    let raw: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Corrupt settings file: {}", e))?;
+    let migrated = migrate(raw)?;
+    let settings: AppSettings =
+        serde_json::from_value(migrated).map_err(|e| format!("Invalid settings file: {}", e))?;
+    Ok(Some(settings))
+}
+
+/// Drop the secrets from a copy of settings.json in `config_dir` (a backup)
+pub fn strip_secrets(config_dir: &Path) -> Result<(), SettingsError> {
+    let path = config_dir.join(SETTINGS_FILE);
+    match read_settings(&path)? {
+        Some(settings) => write_settings(&path, &settings.without_secrets()),
+        None => Ok(()),
+    }
+}
+
+fn write_settings(path: &Path, settings: &AppSettings) -> Result<(), SettingsError> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write_atomic(path, json.as_bytes())
+        .map_err(|e| format!("Failed to write settings: {}", e).into())
+}
+
+/// Recursively merge `patch` into `target` (objects merge, everything else
+/// replaces). Keys must already exist in `target`, so a typo anywhere in the
+/// patch is reported instead of silently dropped by serde.
+fn merge(
+    target: &mut serde_json::Value,
+    patch: serde_json::Value,
+    path: &str,
+) -> Result<(), SettingsError> {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                let Some(slot) = target.get_mut(&key) else {
+                    return Err(SettingsError::invalid(&field, "unknown setting"));
+                };
+                merge(slot, value, &field)?;
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_default_model_moves_into_the_default_profile() {
+        let v1 = serde_json::json!({
+            "schema_version": 1,
+            "default_model": "llama3:70b",
+            "auto_start_services": true,
+        });
+        let settings: AppSettings = serde_json::from_value(migrate(v1).unwrap()).unwrap();
+
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert!(settings.auto_start_services);
+        assert_eq!(settings.active_profile, DEFAULT_PROFILE);
+        let profile = settings.profile();
+        assert_eq!(profile.default_model, "llama3:70b");
+        assert_eq!(profile.project, Profile::default().project);
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_duplicate_profile_projects() {
+        let default = Profile::default();
+        let settings = AppSettings {
+            profiles: vec![
+                default.clone(),
+                Profile {
+                    name: "lab".into(),
+                    ..default
+                },
+            ],
+            ..AppSettings::default()
+        };
+        let err = settings.validate().unwrap_err();
+        assert!(
+            matches!(err, SettingsError::Invalid { field, .. } if field == "profiles.1.project")
+        );
+    }
+
+    #[test]
+    fn v0_files_migrate_through_every_step() {
+        let v0 = serde_json::json!({ "default_model": "mistral", "check_updates": false });
+        let migrated = migrate(v0).unwrap();
+        assert_eq!(migrated["schema_version"], SCHEMA_VERSION);
+        assert!(migrated.get("default_model").is_none());
+
+        let settings: AppSettings = serde_json::from_value(migrated).unwrap();
+        assert!(!settings.check_updates);
+        assert_eq!(settings.profile().default_model, "mistral");
+
+        let newer = serde_json::json!({ "schema_version": SCHEMA_VERSION + 1 });
+        let err = migrate(newer).unwrap_err();
+        assert!(matches!(err, SettingsError::Invalid { field, .. } if field == "schema_version"));
+        assert!(migrate(serde_json::json!([])).is_err());
+    }
+
+    #[test]
+    fn merge_replaces_leaves_and_rejects_unknown_keys() {
+        let mut target = serde_json::json!({
+            "metrics": { "enabled": false, "port": 9090 },
+            "profiles": [1, 2],
+        });
+        merge(
+            &mut target,
+            serde_json::json!({ "metrics": { "port": 9100 }, "profiles": [3] }),
+            "",
+        )
+        .unwrap();
+        assert_eq!(
+            target,
+            serde_json::json!({
+                "metrics": { "enabled": false, "port": 9100 },
+                "profiles": [3],
+            })
+        );
+
+        let err = merge(
+            &mut target,
+            serde_json::json!({ "metrics": { "prot": 1 } }),
+            "",
+        )
+        .unwrap_err();
+        assert!(matches!(err, SettingsError::Invalid { field, .. } if field == "metrics.prot"));
+    }
+
+    #[test]
+    fn update_rejects_nested_typos() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SettingsStore::load(dir.path());
+
+        let (_, updated) = store
+            .update(serde_json::json!({ "metrics": { "port": 9200 } }))
+            .unwrap();
+        assert_eq!(updated.metrics.port, 9200);
+
+        let err = store
+            .update(serde_json::json!({ "metrics": { "enabeld": true } }))
+            .unwrap_err();
+        assert!(matches!(err, SettingsError::Invalid { field, .. } if field == "metrics.enabeld"));
+        assert_eq!(store.get().metrics.port, 9200);
+    }
+
+    #[test]
+    fn secrets_are_masked_and_kept_when_saved_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SettingsStore::load(dir.path());
+        let mut settings = store.get();
+        settings.profiles[0].api_key = Some("vllm-secret".into());
+        settings.gateway.webui_api_key = Some("webui-secret".into());
+        store.save(settings).unwrap();
+
+        let shown = store.get().redacted();
+        assert_eq!(shown.profiles[0].api_key.as_deref(), Some(REDACTED_SECRET));
+        assert_eq!(
+            shown.gateway.webui_api_key.as_deref(),
+            Some(REDACTED_SECRET)
+        );
+
+        let saved = store.save(shown).unwrap();
+        assert_eq!(saved.profiles[0].api_key.as_deref(), Some("vllm-secret"));
+        assert_eq!(saved.gateway.webui_api_key.as_deref(), Some("webui-secret"));
+
+        strip_secrets(dir.path()).unwrap();
+        let stripped = SettingsStore::load(dir.path()).get();
+        assert!(stripped.profiles[0].api_key.is_none());
+        assert!(stripped.gateway.webui_api_key.is_none());
+    }
+}
@@ -0,0 +1,325 @@
+// Setup wizard state machine
+// Persisted progress through prerequisites → installs → certs → model →
+// services → done, reconciled against what is actually on the machine
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::fs;
+use crate::utils::time::unix_now;
+
+const STATE_FILE: &str = "setup_state.json";
+/// Written by older versions once setup finished
+const LEGACY_MARKER: &str = "setup_complete";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    Prerequisites,
+    Installs,
+    Certs,
+    Model,
+    Services,
+    Done,
+}
+
+/// Steps that do work, in order (`Done` is only a terminal state)
+pub const STEPS: [SetupStep; 5] = [
+    SetupStep::Prerequisites,
+    SetupStep::Installs,
+    SetupStep::Certs,
+    SetupStep::Model,
+    SetupStep::Services,
+];
+
+impl SetupStep {
+    pub fn index(self) -> u32 {
+        STEPS.iter().position(|s| *s == self).unwrap_or(STEPS.len()) as u32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub step: SetupStep,
+    pub status: StepStatus,
+    pub error: Option<String>,
+    /// Unix seconds of the last status change
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WizardState {
+    pub current: SetupStep,
+    pub steps: Vec<StepRecord>,
+}
+
+impl Default for WizardState {
+    fn default() -> Self {
+        Self {
+            current: SetupStep::Prerequisites,
+            steps: STEPS
+                .iter()
+                .map(|step| StepRecord {
+                    step: *step,
+                    status: StepStatus::Pending,
+                    error: None,
+                    updated_at: 0,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl WizardState {
+    fn all_completed() -> Self {
+        let mut state = Self::default();
+        for step in STEPS {
+            state.set(step, StepStatus::Completed, None);
+        }
+        state
+    }
+
+    pub fn record(&self, step: SetupStep) -> Option<&StepRecord> {
+        self.steps.iter().find(|r| r.step == step)
+    }
+
+    pub fn set(&mut self, step: SetupStep, status: StepStatus, error: Option<String>) {
+        if let Some(record) = self.steps.iter_mut().find(|r| r.step == step) {
+            record.status = status;
+            record.error = error;
+            record.updated_at = unix_now();
+        }
+        self.current = self.first_incomplete();
+    }
+
+    /// Whether every step before `step` is completed
+    pub fn can_run(&self, step: SetupStep) -> bool {
+        STEPS.iter().take_while(|s| **s != step).all(|s| {
+            self.record(*s)
+                .is_some_and(|r| r.status == StepStatus::Completed)
+        })
+    }
+
+    fn first_incomplete(&self) -> SetupStep {
+        STEPS
+            .into_iter()
+            .find(|s| {
+                self.record(*s)
+                    .is_none_or(|r| r.status != StepStatus::Completed)
+            })
+            .unwrap_or(SetupStep::Done)
+    }
+
+    /// Bring the stored state in line with what detection found.
+    /// `reality` returns Some(true/false) when a step's outcome can be
+    /// checked on the system, None when only the stored state knows.
+    /// Returns true when anything changed.
+    pub fn reconcile(&mut self, reality: impl Fn(SetupStep) -> Option<bool>) -> bool {
+        let mut changed = false;
+        for step in STEPS {
+            let Some(status) = self.record(step).map(|r| r.status) else {
+                continue;
+            };
+            match (reality(step), status) {
+                (Some(true), StepStatus::Pending | StepStatus::Failed) => {
+                    tracing::info!("Setup step {:?} already satisfied, marking done", step);
+                    self.set(step, StepStatus::Completed, None);
+                    changed = true;
+                }
+                (Some(false), StepStatus::Completed) => {
+                    tracing::warn!("Setup step {:?} no longer satisfied, reopening", step);
+                    self.set(step, StepStatus::Pending, None);
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+        changed
+    }
+}
+
+/// Wizard state backed by `setup_state.json`. Held in Tauri state.
+pub struct SetupWizard {
+    dir: PathBuf,
+    state: Mutex<WizardState>,
+    busy: AtomicBool,
+}
+
+impl SetupWizard {
+    /// Load the saved state. Falls back to the legacy `setup_complete` marker,
+    /// then to a fresh wizard. A step left `running` by a crash or an
+    /// interrupted pull is reset to pending so it can be resumed.
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(STATE_FILE);
+        let mut state = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::error!("Ignoring corrupt {:?}: {}", path, e);
+                WizardState::default()
+            }),
+            Err(_) if config_dir.join(LEGACY_MARKER).exists() => {
+                tracing::info!("Migrating legacy setup_complete marker");
+                WizardState::all_completed()
+            }
+            Err(_) => WizardState::default(),
+        };
+
+        let interrupted: Vec<SetupStep> = state
+            .steps
+            .iter()
+            .filter(|r| r.status == StepStatus::Running)
+            .map(|r| r.step)
+            .collect();
+        for step in interrupted {
+            tracing::info!("Setup step {:?} was interrupted, will resume", step);
+            state.set(step, StepStatus::Pending, None);
+        }
+
+        Self {
+            dir: config_dir.to_path_buf(),
+            state: Mutex::new(state),
+            busy: AtomicBool::new(false),
+        }
+    }
+
+    pub fn snapshot(&self) -> WizardState {
+        self.state.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::SeqCst)
+    }
+
+    /// Claim the wizard for a step run; None if one is already running
+    pub fn try_begin(&self) -> Option<BusyGuard<'_>> {
+        self.busy
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| BusyGuard(&self.busy))
+    }
+
+    /// Modify the state and persist it
+    pub fn update(&self, f: impl FnOnce(&mut WizardState)) -> Result<WizardState, String> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| "Setup state lock poisoned".to_string())?;
+        f(&mut state);
+
+        let json = serde_json::to_string_pretty(&*state)
+            .map_err(|e| format!("Failed to serialize setup state: {}", e))?;
+        fs::write_atomic(&self.dir.join(STATE_FILE), json.as_bytes())
+            .map_err(|e| format!("Failed to save setup state: {}", e))?;
+        Ok(state.clone())
+    }
+
+    /// Forget all progress, including the legacy marker
+    pub fn reset(&self) -> Result<WizardState, String> {
+        for file in [STATE_FILE, LEGACY_MARKER] {
+            match std::fs::remove_file(self.dir.join(file)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(format!("Failed to remove {}: {}", file, e));
+                }
+                _ => {}
+            }
+        }
+        let state = WizardState::default();
+        if let Ok(mut current) = self.state.lock() {
+            *current = state.clone();
+        }
+        tracing::info!("Setup wizard reset");
+        Ok(state)
+    }
+}
+
+/// Releases the wizard when the step run ends (or is cancelled)
+pub struct BusyGuard<'a>(&'a AtomicBool);
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(state: &WizardState, step: SetupStep) -> StepStatus {
+        state.record(step).unwrap().status
+    }
+
+    #[test]
+    fn reconcile_follows_what_is_on_the_machine() {
+        let mut state = WizardState::default();
+        state.set(
+            SetupStep::Model,
+            StepStatus::Failed,
+            Some("pull failed".into()),
+        );
+
+        let changed = state.reconcile(|step| match step {
+            SetupStep::Prerequisites | SetupStep::Installs => Some(true),
+            SetupStep::Model => Some(true),
+            SetupStep::Certs => Some(false),
+            _ => None,
+        });
+        assert!(changed);
+        assert_eq!(status(&state, SetupStep::Installs), StepStatus::Completed);
+        assert_eq!(status(&state, SetupStep::Model), StepStatus::Completed);
+        assert!(state.record(SetupStep::Model).unwrap().error.is_none());
+        // Not done yet and not checkable: left alone
+        assert_eq!(status(&state, SetupStep::Certs), StepStatus::Pending);
+        assert_eq!(status(&state, SetupStep::Services), StepStatus::Pending);
+        assert_eq!(state.current, SetupStep::Certs);
+        assert!(!state.can_run(SetupStep::Model));
+    }
+
+    #[test]
+    fn reconcile_reopens_undone_steps_and_keeps_running_ones() {
+        let mut state = WizardState::all_completed();
+        assert_eq!(state.current, SetupStep::Done);
+        assert!(!state.reconcile(|_| None));
+
+        state.set(SetupStep::Services, StepStatus::Running, None);
+        let changed = state.reconcile(|step| match step {
+            SetupStep::Installs => Some(false),
+            SetupStep::Services => Some(true),
+            _ => None,
+        });
+        assert!(changed);
+        assert_eq!(status(&state, SetupStep::Installs), StepStatus::Pending);
+        assert_eq!(status(&state, SetupStep::Services), StepStatus::Running);
+        assert_eq!(state.current, SetupStep::Installs);
+        assert!(state.can_run(SetupStep::Installs));
+    }
+
+    #[test]
+    fn load_resumes_interrupted_steps_and_migrates_the_legacy_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(LEGACY_MARKER), "").unwrap();
+        assert_eq!(
+            SetupWizard::load(dir.path()).snapshot().current,
+            SetupStep::Done
+        );
+
+        let wizard = SetupWizard::load(dir.path());
+        wizard
+            .update(|s| s.set(SetupStep::Model, StepStatus::Running, None))
+            .unwrap();
+        let reloaded = SetupWizard::load(dir.path()).snapshot();
+        assert_eq!(status(&reloaded, SetupStep::Model), StepStatus::Pending);
+        assert_eq!(reloaded.current, SetupStep::Model);
+    }
+}
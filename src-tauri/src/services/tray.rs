@@ -0,0 +1,344 @@
+// System tray
+// Icon tinted by the aggregated health report, with stack controls, a
+// per-service status submenu and the latest model download
+
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tauri::image::Image;
+use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Listener, Manager, Wry};
+use tokio::time::{sleep, Duration};
+
+use crate::commands::docker;
+use crate::commands::health::{self, HealthReport, HealthStatus, ServiceHealth};
+use crate::services::host::Host;
+use crate::services::instance;
+use crate::services::lifecycle::{self, ExitPolicy};
+
+const TRAY_ID: &str = "main";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// One colour for the whole stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayHealth {
+    /// Every service answers
+    Healthy,
+    /// The stack is up but some service isn't
+    Degraded,
+    /// The container runtime or Ollama is down
+    Down,
+    /// Runtime and Ollama are fine, the stack isn't running
+    Stopped,
+    /// No report yet
+    Unknown,
+}
+
+impl TrayHealth {
+    pub fn from_report(report: &HealthReport) -> Self {
+        let healthy = |s: &ServiceHealth| s.status == HealthStatus::Healthy;
+        if [&report.docker, &report.ollama, &report.webui, &report.caddy]
+            .into_iter()
+            .all(healthy)
+        {
+            TrayHealth::Healthy
+        } else if !healthy(&report.docker) || !healthy(&report.ollama) {
+            TrayHealth::Down
+        } else if !healthy(&report.webui) && !healthy(&report.caddy) {
+            TrayHealth::Stopped
+        } else {
+            TrayHealth::Degraded
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TrayHealth::Healthy => "Running",
+            TrayHealth::Degraded => "Degraded",
+            TrayHealth::Down => "Down",
+            TrayHealth::Stopped => "Stopped",
+            TrayHealth::Unknown => "Checking…",
+        }
+    }
+
+    fn color(self) -> [u8; 3] {
+        match self {
+            TrayHealth::Healthy => [0x22, 0xc5, 0x5e],
+            TrayHealth::Degraded => [0xf5, 0x9e, 0x0b],
+            TrayHealth::Down => [0xef, 0x44, 0x44],
+            TrayHealth::Stopped | TrayHealth::Unknown => [0x9c, 0xa3, 0xaf],
+        }
+    }
+}
+
+/// Menu items that change at runtime. Held in Tauri state.
+pub struct Tray {
+    status: MenuItem<Wry>,
+    /// Docker, Ollama, Open WebUI, Caddy
+    services: [MenuItem<Wry>; 4],
+    pull: MenuItem<Wry>,
+    /// (model, whole percent) last shown, to skip redundant updates
+    last_pull: Mutex<Option<(String, u32)>>,
+}
+
+/// Build the tray, then keep it in sync with health checks and pulls
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let item = |id: &str, text: &str, enabled: bool| {
+        MenuItem::with_id(app, id, text, enabled, None::<&str>)
+    };
+
+    let status = item("status", "Dark-GPT: Checking…", false)?;
+    let services = [
+        item("service-docker", "Container runtime", false)?,
+        item("service-ollama", "Ollama", false)?,
+        item("service-webui", "Open WebUI", false)?,
+        item("service-caddy", "Caddy", false)?,
+    ];
+    let pull = item("pull", "No model downloads", false)?;
+    let service_items: Vec<&dyn IsMenuItem<Wry>> =
+        services.iter().map(|i| i as &dyn IsMenuItem<Wry>).collect();
+    let services_menu = Submenu::with_items(app, "Services", true, &service_items)?;
+    let quit_menu = Submenu::with_items(
+        app,
+        "Quit",
+        true,
+        &[
+            &item("quit-stop", "Stop services and quit", true)?,
+            &item("quit-leave", "Quit, leave services running", true)?,
+        ],
+    )?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &item("start", "Start stack", true)?,
+            &item("stop", "Stop stack", true)?,
+            &item("restart", "Restart stack", true)?,
+            &item("open-webui", "Open WebUI", true)?,
+            &PredefinedMenuItem::separator(app)?,
+            &services_menu,
+            &pull,
+            &PredefinedMenuItem::separator(app)?,
+            &item("show", "Show window", true)?,
+            &quit_menu,
+        ],
+    )?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(icon(app, TrayHealth::Unknown))
+        .tooltip("Dark-GPT")
+        .menu(&menu)
+        .on_menu_event(on_menu_event)
+        .build(app)?;
+
+    app.manage(Tray {
+        status,
+        services,
+        pull,
+        last_pull: Mutex::new(None),
+    });
+
+    let handle = app.clone();
+    app.listen_any("model-download-progress", move |event| {
+        if let Ok(progress) = serde_json::from_str::<PullProgress>(event.payload()) {
+            show_pull(&handle, &progress);
+        }
+    });
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&handle).await;
+            sleep(REFRESH_INTERVAL).await;
+        }
+    });
+    Ok(())
+}
+
+/// Run the health checks and update the icon and menu
+pub async fn refresh(app: &AppHandle) {
+    match health::collect_health(&app.state::<Host>()).await {
+        Ok((report, _)) => show_report(app, &report),
+        Err(e) => tracing::warn!("Tray health check failed: {}", e),
+    }
+}
+
+fn show_report(app: &AppHandle, report: &HealthReport) {
+    let Some(tray) = app.try_state::<Tray>() else {
+        return;
+    };
+    let health = TrayHealth::from_report(report);
+    let _ = tray
+        .status
+        .set_text(format!("Dark-GPT: {}", health.label()));
+    let services = [&report.docker, &report.ollama, &report.webui, &report.caddy];
+    for (item, service) in tray.services.iter().zip(services) {
+        let state = match service.status {
+            HealthStatus::Healthy => "healthy",
+            HealthStatus::Unhealthy => "unhealthy",
+            HealthStatus::Unknown => "unknown",
+        };
+        let _ = item.set_text(format!("{}: {}", service.name, state));
+    }
+
+    if let Some(icon_handle) = app.tray_by_id(TRAY_ID) {
+        let _ = icon_handle.set_icon(Some(icon(app, health)));
+        let _ = icon_handle.set_tooltip(Some(format!("Dark-GPT: {}", health.label())));
+    }
+}
+
+/// Payload of `model-download-progress` (see `ollama::DownloadProgress`)
+#[derive(Deserialize)]
+struct PullProgress {
+    model: String,
+    status: String,
+    percent: f32,
+}
+
+fn show_pull(app: &AppHandle, progress: &PullProgress) {
+    let Some(tray) = app.try_state::<Tray>() else {
+        return;
+    };
+    let percent = progress.percent.clamp(0.0, 100.0) as u32;
+    let shown = (progress.model.clone(), percent);
+    let Ok(mut last) = tray.last_pull.lock() else {
+        return;
+    };
+    if progress.status != "success" && last.as_ref() == Some(&shown) {
+        return;
+    }
+    *last = Some(shown);
+
+    let text = if progress.status == "success" {
+        format!("Downloaded {}", progress.model)
+    } else {
+        format!("Downloading {}: {}%", progress.model, percent)
+    };
+    let _ = tray.pull.set_text(text);
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let app = app.clone();
+    match event.id().as_ref() {
+        "start" => run_action(app, "Starting…", |app| async move {
+            docker::start_services(app).await.map(|_| ())
+        }),
+        "stop" => run_action(app, "Stopping…", docker::stop_services),
+        "restart" => run_action(app, "Restarting…", |app| async move {
+            docker::stop_services(app.clone()).await?;
+            docker::start_services(app).await.map(|_| ())
+        }),
+        "open-webui" => {
+            tauri::async_runtime::spawn(async move {
+                let opened = match health::webui_url(&app.state::<Host>()).await {
+                    Ok(url) => {
+                        open_url(&app, &url).map_err(|e| format!("Failed to open {}: {}", url, e))
+                    }
+                    Err(e) => Err(e.message),
+                };
+                if let Err(e) = opened {
+                    tracing::error!("Open WebUI from the tray failed: {}", e);
+                    set_status(&app, &format!("Failed: {}", e));
+                }
+            });
+        }
+        "show" => instance::focus_main_window(&app),
+        "quit-stop" => {
+            set_status(&app, "Stopping…");
+            lifecycle::quit(&app, ExitPolicy::StopStack);
+        }
+        "quit-leave" => lifecycle::quit(&app, ExitPolicy::LeaveRunning),
+        _ => {}
+    }
+}
+
+/// Run a stack action in the background, then re-check health
+fn run_action<F, Fut>(app: AppHandle, pending: &'static str, action: F)
+where
+    F: FnOnce(AppHandle) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = crate::error::AppResult<()>> + Send,
+{
+    tauri::async_runtime::spawn(async move {
+        set_status(&app, pending);
+        if let Err(e) = action(app.clone()).await {
+            tracing::error!("Tray action failed: {}", e.describe());
+            set_status(&app, &format!("Failed: {}", e.message));
+            return;
+        }
+        refresh(&app).await;
+    });
+}
+
+fn set_status(app: &AppHandle, text: &str) {
+    if let Some(tray) = app.try_state::<Tray>() {
+        let _ = tray.status.set_text(format!("Dark-GPT: {}", text));
+    }
+}
+
+#[allow(deprecated)] // tauri-plugin-shell's open is enough for an http(s) URL
+fn open_url(app: &AppHandle, url: &str) -> Result<(), String> {
+    use tauri_plugin_shell::ShellExt;
+    app.shell().open(url, None).map_err(|e| e.to_string())
+}
+
+/// The app icon with a status dot in the bottom-right corner
+fn icon(app: &AppHandle, health: TrayHealth) -> Image<'static> {
+    let (mut rgba, width, height) = match app.default_window_icon() {
+        Some(base) => (base.rgba().to_vec(), base.width(), base.height()),
+        None => (vec![0; 32 * 32 * 4], 32, 32),
+    };
+    let [r, g, b] = health.color();
+    let radius = width.min(height) as f32 * 0.22;
+    let (cx, cy) = (width as f32 - radius - 1.0, height as f32 - radius - 1.0);
+    for y in 0..height {
+        for x in 0..width {
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            let pixel = ((y * width + x) * 4) as usize;
+            if distance <= radius {
+                rgba[pixel..pixel + 4].copy_from_slice(&[r, g, b, 0xff]);
+            } else if distance <= radius + 1.5 {
+                // Dark ring so the dot reads on any icon or panel colour
+                rgba[pixel..pixel + 4].copy_from_slice(&[0x11, 0x18, 0x27, 0xff]);
+            }
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(statuses: [HealthStatus; 4]) -> HealthReport {
+        let [docker, ollama, webui, caddy] = statuses.map(|status| ServiceHealth {
+            name: String::new(),
+            status,
+            message: None,
+        });
+        HealthReport {
+            docker,
+            ollama,
+            webui,
+            caddy,
+        }
+    }
+
+    #[test]
+    fn aggregates_the_health_report() {
+        use HealthStatus::*;
+        let cases = [
+            ([Healthy, Healthy, Healthy, Healthy], TrayHealth::Healthy),
+            ([Healthy, Healthy, Unhealthy, Healthy], TrayHealth::Degraded),
+            ([Healthy, Healthy, Unhealthy, Unknown], TrayHealth::Stopped),
+            ([Unhealthy, Healthy, Unhealthy, Unhealthy], TrayHealth::Down),
+            ([Healthy, Unhealthy, Healthy, Healthy], TrayHealth::Down),
+            ([Unknown, Healthy, Unknown, Unknown], TrayHealth::Down),
+        ];
+        for (statuses, expected) in cases {
+            assert_eq!(TrayHealth::from_report(&report(statuses)), expected);
+        }
+    }
+}
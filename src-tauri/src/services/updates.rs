@@ -0,0 +1,445 @@
+// Update checker
+// Fetches a JSON release manifest (HTTP(S) URL or local file), compares it
+// with the running version and re-checks on a schedule
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::{sleep, Duration};
+
+use super::settings::{AppSettings, SettingsStore};
+use crate::utils::time::unix_now;
+
+/// Attached to every release by the `manifest` job in release.yml
+pub const DEFAULT_MANIFEST_URL: &str =
+    "https://github.com/Kikii95/dark-gpt/releases/latest/download/release-manifest.json";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+/// Manifests are a few hundred bytes; anything bigger is not one
+const MAX_MANIFEST_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateSettings {
+    /// `https://…`, `http://…`, `file://…` or an absolute path
+    pub manifest_url: String,
+    pub interval_hours: u32,
+    /// Versions the user chose to skip
+    pub dismissed_versions: Vec<String>,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            manifest_url: DEFAULT_MANIFEST_URL.to_string(),
+            interval_hours: 24,
+            dismissed_versions: Vec::new(),
+        }
+    }
+}
+
+impl UpdateSettings {
+    /// On error returns (field, message)
+    pub fn validate(&self) -> Result<(), (&'static str, &'static str)> {
+        let url = self.manifest_url.trim();
+        let supported = ["https://", "http://", "file://"]
+            .iter()
+            .any(|scheme| url.starts_with(scheme))
+            || Path::new(url).is_absolute();
+        if !supported {
+            return Err((
+                "manifest_url",
+                "must be an http(s):// or file:// URL, or an absolute path",
+            ));
+        }
+        if self.interval_hours == 0 {
+            return Err(("interval_hours", "must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Release manifest as published next to each release
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    #[serde(default)]
+    pub notes: String,
+    /// Release page or generic download link
+    pub url: String,
+    /// Installer per platform, keyed like `windows-x86_64` or `linux-aarch64`
+    #[serde(default)]
+    pub platforms: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    pub published_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    /// The manifest version is newer than the running one
+    pub available: bool,
+    /// The user skipped this version
+    pub dismissed: bool,
+    pub notes: String,
+    pub download_url: String,
+    pub published_at: Option<String>,
+    /// Unix seconds
+    pub checked_at: u64,
+}
+
+impl UpdateInfo {
+    /// Worth telling the user about
+    pub fn should_notify(&self) -> bool {
+        self.available && !self.dismissed
+    }
+}
+
+/// Fetch and parse the manifest
+pub async fn fetch_manifest(url: &str) -> Result<ReleaseManifest, String> {
+    let url = url.trim();
+    let body = if url.starts_with("http://") || url.starts_with("https://") {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let mut response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch release manifest: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Release manifest returned HTTP {}",
+                response.status()
+            ));
+        }
+        let too_large = || "Release manifest is too large".to_string();
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_MANIFEST_BYTES as u64)
+        {
+            return Err(too_large());
+        }
+        // The length header is optional (chunked), so cap while reading too
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Failed to read release manifest: {}", e))?
+        {
+            if body.len() + chunk.len() > MAX_MANIFEST_BYTES {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        body
+    } else {
+        let path = url.strip_prefix("file://").unwrap_or(url);
+        tokio::fs::read(path)
+            .await
+            .map_err(|e| format!("Failed to read release manifest {}: {}", path, e))?
+    };
+
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid release manifest: {}", e))
+}
+
+/// Compare a manifest with the running version
+pub fn evaluate(
+    manifest: &ReleaseManifest,
+    current: &str,
+    dismissed_versions: &[String],
+) -> Result<UpdateInfo, String> {
+    let current_version = Version::parse(current)
+        .map_err(|e| format!("Invalid current version {}: {}", current, e))?;
+    let latest = manifest.version.trim().trim_start_matches('v');
+    let latest_version = Version::parse(latest).map_err(|e| {
+        format!(
+            "Invalid version in release manifest {}: {}",
+            manifest.version, e
+        )
+    })?;
+
+    let platform = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH);
+    let download_url = manifest
+        .platforms
+        .get(&platform)
+        .unwrap_or(&manifest.url)
+        .clone();
+
+    Ok(UpdateInfo {
+        current_version: current_version.to_string(),
+        latest_version: latest_version.to_string(),
+        available: latest_version > current_version,
+        dismissed: dismissed_versions
+            .iter()
+            .any(|v| Version::parse(v.trim_start_matches('v')).is_ok_and(|v| v == latest_version)),
+        notes: manifest.notes.clone(),
+        download_url,
+        published_at: manifest.published_at.clone(),
+        checked_at: unix_now(),
+    })
+}
+
+/// One check against the configured manifest. Refuses to run in offline mode.
+pub async fn check(settings: &AppSettings) -> Result<UpdateInfo, String> {
+    if settings.offline {
+        return Err("Offline mode is on; update checks are disabled".to_string());
+    }
+    let manifest = fetch_manifest(&settings.updates.manifest_url).await?;
+    evaluate(
+        &manifest,
+        env!("CARGO_PKG_VERSION"),
+        &settings.updates.dismissed_versions,
+    )
+}
+
+/// Scheduled checks and the last result. Held in Tauri state.
+#[derive(Default)]
+pub struct UpdateChecker {
+    last: Mutex<Option<UpdateInfo>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl UpdateChecker {
+    pub fn last(&self) -> Option<UpdateInfo> {
+        self.last.lock().ok().and_then(|l| l.clone())
+    }
+
+    /// Record a check result, emitting `update-available` when it's news
+    pub fn record(&self, app: &AppHandle, info: UpdateInfo) {
+        let notify = info.should_notify()
+            && self.last().is_none_or(|last| {
+                last.latest_version != info.latest_version || !last.should_notify()
+            });
+        if let Ok(mut last) = self.last.lock() {
+            *last = Some(info.clone());
+        }
+        if notify {
+            tracing::info!("Update available: {}", info.latest_version);
+            let _ = app.emit("update-available", &info);
+        }
+    }
+
+    /// Mark a version as dismissed in the cached result
+    pub fn dismiss(&self, version: &str) {
+        if let Ok(mut last) = self.last.lock() {
+            if let Some(info) = last.as_mut().filter(|i| i.latest_version == version) {
+                info.dismissed = true;
+            }
+        }
+    }
+
+    /// (Re)start or stop the schedule for the given settings
+    pub fn apply(&self, app: &AppHandle, settings: &AppSettings) {
+        if let Some(task) = self.task.lock().ok().and_then(|mut t| t.take()) {
+            task.abort();
+        }
+
+        if !settings.check_updates || settings.offline {
+            tracing::info!("Scheduled update checks disabled");
+            return;
+        }
+        let interval = Duration::from_secs(u64::from(settings.updates.interval_hours) * 3600);
+        tracing::info!(
+            "Checking for updates every {}h from {}",
+            settings.updates.interval_hours,
+            settings.updates.manifest_url
+        );
+        let handle = tauri::async_runtime::spawn(schedule(app.clone(), interval));
+        if let Ok(mut task) = self.task.lock() {
+            *task = Some(handle);
+        }
+    }
+}
+
+async fn schedule(app: AppHandle, interval: Duration) {
+    loop {
+        // Re-read settings each round so dismissals are honoured
+        let settings = app.state::<SettingsStore>().get();
+        match check(&settings).await {
+            Ok(info) => app.state::<UpdateChecker>().record(&app, info),
+            Err(e) => tracing::warn!("Update check failed: {}", e),
+        }
+        sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const MANIFEST: &str = r#"{
+        "version": "v99.1.0",
+        "notes": "Faster model pulls",
+        "url": "https://example.com/releases/99.1.0",
+        "published_at": "2026-01-01T00:00:00Z"
+    }"#;
+
+    /// Serve one fixed response to every connection; returns the base URL
+    async fn stub_server(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn settings_for(url: &str) -> AppSettings {
+        AppSettings {
+            updates: UpdateSettings {
+                manifest_url: url.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_newer_version_from_http_manifest() {
+        let base = stub_server("200 OK", MANIFEST).await;
+        let info = check(&settings_for(&format!("{}/manifest.json", base)))
+            .await
+            .unwrap();
+        assert!(info.available);
+        assert!(!info.dismissed);
+        assert!(info.should_notify());
+        assert_eq!(info.latest_version, "99.1.0");
+        assert_eq!(info.current_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.notes, "Faster model pulls");
+        assert_eq!(info.download_url, "https://example.com/releases/99.1.0");
+    }
+
+    #[tokio::test]
+    async fn dismissed_version_is_not_notified() {
+        let base = stub_server("200 OK", MANIFEST).await;
+        let mut settings = settings_for(&base);
+        settings.updates.dismissed_versions = vec!["99.1.0".into()];
+        let info = check(&settings).await.unwrap();
+        assert!(info.available);
+        assert!(info.dismissed);
+        assert!(!info.should_notify());
+    }
+
+    #[tokio::test]
+    async fn http_errors_and_bad_manifests_fail() {
+        let missing = stub_server("404 Not Found", "").await;
+        let err = check(&settings_for(&missing)).await.unwrap_err();
+        assert!(err.contains("404"), "{}", err);
+
+        let garbage = stub_server("200 OK", "<html>not json</html>").await;
+        let err = check(&settings_for(&garbage)).await.unwrap_err();
+        assert!(err.starts_with("Invalid release manifest"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn oversized_manifests_are_refused() {
+        let huge: &'static str = "x".repeat(MAX_MANIFEST_BYTES + 1).leak();
+        let base = stub_server("200 OK", huge).await;
+        let err = check(&settings_for(&base)).await.unwrap_err();
+        assert_eq!(err, "Release manifest is too large");
+    }
+
+    #[tokio::test]
+    async fn offline_mode_skips_the_network() {
+        let mut settings = settings_for("http://127.0.0.1:9/unreachable");
+        settings.offline = true;
+        let err = check(&settings).await.unwrap_err();
+        assert!(err.contains("Offline"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn reads_manifest_from_local_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        std::fs::write(&path, MANIFEST).unwrap();
+
+        let url = format!("file://{}", path.display());
+        assert_eq!(fetch_manifest(&url).await.unwrap().version, "v99.1.0");
+        let plain = path.to_string_lossy();
+        assert_eq!(fetch_manifest(&plain).await.unwrap().version, "v99.1.0");
+    }
+
+    #[test]
+    fn compares_semver_not_strings() {
+        let manifest = |version: &str| ReleaseManifest {
+            version: version.into(),
+            notes: String::new(),
+            url: "https://example.com".into(),
+            platforms: Default::default(),
+            published_at: None,
+        };
+        assert!(
+            evaluate(&manifest("0.10.0"), "0.9.3", &[])
+                .unwrap()
+                .available
+        );
+        assert!(
+            !evaluate(&manifest("0.9.3"), "0.9.3", &[])
+                .unwrap()
+                .available
+        );
+        assert!(
+            !evaluate(&manifest("0.7.0-beta.1"), "0.7.0", &[])
+                .unwrap()
+                .available
+        );
+        assert!(
+            evaluate(&manifest("0.7.0"), "0.7.0-beta.1", &[])
+                .unwrap()
+                .available
+        );
+        assert!(evaluate(&manifest("latest"), "0.7.0", &[]).is_err());
+    }
+
+    #[test]
+    fn picks_platform_installer_when_listed() {
+        let platform = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH);
+        let manifest = ReleaseManifest {
+            version: "99.0.0".into(),
+            notes: String::new(),
+            url: "https://example.com/release".into(),
+            platforms: [(platform, "https://example.com/installer".into())].into(),
+            published_at: None,
+        };
+        let info = evaluate(&manifest, "0.1.0", &[]).unwrap();
+        assert_eq!(info.download_url, "https://example.com/installer");
+    }
+
+    #[test]
+    fn validates_manifest_location() {
+        let with_url = |url: &str| UpdateSettings {
+            manifest_url: url.into(),
+            ..Default::default()
+        };
+        assert!(UpdateSettings::default().validate().is_ok());
+        assert!(with_url("file:///srv/mirror/manifest.json")
+            .validate()
+            .is_ok());
+        assert_eq!(
+            with_url("ftp://mirror/manifest.json")
+                .validate()
+                .unwrap_err()
+                .0,
+            "manifest_url"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+// In-memory ring buffer of recent log lines
+// Plugged into tracing as a second fmt writer so diagnostics can include them
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Keeps the last `capacity` formatted log lines
+#[derive(Clone)]
+pub struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl RecentLogs {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Copy of the buffered lines, oldest first
+    pub fn snapshot(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn push(&self, text: &str) {
+        let Ok(mut lines) = self.lines.lock() else {
+            return;
+        };
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for RecentLogs {
+    type Writer = RecentLogsWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentLogsWriter {
+            logs: self.clone(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// One writer per event; the line is committed when tracing drops it
+pub struct RecentLogsWriter {
+    logs: RecentLogs,
+    buf: Vec<u8>,
+}
+
+impl Write for RecentLogsWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecentLogsWriter {
+    fn drop(&mut self) {
+        self.logs.push(&String::from_utf8_lossy(&self.buf));
+    }
+}
@@ -1,6 +1,15 @@
 // Utils module
 // Utility functions
 
+pub mod fs;
+pub mod log_buffer;
+pub mod paths;
+pub mod redact;
+#[cfg(test)]
+pub mod testing;
+pub mod time;
+#[cfg(test)]
+pub mod ts_bindings;
+
 // TODO: Add utils as needed
-// pub mod paths;
 // pub mod platform;
@@ -0,0 +1,76 @@
+// App directories
+// Resolved from a running app, or without one for the CLI. Both follow
+// Tauri's layout, so the CLI and the desktop app share settings and the
+// bundled compose file.
+
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Context, Env, Manager, Runtime};
+
+#[derive(Debug, Clone)]
+pub struct AppPaths {
+    /// Bundled resources (docker/docker-compose.yml, Caddyfile)
+    pub resource_dir: PathBuf,
+    /// settings.json and the setup wizard state
+    pub config_dir: PathBuf,
+    /// Benchmark history and other app data
+    pub data_dir: PathBuf,
+    /// Rolling log files and the crash log
+    pub log_dir: PathBuf,
+}
+
+impl AppPaths {
+    pub fn from_app<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let path = app.path();
+        Ok(Self {
+            resource_dir: path
+                .resource_dir()
+                .map_err(|e| format!("Failed to get resource dir: {}", e))?,
+            config_dir: path
+                .app_config_dir()
+                .map_err(|e| format!("Failed to get config dir: {}", e))?,
+            data_dir: path
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get data dir: {}", e))?,
+            log_dir: path
+                .app_log_dir()
+                .map_err(|e| format!("Failed to get log dir: {}", e))?,
+        })
+    }
+
+    /// Same directories as `from_app`, without building the app (no webview)
+    pub fn headless<R: Runtime>(context: &Context<R>) -> Result<Self, String> {
+        let identifier = &context.config().identifier;
+        let resource_dir =
+            tauri::utils::platform::resource_dir(context.package_info(), &Env::default())
+                .map_err(|e| format!("Failed to get resource dir: {}", e))?;
+        let config_dir = dirs::config_dir().ok_or("No config directory for this user")?;
+        let data_dir = dirs::data_dir().ok_or("No data directory for this user")?;
+        #[cfg(target_os = "macos")]
+        let log_dir = dirs::home_dir()
+            .ok_or("No home directory for this user")?
+            .join("Library/Logs")
+            .join(identifier);
+        #[cfg(not(target_os = "macos"))]
+        let log_dir = dirs::data_local_dir()
+            .ok_or("No local data directory for this user")?
+            .join(identifier)
+            .join("logs");
+        Ok(Self {
+            resource_dir,
+            config_dir: config_dir.join(identifier),
+            data_dir: data_dir.join(identifier),
+            log_dir,
+        })
+    }
+
+    pub fn compose_file(&self) -> PathBuf {
+        self.resource_dir.join("docker").join("docker-compose.yml")
+    }
+
+    /// mkcert certificates Caddy serves; the resource dir is read-only once
+    /// installed, so they live with the app data
+    pub fn certs_dir(&self) -> PathBuf {
+        self.data_dir.join("certs")
+    }
+}
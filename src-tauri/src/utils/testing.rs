@@ -0,0 +1,237 @@
+// Test doubles
+// A scripted `CommandRunner` and an in-process stub of the Ollama API, so
+// commands can be tested against `FakeRunner::host(&stub.url)` without
+// Docker or Ollama installed.
+
+use std::process::{ExitStatus, Output};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::response::Response;
+use axum::Router;
+
+use crate::services::host::{Cmd, CommandRunner, Host};
+
+struct Rule {
+    prefix: String,
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+/// A command the fake was asked to run
+#[derive(Debug, Clone)]
+pub struct Call {
+    /// Program and arguments, space separated
+    pub line: String,
+    pub envs: Vec<(String, String)>,
+}
+
+/// Answers commands from a script: the first rule whose prefix matches the
+/// command line wins. Unscripted commands fail as if the program were
+/// missing.
+#[derive(Default)]
+pub struct FakeRunner {
+    rules: Mutex<Vec<Rule>>,
+    installed: Mutex<Vec<String>>,
+    calls: Mutex<Vec<Call>>,
+}
+
+impl FakeRunner {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Commands starting with `prefix` succeed and print `stdout`
+    pub fn on(&self, prefix: &str, stdout: &str) -> &Self {
+        self.rule(prefix, 0, stdout, "")
+    }
+
+    /// Commands starting with `prefix` exit 1 and print `stderr`
+    pub fn fail(&self, prefix: &str, stderr: &str) -> &Self {
+        self.rule(prefix, 1, "", stderr)
+    }
+
+    /// Put `program` on the fake PATH
+    pub fn installed(&self, program: &str) -> &Self {
+        self.installed.lock().unwrap().push(program.to_string());
+        self
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// The calls whose command line starts with `prefix`
+    pub fn calls_to(&self, prefix: &str) -> Vec<Call> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.line.starts_with(prefix))
+            .collect()
+    }
+
+    pub fn host(self: &Arc<Self>, ollama_url: &str) -> Host {
+        Host::new(self.clone(), Some(ollama_url.to_string()))
+    }
+
+    fn rule(&self, prefix: &str, code: i32, stdout: &str, stderr: &str) -> &Self {
+        self.rules.lock().unwrap().push(Rule {
+            prefix: prefix.to_string(),
+            code,
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+        });
+        self
+    }
+}
+
+#[async_trait]
+impl CommandRunner for FakeRunner {
+    async fn output(&self, cmd: &Cmd) -> std::io::Result<Output> {
+        let line = cmd.line();
+        self.calls.lock().unwrap().push(Call {
+            line: line.clone(),
+            envs: cmd.get_envs().to_vec(),
+        });
+        let rules = self.rules.lock().unwrap();
+        let rule = rules
+            .iter()
+            .find(|rule| line.starts_with(&rule.prefix))
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("not scripted: {}", line),
+                )
+            })?;
+        Ok(Output {
+            status: exit_status(rule.code),
+            stdout: rule.stdout.clone().into_bytes(),
+            stderr: rule.stderr.clone().into_bytes(),
+        })
+    }
+
+    fn on_path(&self, program: &str) -> bool {
+        self.installed.lock().unwrap().iter().any(|p| p == program)
+    }
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+/// A request the stub received
+#[derive(Debug, Clone)]
+pub struct Received {
+    pub method: Method,
+    pub path: String,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+struct Route {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+#[derive(Default)]
+struct Routes {
+    responses: Vec<Route>,
+    received: Vec<Received>,
+}
+
+/// Ollama on a random loopback port, answering canned responses. Routes
+/// not set up return 404.
+pub struct StubOllama {
+    /// Base URL to hand to `FakeRunner::host`
+    pub url: String,
+    routes: Arc<Mutex<Routes>>,
+}
+
+impl StubOllama {
+    pub async fn start() -> Self {
+        let routes = Arc::new(Mutex::new(Routes::default()));
+        let router = Router::new().fallback(respond).with_state(routes.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+        Self { url, routes }
+    }
+
+    /// Answer `method path` with `status` and `body` (JSON, or NDJSON for
+    /// streaming endpoints)
+    pub fn route(&self, method: Method, path: &str, status: u16, body: &str) -> &Self {
+        self.route_with_headers(method, path, status, &[], body)
+    }
+
+    /// `route` with extra response headers
+    pub fn route_with_headers(
+        &self,
+        method: Method,
+        path: &str,
+        status: u16,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> &Self {
+        self.routes.lock().unwrap().responses.push(Route {
+            method,
+            path: path.to_string(),
+            status: StatusCode::from_u16(status).unwrap(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: body.to_string(),
+        });
+        self
+    }
+
+    pub fn received(&self) -> Vec<Received> {
+        self.routes.lock().unwrap().received.clone()
+    }
+}
+
+async fn respond(State(routes): State<Arc<Mutex<Routes>>>, request: Request) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let headers = request.headers().clone();
+    let body = axum::body::to_bytes(request.into_body(), usize::MAX)
+        .await
+        .unwrap_or_default();
+
+    let mut routes = routes.lock().unwrap();
+    routes.received.push(Received {
+        method: method.clone(),
+        path: path.clone(),
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    });
+    let (status, headers, body) = routes
+        .responses
+        .iter()
+        .find(|route| route.method == method && route.path == path)
+        .map(|route| (route.status, route.headers.clone(), route.body.clone()))
+        .unwrap_or((StatusCode::NOT_FOUND, Vec::new(), String::new()));
+    let mut response = Response::builder()
+        .status(status)
+        .header("content-type", "application/json");
+    for (name, value) in headers {
+        response = response.header(name, value);
+    }
+    response.body(Body::from(body)).unwrap()
+}
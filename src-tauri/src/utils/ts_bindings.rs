@@ -0,0 +1,133 @@
+// TypeScript bindings
+// Renders a JSON schema (from schemars) as TypeScript declarations. Covers
+// what our serialized types use: objects, arrays, string/number/boolean,
+// nullable fields, string enums and tagged unions.
+
+use schemars::schema::RootSchema;
+use serde_json::Value;
+
+const HEADER: &str =
+    "// Generated from the Rust types by `UPDATE_BINDINGS=1 cargo test`. Do not edit.\n";
+
+pub fn render(root: &RootSchema) -> String {
+    let root = serde_json::to_value(root).expect("schema serializes");
+    let mut out = String::from(HEADER);
+    if let Some(title) = root["title"].as_str() {
+        out.push('\n');
+        out.push_str(&declaration(title, &root));
+    }
+    if let Some(definitions) = root["definitions"].as_object() {
+        let mut names: Vec<&String> = definitions.keys().collect();
+        names.sort();
+        for name in names {
+            out.push('\n');
+            out.push_str(&declaration(name, &definitions[name]));
+        }
+    }
+    out
+}
+
+fn declaration(name: &str, schema: &Value) -> String {
+    let mut out = doc(schema, "");
+    if schema["properties"].is_object() {
+        out.push_str(&format!(
+            "export interface {} {}\n",
+            name,
+            object(schema, "")
+        ));
+    } else if let Some(variants) = union_members(schema) {
+        out.push_str(&format!("export type {} =\n", name));
+        for variant in variants {
+            out.push_str(&doc(variant, "  "));
+            out.push_str(&format!("  | {}\n", ty(variant, "  ")));
+        }
+        out.pop();
+        out.push_str(";\n");
+    } else {
+        out.push_str(&format!("export type {} = {};\n", name, ty(schema, "")));
+    }
+    out
+}
+
+/// Serde always writes every field (None as null), so all properties are
+/// required on the TypeScript side
+fn object(schema: &Value, indent: &str) -> String {
+    let Some(properties) = schema["properties"].as_object() else {
+        return "Record<string, never>".to_string();
+    };
+    let inner = format!("{}  ", indent);
+    let mut out = String::from("{\n");
+    for (name, property) in properties {
+        out.push_str(&doc(property, &inner));
+        out.push_str(&format!("{}{}: {};\n", inner, name, ty(property, &inner)));
+    }
+    out.push_str(indent);
+    out.push('}');
+    out
+}
+
+fn union_members(schema: &Value) -> Option<&Vec<Value>> {
+    schema["oneOf"]
+        .as_array()
+        .or_else(|| schema["anyOf"].as_array())
+}
+
+fn ty(schema: &Value, indent: &str) -> String {
+    if let Some(reference) = schema["$ref"].as_str() {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        return values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    if let Some(variants) = union_members(schema) {
+        return variants
+            .iter()
+            .map(|v| ty(v, indent))
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    if types.is_empty() {
+        return "unknown".to_string();
+    }
+    types
+        .into_iter()
+        .map(|t| match t {
+            "string" => "string".to_string(),
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => {
+                let item = ty(&schema["items"], indent);
+                if item.contains(' ') {
+                    format!("({})[]", item)
+                } else {
+                    format!("{}[]", item)
+                }
+            }
+            "object" => object(schema, indent),
+            _ => "unknown".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn doc(schema: &Value, indent: &str) -> String {
+    match schema["description"].as_str() {
+        Some(text) => format!("{}/** {} */\n", indent, text.replace('\n', " ")),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,81 @@
+// Generated from the Rust types by `UPDATE_BINDINGS=1 cargo test`. Do not edit.
+
+export interface AppError {
+  /** Underlying error (stderr, OS error) */
+  cause: string | null;
+  code: ErrorCode;
+  /** What the app was doing, e.g. "starting services" */
+  context: string | null;
+  details: ErrorDetails | null;
+  /** What went wrong, in one sentence */
+  message: string;
+  /** What the user can do about it */
+  remediation: string | null;
+}
+
+/** Stable, machine-readable error codes. Never rename one: the frontend matches on them. */
+export type ErrorCode =
+  /** The container runtime CLI (Docker or Podman) is missing */
+  | "DockerNotInstalled"
+  /** The container engine is installed but not answering */
+  | "DaemonDown"
+  /** A compose command ran and failed */
+  | "ComposeFailed"
+  /** A port the stack publishes is held by something else */
+  | "PortInUse"
+  /** Nothing answers on the Ollama API */
+  | "OllamaUnreachable"
+  /** The requested model isn't installed or doesn't exist */
+  | "ModelNotFound"
+  /** An operation didn't finish in time */
+  | "Timeout"
+  /** Filesystem or process I/O */
+  | "Io"
+  /** An HTTP request failed (downloads, update checks) */
+  | "Network"
+  /** A downloaded file failed its checksum or signature check */
+  | "IntegrityCheckFailed"
+  /** A value from the user or a setting was rejected */
+  | "InvalidInput"
+  /** Administrator authorization was refused or cancelled */
+  | "PermissionDenied"
+  /** Another operation is already running */
+  | "Busy"
+  /** Stopped because the app is closing */
+  | "Cancelled"
+  /** Anything not classified above */
+  | "Internal";
+
+/** Extra data for codes that carry more than a message */
+export type ErrorDetails =
+  /** With `PortInUse`: each conflict and how to fix it */
+  | {
+    conflicts: PortConflict[];
+    kind: "port_conflicts";
+  }
+  /** With `InvalidInput`: dotted path of the rejected field */
+  | {
+    field: string;
+    kind: "field";
+  };
+
+export interface PortConflict {
+  holder: PortHolder;
+  port: number;
+  service: string;
+  suggestion: string;
+}
+
+export type PortHolder =
+  | {
+    kind: "process";
+    name: string;
+    pid: number;
+  }
+  | {
+    kind: "container";
+    name: string;
+  }
+  | {
+    kind: "unknown";
+  };